@@ -11,3 +11,4 @@
 
 #[cfg(featFure = "unstable")]
 pub mod sha256;
+pub mod utilities;
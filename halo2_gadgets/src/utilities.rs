@@ -0,0 +1,33 @@
+//! Utility gadgets.
+
+use ff::PrimeField;
+use halo2_proofs::arithmetic::FieldExt;
+
+pub mod bit_decomposition;
+
+/// Decomposes `value` into `num_words` little-endian words of `word_bits` bits each.
+///
+/// Bits of `value` above `word_bits * num_words` are discarded. This assumes that the
+/// byte representation of `F` is little-endian, as it is for the BN-256 fields.
+///
+/// # Panics
+///
+/// Panics if `word_bits` is larger than 64.
+pub fn decompose<F: FieldExt>(value: F, word_bits: usize, num_words: usize) -> Vec<F> {
+    assert!(word_bits <= 64);
+
+    let repr = value.to_repr();
+    let bytes = repr.as_ref();
+    let bit = |i: usize| {
+        bytes
+            .get(i / 8)
+            .map_or(0, |byte| ((byte >> (i % 8)) & 1) as u64)
+    };
+
+    (0..num_words)
+        .map(|word| {
+            let word = (0..word_bits).fold(0u64, |acc, i| acc | (bit(word * word_bits + i) << i));
+            F::from(word)
+        })
+        .collect()
+}
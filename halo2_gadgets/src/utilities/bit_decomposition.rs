@@ -0,0 +1,271 @@
+//! A chip that decomposes a field element into bits.
+
+use std::marker::PhantomData;
+
+use super::decompose;
+use ff::Field;
+use halo2_proofs::{
+    arithmetic::FieldExt,
+    circuit::{AssignedCell, Chip, Layouter},
+    plonk::{Advice, Column, ConstraintSystem, Error, Expression, Selector},
+    poly::Rotation,
+};
+
+/// Configuration for a [`BitDecompositionChip`].
+#[derive(Clone, Debug)]
+pub struct BitDecompositionConfig {
+    /// The column that the decomposed number is copied into.
+    value: Column<Advice>,
+    /// One column per bit, least significant first.
+    bits: Vec<Column<Advice>>,
+    q_decompose: Selector,
+}
+
+/// A chip that decomposes a number into `bits.len()` bits, constraining each bit to be
+/// boolean and their weighted sum to equal the number.
+///
+/// The number and its bits are laid out on a single row, so a number that does not fit
+/// in the configured number of bits cannot be decomposed.
+#[derive(Clone, Debug)]
+pub struct BitDecompositionChip<F: FieldExt> {
+    config: BitDecompositionConfig,
+    _marker: PhantomData<F>,
+}
+
+impl<F: FieldExt> Chip<F> for BitDecompositionChip<F> {
+    type Config = BitDecompositionConfig;
+    type Loaded = ();
+
+    fn config(&self) -> &Self::Config {
+        &self.config
+    }
+
+    fn loaded(&self) -> &Self::Loaded {
+        &()
+    }
+}
+
+impl<F: FieldExt> BitDecompositionChip<F> {
+    /// Constructs a chip from its configuration.
+    pub fn construct(config: BitDecompositionConfig) -> Self {
+        BitDecompositionChip {
+            config,
+            _marker: PhantomData,
+        }
+    }
+
+    /// Configures the chip to decompose numbers copied into `value` into one bit per
+    /// column of `bits`, least significant first.
+    ///
+    /// Equality constraints are enabled on `value`.
+    pub fn configure(
+        meta: &mut ConstraintSystem<F>,
+        value: Column<Advice>,
+        bits: Vec<Column<Advice>>,
+    ) -> BitDecompositionConfig {
+        meta.enable_equality(value);
+        let q_decompose = meta.selector();
+
+        meta.create_gate("bit decomposition", |meta| {
+            let q_decompose = meta.query_selector(q_decompose);
+            let value = meta.query_advice(value, Rotation::cur());
+            let bits = bits
+                .iter()
+                .map(|column| meta.query_advice(*column, Rotation::cur()))
+                .collect::<Vec<_>>();
+
+            let (recomposed, _) = bits.iter().fold(
+                (Expression::Constant(F::zero()), F::one()),
+                |(acc, coeff), bit| (acc + bit.clone() * coeff, coeff.double()),
+            );
+
+            std::iter::once(("recomposition", q_decompose.clone() * (recomposed - value)))
+                .chain(bits.into_iter().map(move |bit| {
+                    (
+                        "boolean bit",
+                        q_decompose.clone() * bit.clone() * (Expression::Constant(F::one()) - bit),
+                    )
+                }))
+                .collect::<Vec<_>>()
+        });
+
+        BitDecompositionConfig {
+            value,
+            bits,
+            q_decompose,
+        }
+    }
+
+    /// Decomposes `number` into bits, least significant first.
+    pub fn decompose(
+        &self,
+        layouter: impl Layouter<F>,
+        number: &AssignedCell<F, F>,
+    ) -> Result<Vec<AssignedCell<F, F>>, Error> {
+        let bits = number
+            .value()
+            .map(|value| decompose(*value, 1, self.config.bits.len()));
+        self.assign(layouter, number, bits)
+    }
+
+    /// Assigns `number` and the given witness `bits` to a decomposition row.
+    fn assign(
+        &self,
+        mut layouter: impl Layouter<F>,
+        number: &AssignedCell<F, F>,
+        bits: Option<Vec<F>>,
+    ) -> Result<Vec<AssignedCell<F, F>>, Error> {
+        let config = &self.config;
+
+        layouter.assign_region(
+            || "bit decomposition",
+            |mut region| {
+                config.q_decompose.enable(&mut region, 0)?;
+                number.copy_advice(|| "value", &mut region, config.value, 0)?;
+
+                config
+                    .bits
+                    .iter()
+                    .enumerate()
+                    .map(|(i, column)| {
+                        region.assign_advice(
+                            || format!("bit {}", i),
+                            *column,
+                            0,
+                            || bits.as_ref().map(|bits| bits[i]).ok_or(Error::Synthesis),
+                        )
+                    })
+                    .collect()
+            },
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{super::decompose, BitDecompositionChip, BitDecompositionConfig};
+    use ff::Field;
+    use halo2_proofs::{
+        circuit::{Layouter, SimpleFloorPlanner},
+        dev::MockProver,
+        pairing::bn256::Fr,
+        plonk::{Advice, Circuit, Column, ConstraintSystem, Error},
+    };
+
+    #[test]
+    fn decompose_words() {
+        let value = Fr::from(0xa5u64);
+        assert_eq!(
+            decompose(value, 1, 8),
+            [1u64, 0, 1, 0, 0, 1, 0, 1]
+                .iter()
+                .map(|bit| Fr::from(*bit))
+                .collect::<Vec<_>>()
+        );
+        assert_eq!(
+            decompose(value, 4, 3),
+            vec![Fr::from(0x5u64), Fr::from(0xau64), Fr::zero()]
+        );
+    }
+
+    struct MyCircuit {
+        value: Option<Fr>,
+        /// Overrides the bits witnessed by the chip, to test inconsistent witnesses.
+        bits: Option<Vec<Fr>>,
+    }
+
+    impl Circuit<Fr> for MyCircuit {
+        type Config = (Column<Advice>, BitDecompositionConfig);
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            MyCircuit {
+                value: None,
+                bits: None,
+            }
+        }
+
+        fn configure(meta: &mut ConstraintSystem<Fr>) -> Self::Config {
+            let number = meta.advice_column();
+            meta.enable_equality(number);
+            let value = meta.advice_column();
+            let bits = (0..8).map(|_| meta.advice_column()).collect();
+
+            (number, BitDecompositionChip::configure(meta, value, bits))
+        }
+
+        fn synthesize(
+            &self,
+            (number, config): Self::Config,
+            mut layouter: impl Layouter<Fr>,
+        ) -> Result<(), Error> {
+            let chip = BitDecompositionChip::construct(config);
+
+            let number = layouter.assign_region(
+                || "load number",
+                |mut region| {
+                    region.assign_advice(
+                        || "number",
+                        number,
+                        0,
+                        || self.value.ok_or(Error::Synthesis),
+                    )
+                },
+            )?;
+
+            let bits = match &self.bits {
+                Some(bits) => chip.assign(
+                    layouter.namespace(|| "decompose"),
+                    &number,
+                    Some(bits.clone()),
+                )?,
+                None => chip.decompose(layouter.namespace(|| "decompose"), &number)?,
+            };
+            assert_eq!(bits.len(), 8);
+
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn bit_decomposition() {
+        let circuit = MyCircuit {
+            value: Some(Fr::from(0xa5u64)),
+            bits: None,
+        };
+        let prover = MockProver::run(4, &circuit, vec![]).unwrap();
+        assert_eq!(prover.verify(), Ok(()));
+    }
+
+    #[test]
+    fn inconsistent_witness() {
+        // The bits of 0xa4 do not recompose to 0xa5.
+        let circuit = MyCircuit {
+            value: Some(Fr::from(0xa5u64)),
+            bits: Some(decompose(Fr::from(0xa4u64), 1, 8)),
+        };
+        let prover = MockProver::run(4, &circuit, vec![]).unwrap();
+        assert!(prover.verify().is_err());
+
+        // A non-boolean bit is rejected even when the recomposition holds.
+        let mut bits = decompose(Fr::from(0xa5u64), 1, 8);
+        bits[0] = Fr::from(3u64);
+        bits[1] = -Fr::one();
+        let circuit = MyCircuit {
+            value: Some(Fr::from(0xa5u64)),
+            bits: Some(bits),
+        };
+        let prover = MockProver::run(4, &circuit, vec![]).unwrap();
+        assert!(prover.verify().is_err());
+    }
+
+    #[test]
+    fn value_too_large() {
+        let circuit = MyCircuit {
+            value: Some(Fr::from(0x1a5u64)),
+            bits: None,
+        };
+        let prover = MockProver::run(4, &circuit, vec![]).unwrap();
+        assert!(prover.verify().is_err());
+    }
+}
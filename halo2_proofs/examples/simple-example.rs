@@ -3,7 +3,7 @@ use std::marker::PhantomData;
 use halo2_proofs::{
     arithmetic::FieldExt,
     circuit::{AssignedCell, Chip, Layouter, Region, SimpleFloorPlanner},
-    plonk::{Advice, Circuit, Column, ConstraintSystem, Error, Fixed, Instance, Selector},
+    plonk::{Advice, Any, Circuit, Column, ConstraintSystem, Error, Fixed, Instance, Selector},
     poly::Rotation,
 };
 
@@ -137,6 +137,14 @@ impl<F: FieldExt> Chip<F> for FieldChip<F> {
     fn loaded(&self) -> &Self::Loaded {
         &()
     }
+
+    fn columns(&self) -> Vec<Column<Any>> {
+        vec![
+            self.config.advice[0].into(),
+            self.config.advice[1].into(),
+            self.config.instance.into(),
+        ]
+    }
 }
 // ANCHOR_END: chip-impl
 
@@ -346,4 +354,15 @@ fn main() {
     let prover = MockProver::run(k, &circuit, vec![public_inputs]).unwrap();
     assert!(prover.verify().is_err());
     // ANCHOR_END: test-circuit
+
+    // Pass `--prove` to also run the real prover and verifier.
+    if std::env::args().any(|arg| arg == "--prove") {
+        halo2_proofs::dev::quick_prove_verify::<halo2_proofs::pairing::bn256::Bn256, _, _>(
+            k,
+            &circuit,
+            vec![vec![c]],
+            rand_core::OsRng,
+        )
+        .expect("the real proof should verify");
+    }
 }
@@ -1,45 +1,86 @@
 //! This module provides common utilities, traits and structures for group,
 //! field and polynomial arithmetic.
 
+use std::cmp;
+
 use super::multicore;
+use blake2b_simd::Params as Blake2bParams;
 pub use ff::Field;
 use group::{
     ff::{BatchInvert, PrimeField},
-    Group as _,
+    prime::PrimeCurveAffine as _,
+    Curve as _, Group as _,
 };
 
 pub use pairing::arithmetic::*;
 
-fn multiexp_serial<C: CurveAffine>(coeffs: &[C::Scalar], bases: &[C], acc: &mut C::Curve) {
-    let coeffs: Vec<_> = coeffs.iter().map(|a| a.to_repr()).collect();
-
-    let c = if bases.len() < 4 {
+/// Returns the window size, in bits, for a multi-exponentiation over `num_bases` bases.
+fn window_size(num_bases: usize) -> usize {
+    if num_bases < 4 {
         1
-    } else if bases.len() < 32 {
+    } else if num_bases < 32 {
         3
     } else {
-        (f64::from(bases.len() as u32)).ln().ceil() as usize
-    };
+        (f64::from(num_bases as u32)).ln().ceil() as usize
+    }
+}
+
+/// Returns the `segment`-th window of `c` bits of the little-endian `bytes`.
+fn get_at<F: PrimeField>(segment: usize, c: usize, bytes: &F::Repr) -> usize {
+    let skip_bits = segment * c;
+    let skip_bytes = skip_bits / 8;
+
+    if skip_bytes >= 32 {
+        return 0;
+    }
+
+    let mut v = [0; 8];
+    for (v, o) in v.iter_mut().zip(bytes.as_ref()[skip_bytes..].iter()) {
+        *v = *o;
+    }
+
+    let mut tmp = u64::from_le_bytes(v);
+    tmp >>= skip_bits - (skip_bytes * 8);
+    tmp = tmp % (1 << c);
 
-    fn get_at<F: PrimeField>(segment: usize, c: usize, bytes: &F::Repr) -> usize {
-        let skip_bits = segment * c;
-        let skip_bytes = skip_bits / 8;
+    tmp as usize
+}
+
+#[derive(Clone, Copy)]
+enum Bucket<C: CurveAffine> {
+    None,
+    Affine(C),
+    Projective(C::Curve),
+}
 
-        if skip_bytes >= 32 {
-            return 0;
+impl<C: CurveAffine> Bucket<C> {
+    fn add_assign(&mut self, other: &C) {
+        *self = match *self {
+            Bucket::None => Bucket::Affine(*other),
+            Bucket::Affine(a) => Bucket::Projective(a + *other),
+            Bucket::Projective(mut a) => {
+                a += *other;
+                Bucket::Projective(a)
+            }
         }
+    }
 
-        let mut v = [0; 8];
-        for (v, o) in v.iter_mut().zip(bytes.as_ref()[skip_bytes..].iter()) {
-            *v = *o;
+    fn add(self, mut other: C::Curve) -> C::Curve {
+        match self {
+            Bucket::None => other,
+            Bucket::Affine(a) => {
+                other += a;
+                other
+            }
+            Bucket::Projective(a) => other + &a,
         }
+    }
+}
 
-        let mut tmp = u64::from_le_bytes(v);
-        tmp >>= skip_bits - (skip_bytes * 8);
-        tmp = tmp % (1 << c);
+fn multiexp_serial<C: CurveAffine>(coeffs: &[C::Scalar], bases: &[C], acc: &mut C::Curve) {
+    let coeffs: Vec<_> = coeffs.iter().map(|a| a.to_repr()).collect();
 
-        tmp as usize
-    }
+    let c = window_size(bases.len());
 
     let segments = (256 / c) + 1;
 
@@ -48,37 +89,6 @@ fn multiexp_serial<C: CurveAffine>(coeffs: &[C::Scalar], bases: &[C], acc: &mut
             *acc = acc.double();
         }
 
-        #[derive(Clone, Copy)]
-        enum Bucket<C: CurveAffine> {
-            None,
-            Affine(C),
-            Projective(C::Curve),
-        }
-
-        impl<C: CurveAffine> Bucket<C> {
-            fn add_assign(&mut self, other: &C) {
-                *self = match *self {
-                    Bucket::None => Bucket::Affine(*other),
-                    Bucket::Affine(a) => Bucket::Projective(a + *other),
-                    Bucket::Projective(mut a) => {
-                        a += *other;
-                        Bucket::Projective(a)
-                    }
-                }
-            }
-
-            fn add(self, mut other: C::Curve) -> C::Curve {
-                match self {
-                    Bucket::None => other,
-                    Bucket::Affine(a) => {
-                        other += a;
-                        other
-                    }
-                    Bucket::Projective(a) => other + &a,
-                }
-            }
-        }
-
         let mut buckets: Vec<Bucket<C>> = vec![Bucket::None; (1 << c) - 1];
 
         for (coeff, base) in coeffs.iter().zip(bases.iter()) {
@@ -158,6 +168,151 @@ pub fn best_multiexp<C: CurveAffine>(coeffs: &[C::Scalar], bases: &[C]) -> C::Cu
     }
 }
 
+/// Multi-exponentiation over a fixed set of bases, such as a commitment key, that is
+/// reused across many multi-exponentiations.
+///
+/// Each base $G$ is stored alongside $2^{ci} G$ for every $c$-bit window $i$ of a
+/// scalar, so [`FixedBaseMSM::msm`] adds every window into the same buckets and
+/// needs no doublings. This costs `256 / c + 1` affine points of memory per base.
+#[derive(Clone, Debug)]
+pub struct FixedBaseMSM<C: CurveAffine> {
+    c: usize,
+    segments: usize,
+    /// The shifted copies of the bases, `segments` consecutive points per base.
+    tables: Vec<C>,
+}
+
+impl<C: CurveAffine> FixedBaseMSM<C> {
+    /// Precomputes the tables for `bases`.
+    ///
+    /// This will use multithreading if beneficial.
+    pub fn new(bases: &[C]) -> Self {
+        let c = window_size(bases.len());
+        let segments = (256 / c) + 1;
+
+        let mut tables = vec![C::Curve::identity(); bases.len() * segments];
+        let chunk = cmp::max(bases.len() / multicore::current_num_threads(), 1);
+        multicore::scope(|scope| {
+            for (bases, tables) in bases.chunks(chunk).zip(tables.chunks_mut(chunk * segments)) {
+                scope.spawn(move |_| {
+                    for (base, table) in bases.iter().zip(tables.chunks_mut(segments)) {
+                        let mut acc = base.to_curve();
+                        for entry in table.iter_mut() {
+                            *entry = acc;
+                            for _ in 0..c {
+                                acc = acc.double();
+                            }
+                        }
+                    }
+                });
+            }
+        });
+
+        FixedBaseMSM {
+            c,
+            segments,
+            tables: batch_to_affine(&tables),
+        }
+    }
+
+    /// Returns the number of bases.
+    pub fn len(&self) -> usize {
+        self.tables.len() / self.segments
+    }
+
+    /// Returns `true` if there are no bases.
+    pub fn is_empty(&self) -> bool {
+        self.tables.is_empty()
+    }
+
+    /// Performs a multi-exponentiation of the bases by `scalars`.
+    ///
+    /// This function will panic if `scalars` does not have one scalar per base.
+    ///
+    /// This will use multithreading if beneficial.
+    pub fn msm(&self, scalars: &[C::Scalar]) -> C::Curve {
+        assert_eq!(scalars.len(), self.len());
+
+        let scalars: Vec<_> = scalars.iter().map(|a| a.to_repr()).collect();
+        let chunk = cmp::max(scalars.len() / multicore::current_num_threads(), 1);
+        let mut results = vec![C::Curve::identity(); scalars.chunks(chunk).len()];
+        multicore::scope(|scope| {
+            for ((scalars, tables), acc) in scalars
+                .chunks(chunk)
+                .zip(self.tables.chunks(chunk * self.segments))
+                .zip(results.iter_mut())
+            {
+                scope.spawn(move |_| {
+                    let mut buckets: Vec<Bucket<C>> = vec![Bucket::None; (1 << self.c) - 1];
+
+                    for (scalar, table) in scalars.iter().zip(tables.chunks(self.segments)) {
+                        for (segment, base) in table.iter().enumerate() {
+                            let digit = get_at::<C::Scalar>(segment, self.c, scalar);
+                            if digit != 0 {
+                                buckets[digit - 1].add_assign(base);
+                            }
+                        }
+                    }
+
+                    // Summation by parts, as in `multiexp_serial`.
+                    let mut running_sum = C::Curve::identity();
+                    for exp in buckets.into_iter().rev() {
+                        running_sum = exp.add(running_sum);
+                        *acc = *acc + &running_sum;
+                    }
+                });
+            }
+        });
+        results.iter().fold(C::Curve::identity(), |a, b| a + b)
+    }
+}
+
+/// Converts a slice of projective points to affine form.
+///
+/// This uses batch inversion, so it performs a single field inversion for the whole
+/// slice rather than one per point. Identity points are mapped to the affine
+/// identity.
+pub fn batch_to_affine<C: CurveAffine>(points: &[C::Curve]) -> Vec<C> {
+    let mut affine = vec![C::identity(); points.len()];
+    C::Curve::batch_normalize(points, &mut affine);
+    affine
+}
+
+/// Deterministically maps `domain` and `index` to a point on the curve, for
+/// deriving independent generators that nobody knows the discrete logarithms of.
+///
+/// This uses try-and-increment: the inputs are hashed together with a counter to
+/// obtain a candidate $x$-coordinate, and the counter is incremented until
+/// $x^3 + ax + b$ is a square. Of the two possible $y$-coordinates, the even one
+/// is chosen. The resulting point is never the identity.
+///
+/// The curve is assumed to have a prime order, so that every point on it is in
+/// the group generated by the curve's generator.
+pub fn hash_to_curve<C: CurveAffine>(domain: &[u8], index: u64) -> C {
+    for counter in 0u64.. {
+        let hash = Blake2bParams::new()
+            .hash_length(64)
+            .personal(b"Halo2-Generators")
+            .to_state()
+            .update(&(domain.len() as u64).to_le_bytes())
+            .update(domain)
+            .update(&index.to_le_bytes())
+            .update(&counter.to_le_bytes())
+            .finalize();
+
+        let x = C::Base::from_bytes_wide(hash.as_array());
+        let y2 = x.square() * x + C::a() * x + C::b();
+        if let Some(y) = Option::<C::Base>::from(y2.sqrt()) {
+            let y = if bool::from(y.is_odd()) { -y } else { y };
+            if let Some(point) = Option::from(C::from_xy(x, y)) {
+                return point;
+            }
+        }
+    }
+
+    unreachable!("a square is found long before the counter overflows")
+}
+
 /// Performs a radix-$2$ Fast-Fourier Transformation (FFT) on a vector of size
 /// $n = 2^k$, when provided `log_n` = $k$ and an element of multiplicative
 /// order $n$ called `omega` ($\omega$). The result is that the vector `a`, when
@@ -265,6 +420,59 @@ fn parallel_fft<G: Group>(a: &mut [G], omega: G::Scalar, log_n: u32, log_threads
     }
 }
 
+/// Converts a commitment key `g` = $[G_0, \dots, G_{n - 1}]$ in the monomial
+/// basis, with $n = 2^k$, into the Lagrange basis over the $n$-th roots of
+/// unity by performing an inverse FFT over the group elements.
+///
+/// Committing to a polynomial's evaluations with the returned basis is the same
+/// as committing to its coefficients with `g`.
+pub fn g_to_lagrange<C: CurveAffine>(g: Vec<C::Curve>, k: u32) -> Vec<C::Curve> {
+    assert_eq!(g.len(), 1 << k);
+    let n_inv = Option::<C::Scalar>::from(C::Scalar::from(1u64 << k).invert())
+        .expect("inversion should be ok for n = 1<<k");
+    let mut omega_inv = C::Scalar::ROOT_OF_UNITY_INV;
+    for _ in k..C::Scalar::S {
+        omega_inv = omega_inv.square();
+    }
+
+    let mut g_lagrange = g;
+    best_fft(&mut g_lagrange, omega_inv, k);
+    parallelize(&mut g_lagrange, |g, _| {
+        for g in g.iter_mut() {
+            *g *= n_inv;
+        }
+    });
+    g_lagrange
+}
+
+/// Encodes `f` as a fixed-width 32-byte array, using the field's canonical
+/// little-endian representation padded with zeroes.
+///
+/// Panics if the field's representation is longer than 32 bytes.
+pub fn to_bytes32<F: FieldExt>(f: F) -> [u8; 32] {
+    let repr = f.to_repr();
+    let repr = repr.as_ref();
+    assert!(repr.len() <= 32);
+
+    let mut bytes = [0u8; 32];
+    bytes[..repr.len()].copy_from_slice(repr);
+    bytes
+}
+
+/// Decodes a field element from the encoding produced by [`to_bytes32`].
+///
+/// Returns `None` if `bytes` is not the canonical encoding of a field element.
+pub fn from_bytes32<F: FieldExt>(bytes: &[u8; 32]) -> Option<F> {
+    let mut repr = F::Repr::default();
+    let len = repr.as_ref().len();
+    if len > 32 || bytes[len..].iter().any(|byte| *byte != 0) {
+        return None;
+    }
+
+    repr.as_mut().copy_from_slice(&bytes[..len]);
+    Option::from(F::from_repr(repr))
+}
+
 /// This evaluates a provided polynomial (in coefficient form) at `point`.
 pub fn eval_polynomial<F: Field>(poly: &[F], point: F) -> F {
     fn evaluate<F: Field>(poly: &[F], point: F) -> F {
@@ -293,6 +501,33 @@ pub fn eval_polynomial<F: Field>(poly: &[F], point: F) -> F {
     }
 }
 
+/// This evaluates a provided polynomial (in coefficient form) at each of the given
+/// `points`.
+///
+/// When there are enough points to occupy every thread, the points are evaluated in
+/// parallel with a serial Horner evaluation each; otherwise each point is evaluated
+/// with [`eval_polynomial`], which parallelizes over the coefficients instead.
+pub fn eval_polynomial_multi<F: Field>(poly: &[F], points: &[F]) -> Vec<F> {
+    let num_threads = multicore::current_num_threads();
+    if points.len() < num_threads {
+        points
+            .iter()
+            .map(|point| eval_polynomial(poly, *point))
+            .collect()
+    } else {
+        let mut evals = vec![F::zero(); points.len()];
+        parallelize(&mut evals, |evals, start| {
+            for (eval, point) in evals.iter_mut().zip(points[start..].iter()) {
+                *eval = poly
+                    .iter()
+                    .rev()
+                    .fold(F::zero(), |acc, coeff| acc * point + coeff);
+            }
+        });
+        evals
+    }
+}
+
 /// This computes the inner product of two vectors `a` and `b`.
 ///
 /// This function will panic if the two vectors are not the same size.
@@ -333,8 +568,14 @@ where
 
 /// This simple utility function will parallelize an operation that is to be
 /// performed over a mutable slice.
+///
+/// `v` is split into roughly one contiguous chunk per thread, and `f` is called on
+/// each chunk along with the index in `v` at which that chunk starts.
 pub fn parallelize<T: Send, F: Fn(&mut [T], usize) + Send + Sync + Clone>(v: &mut [T], f: F) {
     let n = v.len();
+    if n == 0 {
+        return;
+    }
     let num_threads = multicore::current_num_threads();
     let mut chunk = (n as usize) / num_threads;
     if chunk < num_threads {
@@ -352,6 +593,22 @@ pub fn parallelize<T: Send, F: Fn(&mut [T], usize) + Send + Sync + Clone>(v: &mu
     });
 }
 
+/// Returns the smallest `k` for which a circuit with $2^k$ rows has at least `rows`
+/// rows. The result is never less than 1.
+///
+/// The last `blinding_factors() + 1` rows of a circuit are reserved by the prover, so
+/// `rows` should include them; see [`ConstraintSystem::minimum_rows`].
+///
+/// [`ConstraintSystem::minimum_rows`]: crate::plonk::ConstraintSystem::minimum_rows
+pub fn k_from_rows(rows: usize) -> u32 {
+    cmp::max(rows.next_power_of_two().trailing_zeros(), 1)
+}
+
+/// Returns the number of rows, $2^k$, of a circuit with parameter `k`.
+pub fn rows_for_k(k: u32) -> usize {
+    1 << k
+}
+
 fn log2_floor(num: usize) -> u32 {
     assert!(num > 0);
 
@@ -443,17 +700,17 @@ pub(crate) fn evaluate_vanishing_polynomial<F: FieldExt>(roots: &[F], z: F) -> F
 }
 
 #[cfg(test)]
-use rand_core::OsRng;
+use crate::dev::test_rng;
 
 #[cfg(test)]
 use pairing::bn256::Fr as Fp;
 
 #[test]
 fn test_lagrange_interpolate() {
-    let rng = OsRng;
+    let mut rng = test_rng();
 
-    let points = (0..5).map(|_| Fp::random(rng)).collect::<Vec<_>>();
-    let evals = (0..5).map(|_| Fp::random(rng)).collect::<Vec<_>>();
+    let points = (0..5).map(|_| Fp::random(&mut rng)).collect::<Vec<_>>();
+    let evals = (0..5).map(|_| Fp::random(&mut rng)).collect::<Vec<_>>();
 
     for coeffs in 0..5 {
         let points = &points[0..coeffs];
@@ -467,3 +724,156 @@ fn test_lagrange_interpolate() {
         }
     }
 }
+
+#[test]
+fn test_batch_to_affine() {
+    use group::Curve;
+    use pairing::bn256::{G1Affine, G1};
+
+    let mut rng = test_rng();
+
+    let mut points = (0..10).map(|_| G1::random(&mut rng)).collect::<Vec<_>>();
+    points[3] = G1::identity();
+    points.push(G1::identity());
+
+    let expected = points.iter().map(|p| p.to_affine()).collect::<Vec<_>>();
+    assert_eq!(batch_to_affine::<G1Affine>(&points), expected);
+    assert!(batch_to_affine::<G1Affine>(&[]).is_empty());
+}
+
+#[test]
+fn test_eval_polynomial_multi() {
+    let mut rng = test_rng();
+
+    let poly = (0..37).map(|_| Fp::random(&mut rng)).collect::<Vec<_>>();
+    for &num_points in [0, 1, 3, 100].iter() {
+        let points = (0..num_points)
+            .map(|_| Fp::random(&mut rng))
+            .collect::<Vec<_>>();
+        let expected = points
+            .iter()
+            .map(|point| eval_polynomial(&poly, *point))
+            .collect::<Vec<_>>();
+        assert_eq!(eval_polynomial_multi(&poly, &points), expected);
+    }
+}
+
+#[test]
+fn test_g_to_lagrange() {
+    use pairing::bn256::{G1Affine, G1};
+
+    const K: u32 = 4;
+    let mut rng = test_rng();
+
+    let g = (0..(1 << K))
+        .map(|_| G1::random(&mut rng))
+        .collect::<Vec<_>>();
+    let g_lagrange = batch_to_affine::<G1Affine>(&g_to_lagrange::<G1Affine>(g.clone(), K));
+    let g = batch_to_affine::<G1Affine>(&g);
+
+    let mut omega = Fp::ROOT_OF_UNITY;
+    for _ in K..Fp::S {
+        omega = omega.square();
+    }
+
+    let poly = (0..(1 << K))
+        .map(|_| Fp::random(&mut rng))
+        .collect::<Vec<_>>();
+    let evals = (0..(1 << K))
+        .map(|i| eval_polynomial(&poly, omega.pow_vartime(&[i as u64])))
+        .collect::<Vec<_>>();
+
+    assert_eq!(best_multiexp(&evals, &g_lagrange), best_multiexp(&poly, &g));
+}
+
+#[test]
+fn test_bytes32() {
+    let mut rng = test_rng();
+
+    for _ in 0..100 {
+        let f = Fp::random(&mut rng);
+        assert_eq!(from_bytes32::<Fp>(&to_bytes32(f)), Some(f));
+    }
+
+    // The modulus itself is not a canonical encoding.
+    let mut modulus = to_bytes32(-Fp::one());
+    for byte in modulus.iter_mut() {
+        let (sum, carry) = byte.overflowing_add(1);
+        *byte = sum;
+        if !carry {
+            break;
+        }
+    }
+    assert_eq!(from_bytes32::<Fp>(&modulus), None);
+}
+
+#[test]
+fn test_parallelize() {
+    for &n in [0, 1, 7, 1 << 10, (1 << 10) + 3].iter() {
+        let mut v = vec![0usize; n];
+        parallelize(&mut v, |chunk, start| {
+            for (i, x) in chunk.iter_mut().enumerate() {
+                *x = start + i;
+            }
+        });
+        assert_eq!(v, (0..n).collect::<Vec<_>>());
+    }
+}
+
+#[test]
+fn test_hash_to_curve() {
+    use pairing::bn256::G1Affine;
+
+    let points: Vec<G1Affine> = (0..16).map(|index| hash_to_curve(b"test", index)).collect();
+    for (i, point) in points.iter().enumerate() {
+        assert!(bool::from(point.is_on_curve()));
+        assert!(!bool::from(point.is_identity()));
+        assert!(!points[..i].contains(point));
+        assert_eq!(*point, hash_to_curve::<G1Affine>(b"test", i as u64));
+    }
+
+    // The domain separates otherwise identical indices.
+    assert_ne!(points[0], hash_to_curve::<G1Affine>(b"other", 0));
+}
+
+#[test]
+fn test_fixed_base_msm() {
+    use pairing::bn256::{G1Affine, G1};
+
+    let mut rng = test_rng();
+
+    for &num_bases in [0, 1, 5, 100].iter() {
+        let bases = batch_to_affine::<G1Affine>(
+            &(0..num_bases)
+                .map(|_| G1::random(&mut rng))
+                .collect::<Vec<_>>(),
+        );
+        let msm = FixedBaseMSM::new(&bases);
+        assert_eq!(msm.len(), num_bases);
+
+        // The tables are reused for every multi-exponentiation.
+        for _ in 0..3 {
+            let scalars = (0..num_bases)
+                .map(|_| Fp::random(&mut rng))
+                .collect::<Vec<_>>();
+            assert_eq!(msm.msm(&scalars), best_multiexp(&scalars, &bases));
+        }
+
+        let extremes = vec![-Fp::one(); num_bases];
+        assert_eq!(msm.msm(&extremes), best_multiexp(&extremes, &bases));
+    }
+}
+
+#[test]
+fn test_k_from_rows() {
+    assert_eq!(k_from_rows(5), 3);
+    assert_eq!(k_from_rows(8), 3);
+    assert_eq!(k_from_rows(9), 4);
+    assert_eq!(k_from_rows(0), 1);
+    assert_eq!(k_from_rows(1), 1);
+
+    for k in 1..20 {
+        assert_eq!(k_from_rows(rows_for_k(k)), k);
+        assert_eq!(k_from_rows(rows_for_k(k) + 1), k + 1);
+    }
+}
@@ -1,6 +1,6 @@
 //! Traits and structs for implementing circuit components.
 
-use std::{convert::TryInto, fmt, marker::PhantomData};
+use std::{cmp, collections::HashSet, convert::TryInto, fmt, marker::PhantomData};
 
 use ff::Field;
 
@@ -43,6 +43,25 @@ pub trait Chip<F: FieldExt>: Sized {
     ///
     /// Panics if called before `Chip::load`.
     fn loaded(&self) -> &Self::Loaded;
+
+    /// Returns the columns that this chip assigns to or queries.
+    ///
+    /// This allows a composition layer to detect sub-chips that unexpectedly
+    /// claim the same columns; see [`overlapping_columns`]. The default
+    /// implementation reports no columns.
+    fn columns(&self) -> Vec<Column<Any>> {
+        vec![]
+    }
+}
+
+/// Returns the columns that are used by both `a` and `b`, in the order in which
+/// `a` reports them.
+pub fn overlapping_columns<F: FieldExt, A: Chip<F>, B: Chip<F>>(a: &A, b: &B) -> Vec<Column<Any>> {
+    let b_columns: HashSet<_> = b.columns().into_iter().collect();
+    a.columns()
+        .into_iter()
+        .filter(|column| b_columns.contains(column))
+        .collect()
 }
 
 /// Index of a region in a layouter
@@ -92,6 +111,24 @@ pub struct Cell {
     column: Column<Any>,
 }
 
+impl Cell {
+    /// Returns the index of the region in which this cell resides.
+    pub fn region_index(&self) -> RegionIndex {
+        self.region_index
+    }
+
+    /// Returns the column of this cell.
+    pub fn column(&self) -> Column<Any> {
+        self.column
+    }
+
+    /// Returns the row of this cell, relative to the start of its region. The
+    /// absolute row depends on where the floor planner placed the region.
+    pub fn row_offset(&self) -> usize {
+        self.row_offset
+    }
+}
+
 /// An assigned cell.
 #[derive(Clone, Debug)]
 pub struct AssignedCell<V, F: Field> {
@@ -100,6 +137,12 @@ pub struct AssignedCell<V, F: Field> {
     _marker: PhantomData<F>,
 }
 
+impl<V, F: Field> From<&AssignedCell<V, F>> for Cell {
+    fn from(assigned: &AssignedCell<V, F>) -> Self {
+        assigned.cell
+    }
+}
+
 impl<V, F: Field> AssignedCell<V, F> {
     /// Returns the value of the [`AssignedCell`].
     pub fn value(&self) -> Option<&V> {
@@ -164,15 +207,32 @@ where
 #[derive(Debug)]
 pub struct Region<'r, F: Field> {
     region: &'r mut dyn layouter::RegionLayouter<F>,
+    /// The shape of the cells assigned so far, checked by [`Region::assert_shape`].
+    #[cfg(debug_assertions)]
+    shape: layouter::RegionShape,
 }
 
 impl<'r, F: Field> From<&'r mut dyn layouter::RegionLayouter<F>> for Region<'r, F> {
     fn from(region: &'r mut dyn layouter::RegionLayouter<F>) -> Self {
-        Region { region }
+        Region {
+            region,
+            #[cfg(debug_assertions)]
+            shape: layouter::RegionShape::new(0.into()),
+        }
     }
 }
 
 impl<'r, F: Field> Region<'r, F> {
+    /// Records that the region uses the given column at `offset`.
+    #[cfg(debug_assertions)]
+    fn track(&mut self, column: layouter::RegionColumn, offset: usize) {
+        self.shape.columns.insert(column);
+        self.shape.row_count = cmp::max(self.shape.row_count, offset + 1);
+    }
+
+    #[cfg(not(debug_assertions))]
+    fn track(&mut self, _: layouter::RegionColumn, _: usize) {}
+
     /// Enables a selector at the given offset.
     pub(crate) fn enable_selector<A, AR>(
         &mut self,
@@ -185,7 +245,10 @@ impl<'r, F: Field> Region<'r, F> {
         AR: Into<String>,
     {
         self.region
-            .enable_selector(&|| annotation().into(), selector, offset)
+            .enable_selector(&|| annotation().into(), selector, offset)?;
+        self.track((*selector).into(), offset);
+
+        Ok(())
     }
 
     /// Assign an advice column value (witness).
@@ -213,6 +276,7 @@ impl<'r, F: Field> Region<'r, F> {
                     value = Some(v);
                     Ok(value_f)
                 })?;
+        self.track(cell.column.into(), offset);
 
         Ok(AssignedCell {
             value,
@@ -221,6 +285,29 @@ impl<'r, F: Field> Region<'r, F> {
         })
     }
 
+    /// Assigns the value of `source` to the column `column` at `offset` within this
+    /// region, and constrains the new cell to be equal to `source`.
+    ///
+    /// This is equivalent to [`AssignedCell::copy_advice`].
+    ///
+    /// Returns an error if either `source` or the assigned cell are in columns where
+    /// equality has not been enabled.
+    pub fn copy_advice<V, A, AR>(
+        &mut self,
+        annotation: A,
+        column: Column<Advice>,
+        offset: usize,
+        source: &AssignedCell<V, F>,
+    ) -> Result<AssignedCell<V, F>, Error>
+    where
+        V: Clone,
+        for<'v> Assigned<F>: From<&'v V>,
+        A: Fn() -> AR,
+        AR: Into<String>,
+    {
+        source.copy_advice(annotation, self, column, offset)
+    }
+
     /// Assigns a constant value to the column `advice` at `offset` within this region.
     ///
     /// The constant value will be assigned to a cell within one of the fixed columns
@@ -245,6 +332,7 @@ impl<'r, F: Field> Region<'r, F> {
             offset,
             (&constant).into(),
         )?;
+        self.track(cell.column.into(), offset);
 
         Ok(AssignedCell {
             value: Some(constant),
@@ -276,6 +364,7 @@ impl<'r, F: Field> Region<'r, F> {
             advice,
             offset,
         )?;
+        self.track(cell.column.into(), offset);
 
         Ok(AssignedCell {
             value,
@@ -309,6 +398,7 @@ impl<'r, F: Field> Region<'r, F> {
                     value = Some(v);
                     Ok(value_f)
                 })?;
+        self.track(cell.column.into(), offset);
 
         Ok(AssignedCell {
             value,
@@ -317,6 +407,41 @@ impl<'r, F: Field> Region<'r, F> {
         })
     }
 
+    /// Asserts that the cells assigned in this region so far span exactly `rows` rows
+    /// and the given `columns`. Selectors count towards the rows, but not the columns.
+    ///
+    /// Chips that hard-code the layout of a region (for example via the rotations used
+    /// in their gates) can use this to catch the region's shape drifting from what they
+    /// expect. The check is only performed in debug builds.
+    ///
+    /// # Panics
+    ///
+    /// Panics in debug builds if the region's shape does not match.
+    pub fn assert_shape(&self, rows: usize, columns: &[Column<Any>]) {
+        #[cfg(debug_assertions)]
+        {
+            let actual: HashSet<Column<Any>> = self
+                .shape
+                .columns
+                .iter()
+                .filter_map(|column| match column {
+                    layouter::RegionColumn::Column(column) => Some(*column),
+                    layouter::RegionColumn::Selector(_) => None,
+                })
+                .collect();
+            let expected: HashSet<Column<Any>> = columns.iter().cloned().collect();
+
+            assert_eq!(
+                self.shape.row_count, rows,
+                "region spans {} rows, expected {}",
+                self.shape.row_count, rows
+            );
+            assert_eq!(actual, expected, "region uses unexpected columns");
+        }
+        #[cfg(not(debug_assertions))]
+        let _ = (rows, columns);
+    }
+
     /// Constrains a cell to have a constant value.
     ///
     /// Returns an error if the cell is in a column where equality has not been enabled.
@@ -329,10 +454,35 @@ impl<'r, F: Field> Region<'r, F> {
 
     /// Constrains two cells to have the same value.
     ///
+    /// The cells can be given either as [`Cell`]s or as references to the
+    /// [`AssignedCell`]s they were assigned as. The copy is made in the circuit's
+    /// permutation argument, which the layouter routes it to.
+    ///
+    /// Returns an error if either of the cells are in columns where equality
+    /// has not been enabled.
+    pub fn constrain_equal<L: Into<Cell>, R: Into<Cell>>(
+        &mut self,
+        left: L,
+        right: R,
+    ) -> Result<(), Error> {
+        self.region.constrain_equal(left.into(), right.into())
+    }
+
+    /// Constrains a cell to equal the instance column's cell at absolute
+    /// location `row`.
+    ///
+    /// This wires the cell to the public input directly through the permutation
+    /// argument, without requiring a separate gate.
+    ///
     /// Returns an error if either of the cells are in columns where equality
     /// has not been enabled.
-    pub fn constrain_equal(&mut self, left: Cell, right: Cell) -> Result<(), Error> {
-        self.region.constrain_equal(left, right)
+    pub fn constrain_instance(
+        &mut self,
+        cell: Cell,
+        instance: Column<Instance>,
+        row: usize,
+    ) -> Result<(), Error> {
+        self.region.constrain_instance(cell, instance, row)
     }
 }
 
@@ -416,6 +566,63 @@ pub trait Layouter<F: Field> {
         N: Fn() -> NR,
         NR: Into<String>;
 
+    /// Assigns several regions that do not depend on each other, all with the
+    /// same `name`, and returns their results in order.
+    ///
+    /// The regions are laid out exactly as if each had been passed to
+    /// [`Layouter::assign_region`] in turn. Layouters may synthesize regions that use
+    /// disjoint sets of columns in parallel; the default implementation assigns them
+    /// sequentially.
+    ///
+    /// [`SimpleFloorPlanner`] synthesizes column-disjoint regions on separate threads.
+    /// Such regions cannot use [`Region::assign_advice_from_instance`].
+    fn assign_regions<A, AR, N, NR>(
+        &mut self,
+        name: N,
+        assignments: Vec<A>,
+    ) -> Result<Vec<AR>, Error>
+    where
+        A: FnMut(Region<'_, F>) -> Result<AR, Error> + Send,
+        AR: Send,
+        N: Fn() -> NR,
+        NR: Into<String>,
+    {
+        assignments
+            .into_iter()
+            .map(|assignment| self.assign_region(&name, assignment))
+            .collect()
+    }
+
+    /// Assigns `values` to successive rows of `column` in a new region named
+    /// `annotation`, starting at offset 0, and returns the assigned cells in order.
+    ///
+    /// Returns [`Error::Synthesis`] if a value is `None` when witnesses are being
+    /// assigned.
+    fn assign_advice_slice<A, AR>(
+        &mut self,
+        annotation: A,
+        column: Column<Advice>,
+        values: &[Option<F>],
+    ) -> Result<Vec<Cell>, Error>
+    where
+        A: Fn() -> AR,
+        AR: Into<String>,
+    {
+        self.assign_region(&annotation, |mut region| {
+            values
+                .iter()
+                .enumerate()
+                .map(|(offset, value)| {
+                    region
+                        .assign_advice(&annotation, column, offset, || {
+                            value.ok_or(Error::Synthesis)
+                        })
+                        .map(|assigned| assigned.cell())
+                })
+                .collect()
+        })
+    }
+
     /// Constrains a [`Cell`] to equal an instance column's row value at an
     /// absolute position.
     fn constrain_instance(
@@ -425,6 +632,24 @@ pub trait Layouter<F: Field> {
         row: usize,
     ) -> Result<(), Error>;
 
+    /// Constrains an already-assigned [`Cell`] to equal a fixed constant.
+    ///
+    /// The constant is assigned to a column configured with
+    /// [`ConstraintSystem::enable_constant`], and wired to `cell` via the permutation.
+    /// Returns [`Error::NotEnoughColumnsForConstants`] if no such column exists.
+    ///
+    /// [`ConstraintSystem::enable_constant`]: crate::plonk::ConstraintSystem::enable_constant
+    fn constrain_constant<VR>(&mut self, cell: Cell, constant: VR) -> Result<(), Error>
+    where
+        VR: Into<Assigned<F>>,
+    {
+        let constant = constant.into();
+        self.assign_region(
+            || "constrain constant",
+            |mut region| region.constrain_constant(cell, constant),
+        )
+    }
+
     /// Gets the "root" of this assignment, bypassing the namespacing.
     ///
     /// Not intended for downstream consumption; use [`Layouter::namespace`] instead.
@@ -481,6 +706,20 @@ impl<'a, F: Field, L: Layouter<F> + 'a> Layouter<F> for NamespacedLayouter<'a, F
         self.0.assign_table(name, assignment)
     }
 
+    fn assign_regions<A, AR, N, NR>(
+        &mut self,
+        name: N,
+        assignments: Vec<A>,
+    ) -> Result<Vec<AR>, Error>
+    where
+        A: FnMut(Region<'_, F>) -> Result<AR, Error> + Send,
+        AR: Send,
+        N: Fn() -> NR,
+        NR: Into<String>,
+    {
+        self.0.assign_regions(name, assignments)
+    }
+
     fn constrain_instance(
         &mut self,
         cell: Cell,
@@ -539,3 +778,397 @@ impl<'a, F: Field, L: Layouter<F> + 'a> Drop for NamespacedLayouter<'a, F, L> {
         self.get_root().pop_namespace(gadget_name);
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::cell::RefCell;
+    use std::marker::PhantomData;
+
+    use pairing::bn256::Fr as Fp;
+
+    use super::{
+        overlapping_columns, AssignedCell, Cell, Chip, Layouter, RegionIndex, SimpleFloorPlanner,
+    };
+    use crate::{
+        arithmetic::FieldExt,
+        dev::{mul_circuit::MulConfig, witness_matrix, MockProver},
+        plonk::{Advice, Any, Circuit, Column, ConstraintSystem, Error},
+    };
+
+    /// Mirrors the chip from the simple example, which uses two advice columns.
+    struct FieldChip<F: FieldExt> {
+        advice: [Column<Advice>; 2],
+        _marker: PhantomData<F>,
+    }
+
+    impl<F: FieldExt> Chip<F> for FieldChip<F> {
+        type Config = [Column<Advice>; 2];
+        type Loaded = ();
+
+        fn config(&self) -> &Self::Config {
+            &self.advice
+        }
+
+        fn loaded(&self) -> &Self::Loaded {
+            &()
+        }
+
+        fn columns(&self) -> Vec<Column<Any>> {
+            self.advice.iter().map(|column| (*column).into()).collect()
+        }
+    }
+
+    #[test]
+    fn chip_columns() {
+        let mut meta = ConstraintSystem::<Fp>::default();
+        let advice = [
+            meta.advice_column(),
+            meta.advice_column(),
+            meta.advice_column(),
+            meta.advice_column(),
+        ];
+        let chip = |advice| FieldChip::<Fp> {
+            advice,
+            _marker: PhantomData,
+        };
+
+        let a = chip([advice[0], advice[1]]);
+        assert_eq!(
+            a.columns(),
+            vec![Column::<Any>::from(advice[0]), advice[1].into()]
+        );
+
+        // Disjoint chips do not collide.
+        let b = chip([advice[2], advice[3]]);
+        assert!(overlapping_columns(&a, &b).is_empty());
+
+        // A chip that unexpectedly reuses a column is detected.
+        let c = chip([advice[3], advice[1]]);
+        assert_eq!(
+            overlapping_columns(&a, &c),
+            vec![Column::<Any>::from(advice[1])]
+        );
+    }
+
+    #[test]
+    fn cell_position() {
+        struct MyCircuit {
+            loaded: RefCell<Option<Cell>>,
+        }
+
+        impl Circuit<Fp> for MyCircuit {
+            type Config = Column<Advice>;
+            type FloorPlanner = SimpleFloorPlanner;
+
+            fn without_witnesses(&self) -> Self {
+                MyCircuit {
+                    loaded: RefCell::new(None),
+                }
+            }
+
+            fn configure(meta: &mut ConstraintSystem<Fp>) -> Self::Config {
+                meta.advice_column()
+            }
+
+            fn synthesize(
+                &self,
+                advice: Self::Config,
+                mut layouter: impl Layouter<Fp>,
+            ) -> Result<(), Error> {
+                layouter.assign_region(
+                    || "padding",
+                    |mut region| {
+                        for offset in 0..2 {
+                            region.assign_advice(
+                                || "padding",
+                                advice,
+                                offset,
+                                || Ok(Fp::from(0)),
+                            )?;
+                        }
+                        Ok(())
+                    },
+                )?;
+
+                let cell = layouter.assign_region(
+                    || "load private",
+                    |mut region| {
+                        region
+                            .assign_advice(|| "private input", advice, 0, || Ok(Fp::from(1)))
+                            .map(|assigned| assigned.cell())
+                    },
+                )?;
+                *self.loaded.borrow_mut() = Some(cell);
+
+                Ok(())
+            }
+        }
+
+        let mut meta = ConstraintSystem::<Fp>::default();
+        let advice = MyCircuit::configure(&mut meta);
+
+        let circuit = MyCircuit {
+            loaded: RefCell::new(None),
+        };
+        let (witness, _, _) = witness_matrix(4, &circuit, vec![]).unwrap();
+
+        // The cell reports its column and its row within the second region, which
+        // the floor planner placed after the padding, at row 2.
+        let cell = circuit.loaded.borrow().unwrap();
+        assert_eq!(*cell.region_index(), 1);
+        assert_eq!(cell.column(), advice.into());
+        assert_eq!(cell.row_offset(), 0);
+        assert_eq!(witness[advice.index()][2], Some(Fp::from(1)));
+    }
+
+    #[test]
+    fn assign_advice_slice() {
+        struct MyCircuit {
+            values: Vec<Option<Fp>>,
+            loaded: RefCell<Vec<Cell>>,
+        }
+
+        impl Circuit<Fp> for MyCircuit {
+            type Config = Column<Advice>;
+            type FloorPlanner = SimpleFloorPlanner;
+
+            fn without_witnesses(&self) -> Self {
+                MyCircuit {
+                    values: vec![None; self.values.len()],
+                    loaded: RefCell::new(vec![]),
+                }
+            }
+
+            fn configure(meta: &mut ConstraintSystem<Fp>) -> Self::Config {
+                meta.advice_column()
+            }
+
+            fn synthesize(
+                &self,
+                advice: Self::Config,
+                mut layouter: impl Layouter<Fp>,
+            ) -> Result<(), Error> {
+                let cells = layouter.assign_advice_slice(|| "bits", advice, &self.values)?;
+                *self.loaded.borrow_mut() = cells;
+                Ok(())
+            }
+        }
+
+        let mut meta = ConstraintSystem::<Fp>::default();
+        let advice = MyCircuit::configure(&mut meta);
+
+        let values = [1u64, 0, 1, 1];
+        let circuit = MyCircuit {
+            values: values.iter().map(|v| Some(Fp::from(*v))).collect(),
+            loaded: RefCell::new(vec![]),
+        };
+        assert_eq!(
+            MockProver::run(4, &circuit, vec![]).unwrap().verify(),
+            Ok(())
+        );
+        let (witness, _, _) = witness_matrix(4, &circuit, vec![]).unwrap();
+
+        let cells = circuit.loaded.borrow();
+        assert_eq!(cells.len(), values.len());
+        for (offset, (cell, value)) in cells.iter().zip(values.iter()).enumerate() {
+            assert_eq!(*cell.region_index(), 0);
+            assert_eq!(cell.column(), advice.into());
+            assert_eq!(cell.row_offset(), offset);
+            // The region is the first one, so its offsets are also absolute rows.
+            assert_eq!(witness[advice.index()][offset], Some(Fp::from(*value)));
+        }
+    }
+
+    #[test]
+    fn constrain_equal_assigned_cells() {
+        /// The simple example's `mul` region, with its input copies made by passing
+        /// either the cells or the assigned cells to `constrain_equal`.
+        struct CopyCircuit {
+            assigned: bool,
+            out: u64,
+        }
+
+        impl Circuit<Fp> for CopyCircuit {
+            type Config = MulConfig;
+            type FloorPlanner = SimpleFloorPlanner;
+
+            fn without_witnesses(&self) -> Self {
+                CopyCircuit {
+                    assigned: self.assigned,
+                    out: 0,
+                }
+            }
+
+            fn configure(meta: &mut ConstraintSystem<Fp>) -> Self::Config {
+                MulConfig::configure(meta)
+            }
+
+            fn synthesize(
+                &self,
+                config: Self::Config,
+                mut layouter: impl Layouter<Fp>,
+            ) -> Result<(), Error> {
+                let (a, b) = layouter.assign_region(
+                    || "load private",
+                    |mut region| config.load(&mut region, Fp::from(2), Fp::from(3)),
+                )?;
+
+                layouter.assign_region(
+                    || "mul",
+                    |mut region| {
+                        config.s_mul.enable(&mut region, 0)?;
+                        let lhs = region.assign_advice(
+                            || "lhs",
+                            config.advice[0],
+                            0,
+                            || Ok(Fp::from(2)),
+                        )?;
+                        let rhs = region.assign_advice(
+                            || "rhs",
+                            config.advice[1],
+                            0,
+                            || Ok(Fp::from(3)),
+                        )?;
+                        if self.assigned {
+                            region.constrain_equal(&a, &lhs)?;
+                            region.constrain_equal(&b, &rhs)?;
+                        } else {
+                            region.constrain_equal(a.cell(), lhs.cell())?;
+                            region.constrain_equal(b.cell(), rhs.cell())?;
+                        }
+                        region.assign_advice(
+                            || "out",
+                            config.advice[0],
+                            1,
+                            || Ok(Fp::from(self.out)),
+                        )?;
+                        Ok(())
+                    },
+                )
+            }
+        }
+
+        let run =
+            |assigned, out| MockProver::run(4, &CopyCircuit { assigned, out }, vec![]).unwrap();
+
+        // Both forms make the same copies, and verification is unchanged.
+        assert_eq!(
+            run(true, 6).copy_constraints(),
+            run(false, 6).copy_constraints()
+        );
+        assert_eq!(run(true, 6).verify(), Ok(()));
+        assert_eq!(run(false, 6).verify(), Ok(()));
+        assert!(run(true, 7).verify().is_err());
+        assert!(run(false, 7).verify().is_err());
+    }
+
+    #[test]
+    fn assigned_cell_value_combinators() {
+        let cell = Cell {
+            region_index: RegionIndex(0),
+            row_offset: 0,
+            column: Column::new(0, Any::Advice),
+        };
+        let assigned = |value: Option<u64>| AssignedCell::<Fp, Fp> {
+            value: value.map(Fp::from),
+            cell,
+            _marker: PhantomData,
+        };
+
+        // `value()` returns an `Option`, so derived witnesses can be computed with its
+        // combinators, which propagate the missing values of keygen uniformly.
+        let mul = |a: &AssignedCell<Fp, Fp>, b: &AssignedCell<Fp, Fp>| {
+            let zipped = a.value().zip(b.value()).map(|(a, b)| *a * *b);
+            let manual = a.value().and_then(|a| b.value().map(|b| *a * *b));
+            assert_eq!(zipped, manual);
+            zipped
+        };
+        assert_eq!(
+            mul(&assigned(Some(2)), &assigned(Some(3))),
+            Some(Fp::from(6))
+        );
+        assert_eq!(mul(&assigned(None), &assigned(Some(3))), None);
+        assert_eq!(mul(&assigned(Some(2)), &assigned(None)), None);
+        assert_eq!(mul(&assigned(None), &assigned(None)), None);
+    }
+
+    #[test]
+    fn assigned_cell_value() {
+        /// The simple example's `Number`, which needs no bookkeeping beyond the
+        /// [`AssignedCell`] returned by `assign_advice`.
+        struct Number(AssignedCell<Fp, Fp>);
+
+        struct MulCircuit {
+            a: Option<Fp>,
+            b: Option<Fp>,
+        }
+
+        impl Circuit<Fp> for MulCircuit {
+            type Config = MulConfig;
+            type FloorPlanner = SimpleFloorPlanner;
+
+            fn without_witnesses(&self) -> Self {
+                MulCircuit { a: None, b: None }
+            }
+
+            fn configure(meta: &mut ConstraintSystem<Fp>) -> Self::Config {
+                MulConfig::configure(meta)
+            }
+
+            fn synthesize(
+                &self,
+                config: Self::Config,
+                mut layouter: impl Layouter<Fp>,
+            ) -> Result<(), Error> {
+                let out = layouter.assign_region(
+                    || "mul",
+                    |mut region| {
+                        config.s_mul.enable(&mut region, 0)?;
+
+                        let a = region
+                            .assign_advice(
+                                || "a",
+                                config.advice[0],
+                                0,
+                                || self.a.ok_or(Error::Synthesis),
+                            )
+                            .map(Number)?;
+                        let b = region
+                            .assign_advice(
+                                || "b",
+                                config.advice[1],
+                                0,
+                                || self.b.ok_or(Error::Synthesis),
+                            )
+                            .map(Number)?;
+
+                        // The product is computed from the values carried by the cells.
+                        let value = a.0.value().and_then(|a| b.0.value().map(|b| *a * b));
+                        region
+                            .assign_advice(
+                                || "a * b",
+                                config.advice[0],
+                                1,
+                                || value.ok_or(Error::Synthesis),
+                            )
+                            .map(Number)
+                    },
+                )?;
+
+                assert_eq!(out.0.cell().column(), config.advice[0].into());
+                assert_eq!(out.0.cell().row_offset(), 1);
+                if self.a.is_some() {
+                    assert_eq!(out.0.value(), Some(&Fp::from(6)));
+                }
+                Ok(())
+            }
+        }
+
+        let circuit = MulCircuit {
+            a: Some(Fp::from(2)),
+            b: Some(Fp::from(3)),
+        };
+        let prover = MockProver::run(4, &circuit, vec![]).unwrap();
+        assert_eq!(prover.verify(), Ok(()));
+    }
+}
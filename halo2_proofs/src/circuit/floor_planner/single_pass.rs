@@ -1,5 +1,5 @@
 use std::cmp;
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::fmt;
 use std::marker::PhantomData;
 
@@ -7,9 +7,10 @@ use ff::Field;
 
 use crate::{
     circuit::{
-        layouter::{RegionColumn, RegionLayouter, RegionShape, TableLayouter},
+        layouter::{ClaimedCells, RegionColumn, RegionLayouter, RegionShape, TableLayouter},
         Cell, Layouter, Region, RegionIndex, RegionStart, Table,
     },
+    multicore,
     plonk::{
         Advice, Any, Assigned, Assignment, Circuit, Column, Error, Fixed, FloorPlanner, Instance,
         Selector, TableColumn,
@@ -46,6 +47,8 @@ pub struct SingleChipLayouter<'a, F: Field, CS: Assignment<F> + 'a> {
     columns: HashMap<RegionColumn, usize>,
     /// Stores the table fixed columns.
     table_columns: Vec<TableColumn>,
+    /// Stores the region that assigned each cell.
+    claimed: ClaimedCells,
     _marker: PhantomData<F>,
 }
 
@@ -67,10 +70,109 @@ impl<'a, F: Field, CS: Assignment<F>> SingleChipLayouter<'a, F, CS> {
             regions: vec![],
             columns: HashMap::default(),
             table_columns: vec![],
+            claimed: ClaimedCells::default(),
             _marker: PhantomData,
         };
         Ok(ret)
     }
+
+    /// Assigns the constants collected from a region. For the simple floor planner, we
+    /// assign constants in order in the first `constants` column.
+    fn assign_constants(
+        &mut self,
+        constants_to_assign: Vec<(Assigned<F>, Cell)>,
+    ) -> Result<(), Error> {
+        if self.constants.is_empty() {
+            if !constants_to_assign.is_empty() {
+                return Err(Error::NotEnoughColumnsForConstants);
+            }
+        } else {
+            let constants_column = self.constants[0];
+            let next_constant_row = self
+                .columns
+                .entry(Column::<Any>::from(constants_column).into())
+                .or_default();
+            for (constant, advice) in constants_to_assign {
+                self.cs.assign_fixed(
+                    || format!("Constant({:?})", constant.evaluate()),
+                    constants_column,
+                    *next_constant_row,
+                    || Ok(constant),
+                )?;
+                self.cs.copy(
+                    constants_column.into(),
+                    *next_constant_row,
+                    advice.column,
+                    *self.regions[*advice.region_index] + advice.row_offset,
+                )?;
+                *next_constant_row += 1;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Replays the operations buffered while synthesizing the region at
+    /// `region_index`.
+    fn replay(&mut self, region_index: RegionIndex, ops: Vec<RegionOp<F>>) -> Result<(), Error> {
+        let region_start = *self.regions[*region_index];
+        for op in ops {
+            match op {
+                RegionOp::EnableSelector {
+                    annotation,
+                    selector,
+                    offset,
+                } => {
+                    self.cs
+                        .enable_selector(|| annotation, &selector, region_start + offset)?;
+                }
+                RegionOp::AssignAdvice {
+                    annotation,
+                    column,
+                    offset,
+                    value,
+                } => {
+                    self.claimed
+                        .claim(region_index, column.into(), region_start + offset)?;
+                    self.cs.assign_advice(
+                        || annotation,
+                        column,
+                        region_start + offset,
+                        || value,
+                    )?;
+                }
+                RegionOp::AssignFixed {
+                    annotation,
+                    column,
+                    offset,
+                    value,
+                } => {
+                    self.claimed
+                        .claim(region_index, column.into(), region_start + offset)?;
+                    self.cs
+                        .assign_fixed(|| annotation, column, region_start + offset, || value)?;
+                }
+                RegionOp::ConstrainEqual(left, right) => {
+                    self.cs.copy(
+                        left.column,
+                        *self.regions[*left.region_index] + left.row_offset,
+                        right.column,
+                        *self.regions[*right.region_index] + right.row_offset,
+                    )?;
+                }
+                RegionOp::ConstrainInstance(cell, instance, row) => {
+                    self.cs.copy(
+                        cell.column,
+                        *self.regions[*cell.region_index] + cell.row_offset,
+                        instance.into(),
+                        row,
+                    )?;
+                }
+            }
+        }
+
+        Ok(())
+    }
 }
 
 impl<'a, F: Field, CS: Assignment<F> + 'a> Layouter<F> for SingleChipLayouter<'a, F, CS> {
@@ -114,36 +216,107 @@ impl<'a, F: Field, CS: Assignment<F> + 'a> Layouter<F> for SingleChipLayouter<'a
         let constants_to_assign = region.constants;
         self.cs.exit_region();
 
-        // Assign constants. For the simple floor planner, we assign constants in order in
-        // the first `constants` column.
-        if self.constants.is_empty() {
-            if !constants_to_assign.is_empty() {
-                return Err(Error::NotEnoughColumnsForConstants);
+        self.assign_constants(constants_to_assign)?;
+
+        Ok(result)
+    }
+
+    fn assign_regions<A, AR, N, NR>(
+        &mut self,
+        name: N,
+        mut assignments: Vec<A>,
+    ) -> Result<Vec<AR>, Error>
+    where
+        A: FnMut(Region<'_, F>) -> Result<AR, Error> + Send,
+        AR: Send,
+        N: Fn() -> NR,
+        NR: Into<String>,
+    {
+        let first_region = self.regions.len();
+
+        // Get the shape of each region.
+        let shapes = assignments
+            .iter_mut()
+            .enumerate()
+            .map(|(i, assignment)| {
+                let mut shape = RegionShape::new((first_region + i).into());
+                {
+                    let region: &mut dyn RegionLayouter<F> = &mut shape;
+                    assignment(region.into())?;
+                }
+                Ok(shape)
+            })
+            .collect::<Result<Vec<_>, Error>>()?;
+
+        // Regions can only be synthesized independently if no two of them share a
+        // column, and none of them uses the column that constants are assigned to
+        // in between regions.
+        let mut used: HashSet<RegionColumn> = self
+            .constants
+            .first()
+            .map(|column| Column::<Any>::from(*column).into())
+            .into_iter()
+            .collect();
+        let disjoint = shapes
+            .iter()
+            .all(|shape| shape.columns.iter().all(|column| used.insert(*column)));
+        if !disjoint {
+            return assignments
+                .into_iter()
+                .map(|assignment| self.assign_region(&name, assignment))
+                .collect();
+        }
+
+        // Lay out the regions exactly as `assign_region` would.
+        for shape in shapes {
+            let mut region_start = 0;
+            for column in &shape.columns {
+                region_start =
+                    cmp::max(region_start, self.columns.get(column).cloned().unwrap_or(0));
             }
-        } else {
-            let constants_column = self.constants[0];
-            let next_constant_row = self
-                .columns
-                .entry(Column::<Any>::from(constants_column).into())
-                .or_default();
-            for (constant, advice) in constants_to_assign {
-                self.cs.assign_fixed(
-                    || format!("Constant({:?})", constant.evaluate()),
-                    constants_column,
-                    *next_constant_row,
-                    || Ok(constant),
-                )?;
-                self.cs.copy(
-                    constants_column.into(),
-                    *next_constant_row,
-                    advice.column,
-                    *self.regions[*advice.region_index] + advice.row_offset,
-                )?;
-                *next_constant_row += 1;
+            self.regions.push(region_start.into());
+
+            for column in shape.columns {
+                self.columns.insert(column, region_start + shape.row_count);
             }
         }
 
-        Ok(result)
+        // Synthesize the regions in parallel into per-region buffers.
+        let witness = self.cs.evaluates_witness();
+        let mut buffers: Vec<_> = (0..assignments.len())
+            .map(|i| BufferedRegion::new((first_region + i).into(), witness))
+            .collect();
+        let mut results: Vec<Option<Result<AR, Error>>> =
+            (0..assignments.len()).map(|_| None).collect();
+        multicore::scope(|scope| {
+            for ((assignment, buffer), result) in assignments
+                .iter_mut()
+                .zip(buffers.iter_mut())
+                .zip(results.iter_mut())
+            {
+                scope.spawn(move |_| {
+                    let region: &mut dyn RegionLayouter<F> = buffer;
+                    *result = Some(assignment(region.into()));
+                });
+            }
+        });
+
+        // Merge the buffers into the assignment in region order.
+        buffers
+            .into_iter()
+            .zip(results.into_iter())
+            .map(|(buffer, result)| {
+                let result = result.expect("every region was synthesized")?;
+
+                self.cs.enter_region(&name);
+                self.replay(buffer.region_index, buffer.ops)?;
+                self.cs.exit_region();
+
+                self.assign_constants(buffer.constants)?;
+
+                Ok(result)
+            })
+            .collect()
     }
 
     fn assign_table<A, N, NR>(&mut self, name: N, mut assignment: A) -> Result<(), Error>
@@ -282,12 +455,13 @@ impl<'r, 'a, F: Field, CS: Assignment<F> + 'a> RegionLayouter<F>
         offset: usize,
         to: &'v mut (dyn FnMut() -> Result<Assigned<F>, Error> + 'v),
     ) -> Result<Cell, Error> {
-        self.layouter.cs.assign_advice(
-            annotation,
-            column,
-            *self.layouter.regions[*self.region_index] + offset,
-            to,
-        )?;
+        let row = *self.layouter.regions[*self.region_index] + offset;
+        self.layouter
+            .claimed
+            .claim(self.region_index, column.into(), row)?;
+        self.layouter
+            .cs
+            .assign_advice(annotation, column, row, to)?;
 
         Ok(Cell {
             region_index: self.region_index,
@@ -340,12 +514,11 @@ impl<'r, 'a, F: Field, CS: Assignment<F> + 'a> RegionLayouter<F>
         offset: usize,
         to: &'v mut (dyn FnMut() -> Result<Assigned<F>, Error> + 'v),
     ) -> Result<Cell, Error> {
-        self.layouter.cs.assign_fixed(
-            annotation,
-            column,
-            *self.layouter.regions[*self.region_index] + offset,
-            to,
-        )?;
+        let row = *self.layouter.regions[*self.region_index] + offset;
+        self.layouter
+            .claimed
+            .claim(self.region_index, column.into(), row)?;
+        self.layouter.cs.assign_fixed(annotation, column, row, to)?;
 
         Ok(Cell {
             region_index: self.region_index,
@@ -369,6 +542,180 @@ impl<'r, 'a, F: Field, CS: Assignment<F> + 'a> RegionLayouter<F>
 
         Ok(())
     }
+
+    fn constrain_instance(
+        &mut self,
+        cell: Cell,
+        instance: Column<Instance>,
+        row: usize,
+    ) -> Result<(), Error> {
+        self.layouter.cs.copy(
+            cell.column,
+            *self.layouter.regions[*cell.region_index] + cell.row_offset,
+            instance.into(),
+            row,
+        )
+    }
+}
+
+/// An operation performed on a region, buffered so that the region can be
+/// synthesized independently of the underlying assignment.
+#[derive(Debug)]
+enum RegionOp<F: Field> {
+    EnableSelector {
+        annotation: String,
+        selector: Selector,
+        offset: usize,
+    },
+    AssignAdvice {
+        annotation: String,
+        column: Column<Advice>,
+        offset: usize,
+        value: Result<Assigned<F>, Error>,
+    },
+    AssignFixed {
+        annotation: String,
+        column: Column<Fixed>,
+        offset: usize,
+        value: Result<Assigned<F>, Error>,
+    },
+    ConstrainEqual(Cell, Cell),
+    ConstrainInstance(Cell, Column<Instance>, usize),
+}
+
+/// A region that records its assignments instead of applying them, used by
+/// `SingleChipLayouter::assign_regions` to synthesize regions in parallel.
+#[derive(Debug)]
+struct BufferedRegion<F: Field> {
+    region_index: RegionIndex,
+    /// Whether the underlying assignment evaluates advice values. If not, they are
+    /// not evaluated while buffering either, matching sequential synthesis.
+    witness: bool,
+    ops: Vec<RegionOp<F>>,
+    /// Stores the constants to be assigned, and the cells to which they are copied.
+    constants: Vec<(Assigned<F>, Cell)>,
+}
+
+impl<F: Field> BufferedRegion<F> {
+    fn new(region_index: RegionIndex, witness: bool) -> Self {
+        BufferedRegion {
+            region_index,
+            witness,
+            ops: vec![],
+            constants: vec![],
+        }
+    }
+}
+
+impl<F: Field> RegionLayouter<F> for BufferedRegion<F> {
+    fn enable_selector<'v>(
+        &'v mut self,
+        annotation: &'v (dyn Fn() -> String + 'v),
+        selector: &Selector,
+        offset: usize,
+    ) -> Result<(), Error> {
+        self.ops.push(RegionOp::EnableSelector {
+            annotation: annotation(),
+            selector: *selector,
+            offset,
+        });
+        Ok(())
+    }
+
+    fn assign_advice<'v>(
+        &'v mut self,
+        annotation: &'v (dyn Fn() -> String + 'v),
+        column: Column<Advice>,
+        offset: usize,
+        to: &'v mut (dyn FnMut() -> Result<Assigned<F>, Error> + 'v),
+    ) -> Result<Cell, Error> {
+        let (annotation, value) = if self.witness {
+            (annotation(), to())
+        } else {
+            // The underlying assignment never evaluates this value.
+            (String::new(), Err(Error::Synthesis))
+        };
+        self.ops.push(RegionOp::AssignAdvice {
+            annotation,
+            column,
+            offset,
+            value,
+        });
+
+        Ok(Cell {
+            region_index: self.region_index,
+            row_offset: offset,
+            column: column.into(),
+        })
+    }
+
+    fn assign_advice_from_constant<'v>(
+        &'v mut self,
+        annotation: &'v (dyn Fn() -> String + 'v),
+        column: Column<Advice>,
+        offset: usize,
+        constant: Assigned<F>,
+    ) -> Result<Cell, Error> {
+        let advice = self.assign_advice(annotation, column, offset, &mut || Ok(constant))?;
+        self.constrain_constant(advice, constant)?;
+
+        Ok(advice)
+    }
+
+    fn assign_advice_from_instance<'v>(
+        &mut self,
+        _annotation: &'v (dyn Fn() -> String + 'v),
+        _instance: Column<Instance>,
+        _row: usize,
+        _advice: Column<Advice>,
+        _offset: usize,
+    ) -> Result<(Cell, Option<F>), Error> {
+        // Instance values live in the underlying assignment, which is not available
+        // while regions are synthesized in parallel.
+        Err(Error::Synthesis)
+    }
+
+    fn assign_fixed<'v>(
+        &'v mut self,
+        annotation: &'v (dyn Fn() -> String + 'v),
+        column: Column<Fixed>,
+        offset: usize,
+        to: &'v mut (dyn FnMut() -> Result<Assigned<F>, Error> + 'v),
+    ) -> Result<Cell, Error> {
+        self.ops.push(RegionOp::AssignFixed {
+            annotation: annotation(),
+            column,
+            offset,
+            value: to(),
+        });
+
+        Ok(Cell {
+            region_index: self.region_index,
+            row_offset: offset,
+            column: column.into(),
+        })
+    }
+
+    fn constrain_constant(&mut self, cell: Cell, constant: Assigned<F>) -> Result<(), Error> {
+        self.constants.push((constant, cell));
+        Ok(())
+    }
+
+    fn constrain_equal(&mut self, left: Cell, right: Cell) -> Result<(), Error> {
+        self.ops.push(RegionOp::ConstrainEqual(left, right));
+        Ok(())
+    }
+
+    fn constrain_instance(
+        &mut self,
+        cell: Cell,
+        instance: Column<Instance>,
+        row: usize,
+    ) -> Result<(), Error> {
+        self.ops
+            .push(RegionOp::ConstrainInstance(cell, instance, row));
+        Ok(())
+    }
 }
 
 /// The default value to fill a table column with.
@@ -457,8 +804,9 @@ mod tests {
 
     use super::SimpleFloorPlanner;
     use crate::{
-        dev::MockProver,
-        plonk::{Advice, Circuit, Column, Error},
+        circuit::{Layouter, Region},
+        dev::{mul_circuit::MulConfig, witness_matrix, MockProver},
+        plonk::{Advice, Circuit, Column, ConstraintSystem, Error},
     };
 
     #[test]
@@ -499,4 +847,177 @@ mod tests {
             Error::NotEnoughColumnsForConstants,
         ));
     }
+
+    #[test]
+    fn parallel_regions() {
+        fn mul(
+            config: &MulConfig,
+            a: u64,
+            b: u64,
+        ) -> impl FnMut(Region<'_, Scalar>) -> Result<(), Error> + Send + '_ {
+            move |mut region: Region<'_, Scalar>| {
+                let (a, b) = (Scalar::from(a), Scalar::from(b));
+                config.mul(&mut region, a, b, a * b)?;
+                Ok(())
+            }
+        }
+
+        /// Two independent multiplications on disjoint columns.
+        struct MyCircuit<const PARALLEL: bool> {}
+
+        impl<const PARALLEL: bool> Circuit<Scalar> for MyCircuit<PARALLEL> {
+            type Config = [MulConfig; 2];
+            type FloorPlanner = SimpleFloorPlanner;
+
+            fn without_witnesses(&self) -> Self {
+                MyCircuit {}
+            }
+
+            fn configure(meta: &mut ConstraintSystem<Scalar>) -> Self::Config {
+                [MulConfig::configure(meta), MulConfig::configure(meta)]
+            }
+
+            fn synthesize(
+                &self,
+                config: Self::Config,
+                mut layouter: impl Layouter<Scalar>,
+            ) -> Result<(), Error> {
+                if PARALLEL {
+                    layouter.assign_regions(
+                        || "mul",
+                        vec![mul(&config[0], 2, 3), mul(&config[1], 4, 5)],
+                    )?;
+                } else {
+                    layouter.assign_region(|| "mul", mul(&config[0], 2, 3))?;
+                    layouter.assign_region(|| "mul", mul(&config[1], 4, 5))?;
+                }
+                Ok(())
+            }
+        }
+
+        let prover = MockProver::run(4, &MyCircuit::<true> {}, vec![]).unwrap();
+        assert_eq!(prover.verify(), Ok(()));
+
+        assert_eq!(
+            witness_matrix(4, &MyCircuit::<true> {}, vec![]).unwrap(),
+            witness_matrix(4, &MyCircuit::<false> {}, vec![]).unwrap(),
+        );
+    }
+
+    #[test]
+    fn region_overlap() {
+        struct MyCircuit {}
+
+        impl Circuit<Scalar> for MyCircuit {
+            type Config = Column<Advice>;
+            type FloorPlanner = SimpleFloorPlanner;
+
+            fn without_witnesses(&self) -> Self {
+                MyCircuit {}
+            }
+
+            fn configure(meta: &mut ConstraintSystem<Scalar>) -> Self::Config {
+                meta.advice_column()
+            }
+
+            fn synthesize(
+                &self,
+                config: Self::Config,
+                mut layouter: impl Layouter<Scalar>,
+            ) -> Result<(), Error> {
+                // The first region only assigns its second row once it is being
+                // assigned, after it has been laid out with a single row...
+                let mut measured = false;
+                layouter.assign_region(
+                    || "drifting region",
+                    |mut region| {
+                        region.assign_advice(|| "a", config, 0, || Ok(Scalar::one()))?;
+                        if measured {
+                            region.assign_advice(|| "b", config, 1, || Ok(Scalar::one()))?;
+                        }
+                        measured = true;
+                        Ok(())
+                    },
+                )?;
+
+                // ...so it overwrites the first row of the next region.
+                layouter.assign_region(
+                    || "next region",
+                    |mut region| {
+                        region.assign_advice(|| "c", config, 0, || Ok(Scalar::one()))?;
+                        Ok(())
+                    },
+                )
+            }
+        }
+
+        assert!(matches!(
+            MockProver::run(3, &MyCircuit {}, vec![]).unwrap_err(),
+            Error::RegionOverlap {
+                row: 1,
+                region_a: 0,
+                region_b: 1,
+                ..
+            },
+        ));
+    }
+
+    #[test]
+    fn parallel_regions_keygen() {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+
+        use pairing::bn256::{Bn256, G1Affine};
+
+        use crate::{plonk::keygen_vk, poly::commitment::Params};
+
+        /// Counts how often its witness is evaluated.
+        #[derive(Default)]
+        struct MyCircuit {
+            evaluated: AtomicUsize,
+        }
+
+        impl Circuit<Scalar> for MyCircuit {
+            type Config = [Column<Advice>; 2];
+            type FloorPlanner = SimpleFloorPlanner;
+
+            fn without_witnesses(&self) -> Self {
+                MyCircuit::default()
+            }
+
+            fn configure(meta: &mut ConstraintSystem<Scalar>) -> Self::Config {
+                [meta.advice_column(), meta.advice_column()]
+            }
+
+            fn synthesize(
+                &self,
+                config: Self::Config,
+                mut layouter: impl Layouter<Scalar>,
+            ) -> Result<(), Error> {
+                let assign = |column: Column<Advice>| {
+                    move |mut region: Region<'_, Scalar>| {
+                        region.assign_advice(
+                            || "a",
+                            column,
+                            0,
+                            || {
+                                self.evaluated.fetch_add(1, Ordering::SeqCst);
+                                Ok(Scalar::one())
+                            },
+                        )?;
+                        Ok(())
+                    }
+                };
+                layouter.assign_regions(|| "a", vec![assign(config[0]), assign(config[1])])?;
+                Ok(())
+            }
+        }
+
+        let params: Params<G1Affine> = Params::<G1Affine>::unsafe_setup::<Bn256>(3);
+        let circuit = MyCircuit::default();
+        keygen_vk(&params, &circuit).unwrap();
+        assert_eq!(circuit.evaluated.load(Ordering::SeqCst), 0);
+
+        MockProver::run(3, &circuit, vec![]).unwrap();
+        assert_eq!(circuit.evaluated.load(Ordering::SeqCst), 2);
+    }
 }
@@ -5,7 +5,7 @@ use ff::Field;
 use crate::{
     circuit::{
         floor_planner::single_pass::SimpleTableLayouter,
-        layouter::{RegionColumn, RegionLayouter, RegionShape, TableLayouter},
+        layouter::{ClaimedCells, RegionColumn, RegionLayouter, RegionShape, TableLayouter},
         Cell, Layouter, Region, RegionIndex, RegionStart, Table,
     },
     plonk::{
@@ -35,6 +35,8 @@ struct V1Plan<'a, F: Field, CS: Assignment<F> + 'a> {
     constants: Vec<(Assigned<F>, Cell)>,
     /// Stores the table fixed columns.
     table_columns: Vec<TableColumn>,
+    /// Stores the region that assigned each cell.
+    claimed: ClaimedCells,
 }
 
 impl<'a, F: Field, CS: Assignment<F> + 'a> fmt::Debug for V1Plan<'a, F, CS> {
@@ -51,6 +53,7 @@ impl<'a, F: Field, CS: Assignment<F>> V1Plan<'a, F, CS> {
             regions: vec![],
             constants: vec![],
             table_columns: vec![],
+            claimed: ClaimedCells::default(),
         };
         Ok(ret)
     }
@@ -398,12 +401,11 @@ impl<'r, 'a, F: Field, CS: Assignment<F> + 'a> RegionLayouter<F> for V1Region<'r
         offset: usize,
         to: &'v mut (dyn FnMut() -> Result<Assigned<F>, Error> + 'v),
     ) -> Result<Cell, Error> {
-        self.plan.cs.assign_advice(
-            annotation,
-            column,
-            *self.plan.regions[*self.region_index] + offset,
-            to,
-        )?;
+        let row = *self.plan.regions[*self.region_index] + offset;
+        self.plan
+            .claimed
+            .claim(self.region_index, column.into(), row)?;
+        self.plan.cs.assign_advice(annotation, column, row, to)?;
 
         Ok(Cell {
             region_index: self.region_index,
@@ -456,12 +458,11 @@ impl<'r, 'a, F: Field, CS: Assignment<F> + 'a> RegionLayouter<F> for V1Region<'r
         offset: usize,
         to: &'v mut (dyn FnMut() -> Result<Assigned<F>, Error> + 'v),
     ) -> Result<Cell, Error> {
-        self.plan.cs.assign_fixed(
-            annotation,
-            column,
-            *self.plan.regions[*self.region_index] + offset,
-            to,
-        )?;
+        let row = *self.plan.regions[*self.region_index] + offset;
+        self.plan
+            .claimed
+            .claim(self.region_index, column.into(), row)?;
+        self.plan.cs.assign_fixed(annotation, column, row, to)?;
 
         Ok(Cell {
             region_index: self.region_index,
@@ -485,6 +486,20 @@ impl<'r, 'a, F: Field, CS: Assignment<F> + 'a> RegionLayouter<F> for V1Region<'r
 
         Ok(())
     }
+
+    fn constrain_instance(
+        &mut self,
+        cell: Cell,
+        instance: Column<Instance>,
+        row: usize,
+    ) -> Result<(), Error> {
+        self.plan.cs.copy(
+            cell.column,
+            *self.plan.regions[*cell.region_index] + cell.row_offset,
+            instance.into(),
+            row,
+        )
+    }
 }
 
 #[cfg(test)]
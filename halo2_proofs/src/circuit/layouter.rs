@@ -1,7 +1,7 @@
 //! Implementations of common circuit layouters.
 
 use std::cmp;
-use std::collections::HashSet;
+use std::collections::{hash_map::Entry, HashMap, HashSet};
 use std::fmt;
 
 use ff::Field;
@@ -102,6 +102,17 @@ pub trait RegionLayouter<F: Field>: fmt::Debug {
     ///
     /// Returns an error if either of the cells is not within the given permutation.
     fn constrain_equal(&mut self, left: Cell, right: Cell) -> Result<(), Error>;
+
+    /// Constrains a cell to equal the instance column's cell at absolute
+    /// location `row`.
+    ///
+    /// Returns an error if either of the cells is not within the given permutation.
+    fn constrain_instance(
+        &mut self,
+        cell: Cell,
+        instance: Column<Instance>,
+        row: usize,
+    ) -> Result<(), Error>;
 }
 
 /// Helper trait for implementing a custom [`Layouter`].
@@ -122,6 +133,37 @@ pub trait TableLayouter<F: Field>: fmt::Debug {
     ) -> Result<(), Error>;
 }
 
+/// The region that assigned each cell so far, which layouters use to detect regions
+/// that overwrite each other's cells.
+#[derive(Clone, Debug, Default)]
+pub(crate) struct ClaimedCells(HashMap<(Column<Any>, usize), usize>);
+
+impl ClaimedCells {
+    /// Records that the region at `region_index` assigns the cell in `column` at the
+    /// absolute `row`, returning [`Error::RegionOverlap`] if another region has
+    /// already assigned that cell.
+    pub(crate) fn claim(
+        &mut self,
+        region_index: RegionIndex,
+        column: Column<Any>,
+        row: usize,
+    ) -> Result<(), Error> {
+        match self.0.entry((column, row)) {
+            Entry::Occupied(entry) if *entry.get() != *region_index => Err(Error::RegionOverlap {
+                column,
+                row,
+                region_a: *entry.get(),
+                region_b: *region_index,
+            }),
+            Entry::Occupied(_) => Ok(()),
+            Entry::Vacant(entry) => {
+                entry.insert(*region_index);
+                Ok(())
+            }
+        }
+    }
+}
+
 /// The shape of a region. For a region at a certain index, we track
 /// the set of columns it uses as well as the number of rows it uses.
 #[derive(Clone, Debug)]
@@ -284,4 +326,14 @@ impl<F: Field> RegionLayouter<F> for RegionShape {
         // Equality constraints don't affect the region shape.
         Ok(())
     }
+
+    fn constrain_instance(
+        &mut self,
+        _cell: Cell,
+        _instance: Column<Instance>,
+        _row: usize,
+    ) -> Result<(), Error> {
+        // Instance columns are global, so they don't affect the region shape.
+        Ok(())
+    }
 }
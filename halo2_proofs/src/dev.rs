@@ -5,17 +5,27 @@ use std::collections::HashSet;
 use std::fmt;
 use std::iter;
 use std::ops::{Add, Mul, Neg, Range};
+use std::time::Duration;
+#[cfg(feature = "profile")]
+use std::time::Instant;
 
 use ff::Field;
+use rand_chacha::{rand_core::SeedableRng, ChaCha8Rng};
+use rand_core::RngCore;
 
 use crate::plonk::Assigned;
 use crate::{
-    arithmetic::{FieldExt, Group},
+    arithmetic::{FieldExt, Group, MultiMillerLoop},
     plonk::{
-        permutation, Advice, Any, Assignment, Circuit, Column, ColumnType, ConstraintSystem, Error,
-        Expression, Fixed, FloorPlanner, Instance, Selector, VirtualCell,
+        create_proof, keygen_pk, keygen_vk, permutation, verify_proof, Advice, Any, Assignment,
+        Circuit, Column, ColumnType, ConstraintSystem, Error, Expression, Fixed, FloorPlanner,
+        Instance, Selector, SingleVerifier, VirtualCell,
     },
-    poly::Rotation,
+    poly::{
+        commitment::{Params, ParamsVerifier},
+        Rotation,
+    },
+    transcript::{Blake2bRead, Blake2bWrite, Challenge255},
 };
 
 pub mod metadata;
@@ -27,6 +37,15 @@ pub use cost::CircuitCost;
 mod gates;
 pub use gates::CircuitGates;
 
+mod checking;
+pub use checking::CheckingAssignment;
+
+mod snapshot;
+pub use snapshot::{CircuitSnapshot, SnapshotDiff};
+
+#[cfg(test)]
+pub(crate) mod mul_circuit;
+
 #[cfg(feature = "dev-graph")]
 mod graph;
 
@@ -34,6 +53,35 @@ mod graph;
 #[cfg_attr(docsrs, doc(cfg(feature = "dev-graph")))]
 pub use graph::{circuit_dot_graph, layout::CircuitLayout};
 
+/// The environment variable that can be used to override the seed of [`test_rng`].
+pub const TEST_RNG_SEED_ENV: &str = "HALO2_TEST_SEED";
+
+/// The seed used by [`test_rng`] when [`TEST_RNG_SEED_ENV`] is not set.
+pub const DEFAULT_TEST_RNG_SEED: u64 = 0x5eed_5eed_5eed_5eed;
+
+/// Returns a deterministic RNG for use in tests.
+///
+/// The RNG is seeded with [`DEFAULT_TEST_RNG_SEED`], unless the environment variable
+/// [`TEST_RNG_SEED_ENV`] is set to a `u64`, in which case that value is used instead.
+/// This makes failures in proof and sampling tests reproducible: a failing run can be
+/// replayed by exporting the same seed.
+///
+/// ChaCha8 is used rather than `StdRng`, whose algorithm may change between `rand`
+/// releases, so that a seed reproduces the same values across upgrades.
+///
+/// # Panics
+///
+/// Panics if [`TEST_RNG_SEED_ENV`] is set but cannot be parsed as a `u64`.
+pub fn test_rng() -> ChaCha8Rng {
+    let seed = match std::env::var(TEST_RNG_SEED_ENV) {
+        Ok(seed) => seed
+            .parse()
+            .unwrap_or_else(|_| panic!("{} must be a u64, got {:?}", TEST_RNG_SEED_ENV, seed)),
+        Err(_) => DEFAULT_TEST_RNG_SEED,
+    };
+    ChaCha8Rng::seed_from_u64(seed)
+}
+
 /// The location within the circuit at which a particular [`VerifyFailure`] occurred.
 #[derive(Debug, PartialEq)]
 pub enum FailureLocation {
@@ -64,6 +112,14 @@ impl fmt::Display for FailureLocation {
 }
 
 impl FailureLocation {
+    /// Returns the absolute circuit row of this location.
+    fn row(&self, regions: &[Region]) -> usize {
+        match self {
+            Self::InRegion { region, offset } => regions[region.index()].rows.unwrap().0 + offset,
+            Self::OutsideRegion { row } => *row,
+        }
+    }
+
     fn find_expressions<'a, F: Field>(
         cs: &ConstraintSystem<F>,
         regions: &[Region],
@@ -173,6 +229,9 @@ pub enum VerifyFailure {
         /// - The input expressions use a column queried at a non-zero `Rotation`, and the
         ///   lookup is active on a row adjacent to an unrelated region.
         location: FailureLocation,
+        /// The values of the lookup's input expressions at this location, which do not
+        /// appear together in any row of the table.
+        input_values: Vec<String>,
     },
     /// A permutation did not preserve the original value of a cell.
     Permutation {
@@ -181,6 +240,14 @@ pub enum VerifyFailure {
         /// The row on which this permutation is not satisfied.
         row: usize,
     },
+    /// A cell that is part of an equality constraint was never assigned, so the prover
+    /// could choose any value for it.
+    PermutationCellNotAssigned {
+        /// The column of the unassigned cell.
+        column: metadata::Column,
+        /// The row of the unassigned cell.
+        row: usize,
+    },
 }
 
 impl fmt::Display for VerifyFailure {
@@ -220,11 +287,15 @@ impl fmt::Display for VerifyFailure {
                 name,
                 lookup_index,
                 location,
+                input_values,
             } => {
                 write!(
                     f,
-                    "Lookup {}(index: {}) is not satisfied {}",
-                    name, lookup_index, location
+                    "Lookup {}(index: {}) is not satisfied {} with inputs ({})",
+                    name,
+                    lookup_index,
+                    location,
+                    input_values.join(", ")
                 )
             }
             Self::Permutation { column, row } => {
@@ -234,7 +305,81 @@ impl fmt::Display for VerifyFailure {
                     column, row
                 )
             }
+            Self::PermutationCellNotAssigned { column, row } => {
+                write!(
+                    f,
+                    "Cell ({:?}, {}) is in an equality constraint but was never assigned",
+                    column, row
+                )
+            }
+        }
+    }
+}
+
+impl VerifyFailure {
+    /// Returns the key by which [`MockProver::verify`] orders failures: the failure
+    /// kind (in declaration order), then the gate or lookup index (and the constraint
+    /// index within a gate), then the absolute row, then the column.
+    fn sort_key(
+        &self,
+        regions: &[Region],
+    ) -> (u8, (usize, usize), isize, Option<metadata::Column>) {
+        match self {
+            Self::CellNotAssigned {
+                gate,
+                region,
+                column,
+                offset,
+            } => (
+                0,
+                (gate.index(), 0),
+                regions[region.index()].rows.unwrap().0 as isize + offset,
+                Some((*column).into()),
+            ),
+            Self::ConstraintNotSatisfied {
+                constraint,
+                location,
+                ..
+            } => (
+                1,
+                (constraint.gate_index(), constraint.index()),
+                location.row(regions) as isize,
+                None,
+            ),
+            Self::ConstraintPoisoned { constraint } => {
+                (2, (constraint.gate_index(), constraint.index()), 0, None)
+            }
+            Self::Lookup {
+                lookup_index,
+                location,
+                ..
+            } => (3, (*lookup_index, 0), location.row(regions) as isize, None),
+            Self::Permutation { column, row } => (4, (0, 0), *row as isize, Some(column.clone())),
+            Self::PermutationCellNotAssigned { column, row } => {
+                (5, (0, 0), *row as isize, Some(column.clone()))
+            }
+        }
+    }
+
+    /// Renders this failure in the context of the prover that produced it.
+    ///
+    /// This contains the same information as the [`fmt::Display`] output, and in
+    /// addition renders the polynomial of any failing constraint symbolically (for
+    /// example `F3@0 * (A0@0 * A1@0 - A0@1)`). Note that selectors have been
+    /// compressed into fixed columns by the time the constraints are checked, and are
+    /// rendered as such.
+    pub fn describe<F: FieldExt>(&self, prover: &MockProver<F>) -> String {
+        let mut ret = self.to_string();
+        if let Self::ConstraintNotSatisfied { constraint, .. }
+        | Self::ConstraintPoisoned { constraint } = self
+        {
+            let poly = &prover.cs.gates[constraint.gate_index()].polynomials()[constraint.index()];
+            if !ret.ends_with('\n') {
+                ret.push('\n');
+            }
+            ret.push_str(&format!("  {}\n", util::format_expression(poly)));
         }
+        ret
     }
 }
 
@@ -252,6 +397,12 @@ struct Region {
     /// The cells assigned in this region. We store this as a `HashMap` with count
     /// so that if any cells are double-assigned, they will be visibly darker.
     cells: HashMap<(Column<Any>, usize), usize>,
+    /// When synthesis of this region started.
+    #[cfg(feature = "profile")]
+    started: Instant,
+    /// How long this region took to synthesize, once it has been exited.
+    #[cfg(feature = "profile")]
+    synthesis_time: Duration,
 }
 
 impl Region {
@@ -501,7 +652,10 @@ pub struct MockProver<F: Group + Field> {
 
     selectors: Vec<Vec<bool>>,
 
-    permutation: permutation::keygen::Assembly,
+    // The assemblies of the permutation arguments, in the order of `cs.permutations()`.
+    permutations: Vec<permutation::keygen::Assembly>,
+    // The equality constraints made during synthesis, in order.
+    copies: Vec<((Column<Any>, usize), (Column<Any>, usize))>,
 
     // A range of available rows for assignment and copies.
     usable_rows: Range<usize>,
@@ -520,11 +674,21 @@ impl<F: Field + Group> Assignment<F> for MockProver<F> {
             rows: None,
             enabled_selectors: HashMap::default(),
             cells: HashMap::default(),
+            #[cfg(feature = "profile")]
+            started: Instant::now(),
+            #[cfg(feature = "profile")]
+            synthesis_time: Duration::default(),
         });
     }
 
     fn exit_region(&mut self) {
-        self.regions.push(self.current_region.take().unwrap());
+        #[cfg_attr(not(feature = "profile"), allow(unused_mut))]
+        let mut region = self.current_region.take().unwrap();
+        #[cfg(feature = "profile")]
+        {
+            region.synthesis_time = region.started.elapsed();
+        }
+        self.regions.push(region);
     }
 
     fn enable_selector<A, AR>(&mut self, _: A, selector: &Selector, row: usize) -> Result<(), Error>
@@ -636,8 +800,17 @@ impl<F: Field + Group> Assignment<F> for MockProver<F> {
             return Err(Error::not_enough_rows_available(self.k));
         }
 
-        self.permutation
-            .copy(left_column, left_row, right_column, right_row)
+        permutation::keygen::copy(
+            &mut self.permutations,
+            left_column,
+            left_row,
+            right_column,
+            right_row,
+        )?;
+        self.copies
+            .push(((left_column, left_row), (right_column, right_row)));
+
+        Ok(())
     }
 
     fn fill_from_row(
@@ -683,6 +856,8 @@ impl<F: FieldExt> MockProver<F> {
         let mut cs = ConstraintSystem::default();
         let config = ConcreteCircuit::configure(&mut cs);
         let cs = cs;
+        cs.check_selectors()?;
+        cs.check_rotations()?;
 
         if n < cs.minimum_rows() {
             return Err(Error::not_enough_rows_available(k));
@@ -705,7 +880,11 @@ impl<F: FieldExt> MockProver<F> {
             .collect::<Result<Vec<_>, _>>()?;
 
         // Fixed columns contain no blinding factors.
-        let fixed = vec![vec![CellValue::Unassigned; n]; cs.num_fixed_columns];
+        let fixed = cs
+            .fixed_defaults()
+            .into_iter()
+            .map(|default| vec![default.map_or(CellValue::Unassigned, CellValue::Assigned); n])
+            .collect();
         let selectors = vec![vec![false; n]; cs.num_selectors];
         // Advice columns contain blinding factors.
         let blinding_factors = cs.blinding_factors();
@@ -721,7 +900,10 @@ impl<F: FieldExt> MockProver<F> {
             };
             cs.num_advice_columns
         ];
-        let permutation = permutation::keygen::Assembly::new(n, &cs.permutation);
+        let permutations = cs
+            .permutations()
+            .map(|argument| permutation::keygen::Assembly::new(n, argument))
+            .collect();
         let constants = cs.constants.clone();
 
         let mut prover = MockProver {
@@ -734,7 +916,8 @@ impl<F: FieldExt> MockProver<F> {
             advice,
             instance,
             selectors,
-            permutation,
+            permutations,
+            copies: vec![],
             usable_rows: 0..usable_rows,
         };
 
@@ -753,20 +936,203 @@ impl<F: FieldExt> MockProver<F> {
         Ok(prover)
     }
 
+    /// Returns this `MockProver` with `selector` disabled on every row, as if the
+    /// circuit had never enabled it.
+    ///
+    /// This is intended for testing circuits: if a witness that fails verification
+    /// passes once a selector is forced off, then the gates controlled by that
+    /// selector are the ones catching it.
+    pub fn with_selector_forced_off(mut self, selector: Selector) -> Self {
+        // Selectors that are combined into the same fixed column are never enabled
+        // on the same row, so on every row where this selector is enabled, the
+        // column holds this selector's value alone.
+        let column = self.cs.selector_map[selector.0].index();
+        for (row, enabled) in self.selectors[selector.0].iter_mut().enumerate() {
+            if *enabled {
+                self.fixed[column][row] = CellValue::Assigned(F::zero());
+                *enabled = false;
+            }
+        }
+
+        for region in self.regions.iter_mut() {
+            region.enabled_selectors.remove(&selector);
+        }
+
+        self
+    }
+
+    /// Returns the name, the number of rows, and the synthesis time of each region in
+    /// the circuit, in the order in which the regions were assigned.
+    ///
+    /// Synthesis times are only measured with the `profile` feature enabled, and are
+    /// zero otherwise.
+    pub fn region_profile(&self) -> Vec<(String, usize, Duration)> {
+        self.regions
+            .iter()
+            .map(|region| {
+                let rows = region.rows.map_or(0, |(start, end)| end - start + 1);
+                #[cfg(feature = "profile")]
+                let synthesis_time = region.synthesis_time;
+                #[cfg(not(feature = "profile"))]
+                let synthesis_time = Duration::default();
+                (region.name.clone(), rows, synthesis_time)
+            })
+            .collect()
+    }
+
+    /// Returns the `(column, row)` positions of the instance cells that the circuit
+    /// actually uses, sorted by column and then by row.
+    ///
+    /// An instance cell is used if it is queried by a gate on a row where one of
+    /// the gate's selectors is enabled, or if it is part of an equality constraint
+    /// (for example via [`Layouter::constrain_instance`]). These are the positions
+    /// at which the public inputs must be provided.
+    ///
+    /// [`Layouter::constrain_instance`]: crate::circuit::Layouter::constrain_instance
+    pub fn queried_instance_cells(&self) -> Vec<(usize, usize)> {
+        let n = self.n as i32;
+
+        let gate_cells = self.regions.iter().flat_map(|r| {
+            r.enabled_selectors.iter().flat_map(move |(selector, at)| {
+                self.cs
+                    .gates
+                    .iter()
+                    .filter(move |g| g.queried_selectors().contains(selector))
+                    .flat_map(move |gate| {
+                        at.iter().flat_map(move |selector_row| {
+                            gate.queried_cells()
+                                .iter()
+                                .filter(|cell| cell.column.column_type() == &Any::Instance)
+                                .map(move |cell| {
+                                    let row = (*selector_row as i32 + n + cell.rotation.0) % n;
+                                    (cell.column.index(), row as usize)
+                                })
+                        })
+                    })
+            })
+        });
+
+        let mut copied_cells = vec![];
+        for (argument, assembly) in self.cs.permutations().zip(self.permutations.iter()) {
+            let columns = argument.get_columns();
+            for (column, values) in assembly.mapping.iter().enumerate() {
+                if columns[column].column_type() != &Any::Instance {
+                    continue;
+                }
+                copied_cells.extend(
+                    values
+                        .iter()
+                        .enumerate()
+                        .filter(|(row, cell)| **cell != (column, *row))
+                        .map(|(row, _)| (columns[column].index(), row)),
+                );
+            }
+        }
+
+        gate_cells
+            .chain(copied_cells)
+            .collect::<std::collections::BTreeSet<_>>()
+            .into_iter()
+            .collect()
+    }
+
+    /// Returns every equality constraint made during synthesis, as pairs of
+    /// `(column, row)` cells, grouped by the permutation cycle they belong to.
+    ///
+    /// Cycles are ordered by the first copy made into them, and the copies within a
+    /// cycle are in the order they were made. This includes the copies made by
+    /// [`Layouter::constrain_instance`] and to the circuit's constants.
+    ///
+    /// [`Layouter::constrain_instance`]: crate::circuit::Layouter::constrain_instance
+    #[allow(clippy::type_complexity)]
+    pub fn copy_constraints(
+        &self,
+    ) -> Vec<Vec<((metadata::Column, usize), (metadata::Column, usize))>> {
+        let mut cycles: Vec<Vec<_>> = vec![];
+        // The index in `cycles` of each cycle, identified by its permutation argument
+        // and its distinguished cell.
+        let mut indices: HashMap<(usize, (usize, usize)), usize> = HashMap::new();
+        for &((left_column, left_row), (right_column, right_row)) in &self.copies {
+            let cycle = self
+                .permutations
+                .iter()
+                .enumerate()
+                .find_map(|(argument, assembly)| {
+                    assembly
+                        .cycle(left_column, left_row)
+                        .map(|cell| (argument, cell))
+                })
+                .unwrap();
+            let index = *indices.entry(cycle).or_insert_with(|| {
+                cycles.push(vec![]);
+                cycles.len() - 1
+            });
+            cycles[index].push((
+                (left_column.into(), left_row),
+                (right_column.into(), right_row),
+            ));
+        }
+
+        cycles
+    }
+
     /// Returns `Ok(())` if this `MockProver` is satisfied, or a list of errors indicating
     /// the reasons that the circuit is not satisfied.
+    ///
+    /// The errors are sorted by kind (in the order in which the [`VerifyFailure`]
+    /// variants are declared), then by gate or lookup index, then by row, then by
+    /// column, so that repeated runs on the same circuit return identical lists.
     pub fn verify(&self) -> Result<(), Vec<VerifyFailure>> {
         self.verify_at_rows(self.usable_rows.clone(), self.usable_rows.clone())
     }
 
+    /// Panics if this `MockProver` is not satisfied.
+    ///
+    /// The panic message lists every failure returned by [`MockProver::verify`], each
+    /// rendered by [`VerifyFailure::describe`], so that the polynomial of a failing
+    /// constraint appears in the test output.
+    pub fn assert_satisfied(&self) {
+        if let Err(failures) = self.verify() {
+            let failures: Vec<_> = failures.iter().map(|f| f.describe(self)).collect();
+            panic!("circuit was not satisfied:\n\n{}", failures.join("\n"));
+        }
+    }
+
     /// Returns `Ok(())` if this `MockProver` is satisfied, or a list of errors indicating
     /// the reasons that the circuit is not satisfied.
     /// Constraints are only checked at `gate_row_ids`,
     /// and lookup inputs are only checked at `lookup_input_row_ids`
+    ///
+    /// The errors are ordered as for [`MockProver::verify`].
     pub fn verify_at_rows<I: Clone + Iterator<Item = usize>>(
         &self,
         gate_row_ids: I,
         lookup_input_row_ids: I,
+    ) -> Result<(), Vec<VerifyFailure>> {
+        self.verify_rows(gate_row_ids, lookup_input_row_ids, &(0..self.n as usize))
+    }
+
+    /// Returns `Ok(())` if the rows `rows` of this `MockProver` are satisfied, or a
+    /// list of errors indicating the reasons that they are not.
+    ///
+    /// Gates, lookup inputs, the assignment of cells used by enabled gates, and
+    /// equality constraints are only checked on these rows, which must be usable.
+    /// Lookup inputs are still looked up in the whole table, so a lookup table does
+    /// not need to lie within `rows`.
+    ///
+    /// The errors are ordered as for [`MockProver::verify`].
+    pub fn verify_range(&self, rows: Range<usize>) -> Result<(), Vec<VerifyFailure>> {
+        self.verify_rows(rows.clone(), rows.clone(), &rows)
+    }
+
+    /// Checks gates at `gate_row_ids` and lookup inputs at `lookup_input_row_ids`.
+    /// The assignment of cells used by enabled gates, and equality constraints, are
+    /// only checked on `rows`.
+    fn verify_rows<I: Clone + Iterator<Item = usize>>(
+        &self,
+        gate_row_ids: I,
+        lookup_input_row_ids: I,
+        rows: &Range<usize>,
     ) -> Result<(), Vec<VerifyFailure>> {
         let n = self.n as i32;
 
@@ -809,7 +1175,9 @@ impl<F: FieldExt> MockProver<F> {
                                 let cell_row = ((gate_row + n + cell.rotation.0) % n) as usize;
 
                                 // Check that it was assigned!
-                                if r.is_assigned(cell.column, cell_row) {
+                                if !rows.contains(selector_row)
+                                    || r.is_assigned(cell.column, cell_row)
+                                {
                                     None
                                 } else {
                                     Some(VerifyFailure::CellNotAssigned {
@@ -999,50 +1367,67 @@ impl<F: FieldExt> MockProver<F> {
                                         input_row,
                                         lookup.input_expressions.iter(),
                                     ),
+                                    input_values: inputs
+                                        .into_iter()
+                                        .map(|value| match value {
+                                            Value::Real(v) => util::format_value(v),
+                                            Value::Poison => "Poison".to_owned(),
+                                        })
+                                        .collect(),
                                 })
                             }
                         })
                 });
 
-        // Check that permutations preserve the original values of the cells.
-        let perm_errors = {
+        // Check that permutations preserve the original values of the cells, in each
+        // of the permutation arguments.
+        let mut perm_errors = vec![];
+        for (argument, assembly) in self.cs.permutations().zip(self.permutations.iter()) {
+            let columns = argument.get_columns();
+
             // Original values of columns involved in the permutation.
-            let original = |column, row| {
-                self.cs
-                    .permutation
-                    .get_columns()
-                    .get(column)
-                    .map(|c: &Column<Any>| match c.column_type() {
-                        Any::Advice => self.advice[c.index()][row],
-                        Any::Fixed => self.fixed[c.index()][row],
-                        Any::Instance => CellValue::Assigned(self.instance[c.index()][row]),
-                    })
-                    .unwrap()
+            let original = |column: usize, row: usize| {
+                let c = &columns[column];
+                match c.column_type() {
+                    Any::Advice => self.advice[c.index()][row],
+                    Any::Fixed => self.fixed[c.index()][row],
+                    Any::Instance => CellValue::Assigned(self.instance[c.index()][row]),
+                }
             };
 
             // Iterate over each column of the permutation
-            self.permutation
-                .mapping
-                .iter()
-                .enumerate()
-                .flat_map(move |(column, values)| {
+            perm_errors.extend(assembly.mapping.iter().enumerate().flat_map(
+                |(column_index, values)| {
                     // Iterate over each row of the column to check that the cell's
                     // value is preserved by the mapping.
+                    let column: metadata::Column = columns[column_index].into();
                     values.iter().enumerate().filter_map(move |(row, cell)| {
-                        let original_cell = original(column, row);
+                        if !rows.contains(&row) {
+                            return None;
+                        }
+                        let original_cell = original(column_index, row);
                         let permuted_cell = original(cell.0, cell.1);
-                        if original_cell == permuted_cell {
+                        let in_cycle = *cell != (column_index, row);
+                        if in_cycle && original_cell == CellValue::Unassigned {
+                            Some(VerifyFailure::PermutationCellNotAssigned {
+                                column: column.clone(),
+                                row,
+                            })
+                        } else if original_cell == permuted_cell
+                            || permuted_cell == CellValue::Unassigned
+                        {
+                            // An unassigned permuted cell is reported on its own row.
                             None
                         } else {
                             Some(VerifyFailure::Permutation {
-                                column: (*self.cs.permutation.get_columns().get(column).unwrap())
-                                    .into(),
+                                column: column.clone(),
                                 row,
                             })
                         }
                     })
-                })
-        };
+                },
+            ));
+        }
 
         let mut errors: Vec<_> = iter::empty()
             .chain(selector_errors)
@@ -1053,6 +1438,8 @@ impl<F: FieldExt> MockProver<F> {
         if errors.is_empty() {
             Ok(())
         } else {
+            errors.sort_by_cached_key(|error| error.sort_key(&self.regions));
+
             // Remove any duplicate `ConstraintPoisoned` errors (we check all unavailable
             // rows in case the trigger is row-specific, but the error message only points
             // at the constraint).
@@ -1068,20 +1455,356 @@ impl<F: FieldExt> MockProver<F> {
     }
 }
 
+/// Renders a multi-line diagnostic report for `failure`, suitable for printing from a
+/// command-line tool.
+///
+/// For a constraint that is not satisfied, the report lists the gate and constraint
+/// that failed along with its polynomial, the region and offset of the failure and
+/// its absolute row, the value of each cell queried by the gate, and the non-zero
+/// value that the polynomial evaluates to on that row. Other failures are rendered
+/// as by [`VerifyFailure::describe`].
+pub fn explain_failure<F: FieldExt>(prover: &MockProver<F>, failure: &VerifyFailure) -> String {
+    let (constraint, location, cell_values) = match failure {
+        VerifyFailure::ConstraintNotSatisfied {
+            constraint,
+            location,
+            cell_values,
+        } => (constraint, location, cell_values),
+        _ => return failure.describe(prover),
+    };
+
+    let gate = &prover.cs.gates[constraint.gate_index()];
+    let poly = &gate.polynomials()[constraint.index()];
+    let row = location.row(&prover.regions);
+
+    let n = prover.n as i32;
+    let resolve = |at: Rotation| ((row as i32 + n + at.0) % n) as usize;
+    let result = poly.evaluate(
+        &|scalar| Value::Real(scalar),
+        &|_| panic!("virtual selectors are removed during optimization"),
+        &|index, _, _| {
+            let (column, at) = prover.cs.fixed_queries[index];
+            prover.fixed[column.index()][resolve(at)].into()
+        },
+        &|index, _, _| {
+            let (column, at) = prover.cs.advice_queries[index];
+            prover.advice[column.index()][resolve(at)].into()
+        },
+        &|index, _, _| {
+            let (column, at) = prover.cs.instance_queries[index];
+            Value::Real(prover.instance[column.index()][resolve(at)])
+        },
+        &|a| -a,
+        &|a, b| a + b,
+        &|a, b| a * b,
+        &|a, scalar| a * scalar,
+    );
+
+    let mut ret = format!("{} is not satisfied {}\n\n", constraint, location);
+    ret.push_str("Gate:\n");
+    ret.push_str(&format!(
+        "  Gate {} ('{}'), constraint {}",
+        constraint.gate_index(),
+        gate.name(),
+        constraint.index()
+    ));
+    let constraint_name = gate.constraint_name(constraint.index());
+    if !constraint_name.is_empty() {
+        ret.push_str(&format!(" ('{}')", constraint_name));
+    }
+    ret.push_str(&format!("\n  {}\n\n", util::format_expression(poly)));
+
+    ret.push_str("Location:\n");
+    match location {
+        FailureLocation::InRegion { region, offset } => ret.push_str(&format!(
+            "  {} at offset {} (row {})\n\n",
+            region, offset, row
+        )),
+        FailureLocation::OutsideRegion { .. } => {
+            ret.push_str(&format!("  outside any region (row {})\n\n", row))
+        }
+    }
+
+    ret.push_str("Cells:\n");
+    for (cell, value) in cell_values {
+        ret.push_str(&format!("  {} = {}\n", cell, value));
+    }
+
+    ret.push_str("\nResult:\n");
+    ret.push_str(&format!(
+        "  {}\n",
+        match result {
+            Value::Real(v) => util::format_value(v),
+            Value::Poison => "poisoned".into(),
+        }
+    ));
+    ret
+}
+
+/// Synthesizes `circuit` with the given `instance` values, and returns the resulting
+/// witness as column-major `(advice, fixed, instance)` matrices, arranged as
+/// `[column][row]`.
+///
+/// Unassigned advice and fixed cells, as well as the advice cells in the rows reserved
+/// for blinding factors, are `None`. The fixed matrix includes the columns that
+/// selectors are compressed into, after the circuit's own fixed columns.
+#[allow(clippy::type_complexity)]
+pub fn witness_matrix<F: FieldExt, ConcreteCircuit: Circuit<F>>(
+    k: u32,
+    circuit: &ConcreteCircuit,
+    instance: Vec<Vec<F>>,
+) -> Result<(Vec<Vec<Option<F>>>, Vec<Vec<Option<F>>>, Vec<Vec<F>>), Error> {
+    let prover = MockProver::run(k, circuit, instance)?;
+
+    let to_matrix = |columns: Vec<Vec<CellValue<F>>>| {
+        columns
+            .into_iter()
+            .map(|column| {
+                column
+                    .into_iter()
+                    .map(|cell| match cell {
+                        CellValue::Assigned(v) => Some(v),
+                        CellValue::Unassigned | CellValue::Poison(_) => None,
+                    })
+                    .collect()
+            })
+            .collect()
+    };
+
+    Ok((
+        to_matrix(prover.advice),
+        to_matrix(prover.fixed),
+        prover.instance,
+    ))
+}
+
+/// Builds the `instance` argument of [`MockProver::run`] by logical position, rather
+/// than by indexing into hand-allocated vectors.
+///
+/// ```
+/// use halo2_proofs::{dev::InstanceBuilder, pairing::bn256::Fr as Fp, plonk::ConstraintSystem};
+///
+/// let mut meta = ConstraintSystem::<Fp>::default();
+/// let instance = meta.instance_column();
+///
+/// let mut builder = InstanceBuilder::new(4, &meta);
+/// builder.set(instance, 2, Fp::from(7)).unwrap();
+/// let columns = builder.build();
+/// assert_eq!(columns[0][..3], [Fp::from(0), Fp::from(0), Fp::from(7)]);
+/// assert_eq!(columns[0].len(), 16 - (meta.blinding_factors() + 1));
+/// ```
+#[derive(Debug)]
+pub struct InstanceBuilder<F: Field> {
+    usable_rows: usize,
+    columns: Vec<Vec<F>>,
+}
+
+impl<F: Field> InstanceBuilder<F> {
+    /// Creates a builder for the instance columns of `cs`, in a circuit of size
+    /// `2^k`, with every cell initially zero.
+    pub fn new(k: u32, cs: &ConstraintSystem<F>) -> Self {
+        let usable_rows = (1 << k) - (cs.blinding_factors() + 1);
+        InstanceBuilder {
+            usable_rows,
+            columns: vec![vec![F::zero(); usable_rows]; cs.num_instance_columns()],
+        }
+    }
+
+    /// Sets the cell at `row` of `column` to `value`.
+    ///
+    /// Returns [`Error::InvalidInstances`] if the circuit has no such column, and
+    /// [`Error::InstanceTooLarge`] if `row` is not one of the circuit's usable rows,
+    /// which excludes the rows reserved for blinding factors.
+    pub fn set(&mut self, column: Column<Instance>, row: usize, value: F) -> Result<(), Error> {
+        let cells = self
+            .columns
+            .get_mut(column.index())
+            .ok_or(Error::InvalidInstances)?;
+        if row >= self.usable_rows {
+            return Err(Error::InstanceTooLarge);
+        }
+
+        cells[row] = value;
+        Ok(())
+    }
+
+    /// Returns the instance columns, each zero-padded to the circuit's usable rows.
+    ///
+    /// This is the longest instance that [`MockProver::run`], the prover, and the
+    /// verifier accept.
+    pub fn build(self) -> Vec<Vec<F>> {
+        self.columns
+    }
+}
+
+/// A cell whose assignment differs between two synthesis runs, as reported by
+/// [`diff_assignments`].
+#[derive(Debug, PartialEq)]
+pub struct CellDiff<F: Field> {
+    /// The column containing the cell.
+    pub column: metadata::Column,
+    /// The row of the cell.
+    pub row: usize,
+    /// The value assigned by the first circuit, if any.
+    pub a: Option<F>,
+    /// The value assigned by the second circuit, if any.
+    pub b: Option<F>,
+}
+
+/// Synthesizes `circuit_a` and `circuit_b` with the same `instance` values, and
+/// returns every advice or fixed cell whose assignment differs between them.
+///
+/// Cells are ordered by column (advice columns first, then fixed columns), and then
+/// by row. This is useful for tracking down which part of a witness changed after
+/// a regression.
+pub fn diff_assignments<F: FieldExt, ConcreteCircuit: Circuit<F>>(
+    k: u32,
+    circuit_a: &ConcreteCircuit,
+    circuit_b: &ConcreteCircuit,
+    instance: Vec<Vec<F>>,
+) -> Result<Vec<CellDiff<F>>, Error> {
+    let (advice_a, fixed_a, _) = witness_matrix(k, circuit_a, instance.clone())?;
+    let (advice_b, fixed_b, _) = witness_matrix(k, circuit_b, instance)?;
+
+    let columns = |column_type: Any, a: Vec<Vec<Option<F>>>, b: Vec<Vec<Option<F>>>| {
+        a.into_iter()
+            .zip(b.into_iter())
+            .enumerate()
+            .flat_map(move |(index, (a, b))| {
+                a.into_iter()
+                    .zip(b.into_iter())
+                    .enumerate()
+                    .filter(|(_, (a, b))| a != b)
+                    .map(move |(row, (a, b))| CellDiff {
+                        column: (column_type, index).into(),
+                        row,
+                        a,
+                        b,
+                    })
+            })
+    };
+
+    Ok(columns(Any::Advice, advice_a, advice_b)
+        .chain(columns(Any::Fixed, fixed_a, fixed_b))
+        .collect())
+}
+
+/// Runs the real prover and verifier on `circuit` over the pairing-friendly curve
+/// `E`, as a one-call smoke test complementing [`MockProver`].
+///
+/// This generates parameters and keys for `circuit`, creates a proof for the given
+/// `instance` columns with a Blake2b transcript and the blinding randomness `rng`, and
+/// verifies it. The parameters come from [`Params::unsafe_setup`], so this must only
+/// be used for testing.
+pub fn quick_prove_verify<E: MultiMillerLoop, ConcreteCircuit: Circuit<E::Scalar>, R: RngCore>(
+    k: u32,
+    circuit: &ConcreteCircuit,
+    instance: Vec<Vec<E::Scalar>>,
+    rng: R,
+) -> Result<(), Error> {
+    let params = Params::<E::G1Affine>::unsafe_setup::<E>(k);
+    let public_inputs_size = instance
+        .iter()
+        .map(|column| column.len())
+        .max()
+        .unwrap_or(0);
+    let params_verifier: ParamsVerifier<E> = params.verifier(public_inputs_size)?;
+
+    let vk = keygen_vk(&params, &circuit.without_witnesses())?;
+    let pk = keygen_pk(&params, vk, &circuit.without_witnesses())?;
+
+    let instance: Vec<&[E::Scalar]> = instance.iter().map(|column| &column[..]).collect();
+    let mut transcript = Blake2bWrite::<_, _, Challenge255<_>>::init(vec![]);
+    create_proof(
+        &params,
+        &pk,
+        std::slice::from_ref(circuit),
+        &[&instance],
+        rng,
+        &mut transcript,
+    )?;
+    let proof = transcript.finalize();
+
+    let strategy = SingleVerifier::new(&params_verifier);
+    let mut transcript = Blake2bRead::<_, _, Challenge255<_>>::init(&proof[..]);
+    verify_proof(
+        &params_verifier,
+        pk.get_vk(),
+        strategy,
+        &[&instance],
+        &mut transcript,
+    )
+}
+
+/// Checks `circuit` with both the [`MockProver`] and the real prover and verifier (as
+/// run by [`quick_prove_verify`]), and panics if exactly one of them accepts it.
+///
+/// The real prover is run over `E` with the blinding randomness `rng`. Returns whether
+/// the circuit was accepted. A disagreement points at a check that one of the two
+/// provers is missing, so this is useful for testing the provers themselves as well as
+/// circuits.
+pub fn cross_check<E: MultiMillerLoop, ConcreteCircuit: Circuit<E::Scalar>, R: RngCore>(
+    k: u32,
+    circuit: &ConcreteCircuit,
+    instance: Vec<Vec<E::Scalar>>,
+    rng: R,
+) -> bool {
+    let mock = match MockProver::run(k, circuit, instance.clone()) {
+        Ok(prover) => prover.verify().map_err(|failures| {
+            failures
+                .iter()
+                .map(|failure| failure.to_string())
+                .collect::<Vec<_>>()
+                .join("\n")
+        }),
+        Err(e) => Err(e.to_string()),
+    };
+    let real = quick_prove_verify::<E, _, _>(k, circuit, instance, rng);
+
+    match (mock, real) {
+        (Ok(()), Err(e)) => panic!(
+            "the mock prover accepts the circuit, but the real prover rejects it: {}",
+            e
+        ),
+        (Err(failures), Ok(())) => panic!(
+            "the real prover accepts the circuit, but the mock prover rejects it:\n{}",
+            failures
+        ),
+        (mock, _) => mock.is_ok(),
+    }
+}
+
 #[cfg(test)]
 mod tests {
-    use pairing::bn256::Fr as Fp;
+    use pairing::bn256::{Bn256, Fr as Fp};
 
-    use super::{FailureLocation, MockProver, VerifyFailure};
+    use rand_core::RngCore;
+
+    use super::{
+        explain_failure, metadata,
+        mul_circuit::{MulCircuit, MulConfig, PublicMulCircuit},
+        test_rng, CellDiff, CheckingAssignment, FailureLocation, InstanceBuilder, MockProver,
+        SnapshotDiff, VerifyFailure,
+    };
     use crate::{
         circuit::{Layouter, SimpleFloorPlanner},
         plonk::{
-            Advice, Any, Circuit, Column, ConstraintSystem, Error, Expression, Selector,
-            TableColumn,
+            Advice, Any, Assignment, Circuit, Column, ConstraintSystem, Error, Expression, Fixed,
+            Instance, Selector, TableColumn,
         },
         poly::Rotation,
     };
 
+    #[test]
+    fn test_rng_is_deterministic() {
+        let mut a = test_rng();
+        let mut b = test_rng();
+
+        let a: Vec<u64> = (0..16).map(|_| a.next_u64()).collect();
+        let b: Vec<u64> = (0..16).map(|_| b.next_u64()).collect();
+        assert_eq!(a, b);
+    }
+
     #[test]
     fn unassigned_cell() {
         const K: u32 = 4;
@@ -1155,39 +1878,18 @@ mod tests {
     }
 
     #[test]
-    fn bad_lookup() {
+    fn selector_without_output() {
         const K: u32 = 4;
 
-        #[derive(Clone)]
-        struct FaultyCircuitConfig {
-            a: Column<Advice>,
-            q: Selector,
-            table: TableColumn,
-        }
-
-        struct FaultyCircuit {}
+        /// Enables `s_mul`, but forgets to assign the output on the next row.
+        struct MissingOutCircuit {}
 
-        impl Circuit<Fp> for FaultyCircuit {
-            type Config = FaultyCircuitConfig;
+        impl Circuit<Fp> for MissingOutCircuit {
+            type Config = MulConfig;
             type FloorPlanner = SimpleFloorPlanner;
 
             fn configure(meta: &mut ConstraintSystem<Fp>) -> Self::Config {
-                let a = meta.advice_column();
-                let q = meta.complex_selector();
-                let table = meta.lookup_table_column();
-
-                meta.lookup("lookup", |cells| {
-                    let a = cells.query_advice(a, Rotation::cur());
-                    let q = cells.query_selector(q);
-
-                    // If q is enabled, a must be in the table.
-                    // When q is not enabled, lookup the default value instead.
-                    let not_q = Expression::Constant(Fp::one()) - q.clone();
-                    let default = Expression::Constant(Fp::from(2));
-                    vec![(q * a + not_q * default, table)]
-                });
-
-                FaultyCircuitConfig { a, q, table }
+                MulConfig::configure(meta)
             }
 
             fn without_witnesses(&self) -> Self {
@@ -1199,67 +1901,1864 @@ mod tests {
                 config: Self::Config,
                 mut layouter: impl Layouter<Fp>,
             ) -> Result<(), Error> {
-                layouter.assign_table(
-                    || "Doubling table",
-                    |mut table| {
-                        (1..(1 << (K - 1)))
-                            .map(|i| {
-                                table.assign_cell(
-                                    || format!("table[{}] = {}", i, 2 * i),
-                                    config.table,
-                                    i - 1,
-                                    || Ok(Fp::from(2 * i as u64)),
-                                )
-                            })
-                            .fold(Ok(()), |acc, res| acc.and(res))
-                    },
-                )?;
-
                 layouter.assign_region(
-                    || "Good synthesis",
+                    || "mul",
                     |mut region| {
-                        // Enable the lookup on rows 0 and 1.
-                        config.q.enable(&mut region, 0)?;
-                        config.q.enable(&mut region, 1)?;
-
-                        // Assign a = 2 and a = 6.
-                        region.assign_advice(|| "a = 2", config.a, 0, || Ok(Fp::from(2)))?;
-                        region.assign_advice(|| "a = 6", config.a, 1, || Ok(Fp::from(6)))?;
-
+                        config.s_mul.enable(&mut region, 0)?;
+                        config.load(&mut region, Fp::from(2), Fp::from(3))?;
                         Ok(())
                     },
-                )?;
-
+                )
+            }
+        }
+
+        let prover = MockProver::run(K, &MissingOutCircuit {}, vec![]).unwrap();
+        let errors = prover.verify().unwrap_err();
+        assert!(errors.contains(&VerifyFailure::CellNotAssigned {
+            gate: (0, "mul").into(),
+            region: (0, "mul".to_owned()).into(),
+            column: Column::new(0, Any::Advice),
+            offset: 1,
+        }));
+    }
+
+    #[test]
+    fn selector_forced_off() {
+        const K: u32 = 4;
+
+        let config = MulCircuit::configure(&mut ConstraintSystem::default());
+
+        // 2 * 3 != 7
+        let prover = MockProver::run(K, &MulCircuit::new(2, 3, 7), vec![]).unwrap();
+        assert!(matches!(
+            &prover.verify().unwrap_err()[..],
+            [VerifyFailure::ConstraintNotSatisfied { .. }]
+        ));
+
+        let prover = prover.with_selector_forced_off(config.s_mul);
+        assert_eq!(prover.verify(), Ok(()));
+    }
+
+    #[test]
+    fn selector_in_blinding_row() {
+        const K: u32 = 4;
+
+        #[derive(Clone)]
+        struct BoolConfig {
+            a: Column<Advice>,
+            s_bool: Selector,
+        }
+
+        struct BoolCircuit {
+            row: usize,
+        }
+
+        impl Circuit<Fp> for BoolCircuit {
+            type Config = BoolConfig;
+            type FloorPlanner = SimpleFloorPlanner;
+
+            fn configure(meta: &mut ConstraintSystem<Fp>) -> Self::Config {
+                let a = meta.advice_column();
+                let s_bool = meta.selector();
+
+                meta.create_gate("bool", |meta| {
+                    let a = meta.query_advice(a, Rotation::cur());
+                    let s_bool = meta.query_selector(s_bool);
+                    vec![s_bool * a.clone() * (Expression::Constant(Fp::one()) - a)]
+                });
+
+                BoolConfig { a, s_bool }
+            }
+
+            fn without_witnesses(&self) -> Self {
+                Self { row: self.row }
+            }
+
+            fn synthesize(
+                &self,
+                config: Self::Config,
+                mut layouter: impl Layouter<Fp>,
+            ) -> Result<(), Error> {
+                layouter.assign_region(
+                    || "bool",
+                    |mut region| {
+                        config.s_bool.enable(&mut region, self.row)?;
+                        region.assign_advice(|| "a", config.a, self.row, || Ok(Fp::one()))?;
+                        Ok(())
+                    },
+                )
+            }
+        }
+
+        let mut cs = ConstraintSystem::default();
+        BoolCircuit::configure(&mut cs);
+        let usable_rows = (1 << K) - (cs.blinding_factors() + 1);
+
+        let prover = MockProver::run(
+            K,
+            &BoolCircuit {
+                row: usable_rows - 1,
+            },
+            vec![],
+        )
+        .unwrap();
+        assert_eq!(prover.verify(), Ok(()));
+
+        // The blinding rows, and the row after them, hold random values in the real
+        // prover, so no gate may be enabled there.
+        for row in usable_rows..(1 << K) {
+            assert!(matches!(
+                MockProver::run(K, &BoolCircuit { row }, vec![]),
+                Err(Error::NotEnoughRowsAvailable { current_k: K })
+            ));
+        }
+    }
+
+    #[test]
+    fn verify_range() {
+        const K: u32 = 4;
+
+        #[derive(Clone)]
+        struct RangeConfig {
+            mul: MulConfig,
+            s_bool: Selector,
+        }
+
+        struct RangeCircuit {
+            bit: u64,
+            product: u64,
+        }
+
+        impl Circuit<Fp> for RangeCircuit {
+            type Config = RangeConfig;
+            type FloorPlanner = SimpleFloorPlanner;
+
+            fn configure(meta: &mut ConstraintSystem<Fp>) -> Self::Config {
+                let mul = MulConfig::configure(meta);
+                let s_bool = meta.selector();
+
+                let a = mul.advice[0];
+                meta.create_gate("bool", |meta| {
+                    let a = meta.query_advice(a, Rotation::cur());
+                    let s_bool = meta.query_selector(s_bool);
+                    vec![s_bool * a.clone() * (Expression::Constant(Fp::one()) - a)]
+                });
+
+                RangeConfig { mul, s_bool }
+            }
+
+            fn without_witnesses(&self) -> Self {
+                Self { bit: 0, product: 0 }
+            }
+
+            fn synthesize(
+                &self,
+                config: Self::Config,
+                mut layouter: impl Layouter<Fp>,
+            ) -> Result<(), Error> {
+                layouter.assign_region(
+                    || "bool",
+                    |mut region| {
+                        config.s_bool.enable(&mut region, 0)?;
+                        region.assign_advice(
+                            || "bit",
+                            config.mul.advice[0],
+                            0,
+                            || Ok(Fp::from(self.bit)),
+                        )?;
+                        Ok(())
+                    },
+                )?;
+                layouter.assign_region(
+                    || "mul",
+                    |mut region| {
+                        config.mul.mul(
+                            &mut region,
+                            Fp::from(2),
+                            Fp::from(3),
+                            Fp::from(self.product),
+                        )?;
+                        Ok(())
+                    },
+                )
+            }
+        }
+
+        // The `bool` region is not satisfied, but only the `mul` region is verified.
+        let prover = MockProver::run(K, &RangeCircuit { bit: 2, product: 6 }, vec![]).unwrap();
+        let (start, end) = prover.regions[1].rows.unwrap();
+        let mul_rows = start..(end + 1);
+        assert!(prover.verify().is_err());
+        assert_eq!(prover.verify_range(mul_rows.clone()), Ok(()));
+
+        let prover = MockProver::run(K, &RangeCircuit { bit: 2, product: 7 }, vec![]).unwrap();
+        assert_eq!(
+            prover.verify_range(mul_rows),
+            Err(vec![VerifyFailure::ConstraintNotSatisfied {
+                constraint: ((0, "mul").into(), 0, "").into(),
+                location: FailureLocation::InRegion {
+                    region: (1, "mul").into(),
+                    offset: 0,
+                },
+                cell_values: vec![
+                    (((Any::Advice, 0).into(), 0).into(), "0x2".to_string()),
+                    (((Any::Advice, 0).into(), 1).into(), "0x7".to_string()),
+                    (((Any::Advice, 1).into(), 0).into(), "0x3".to_string()),
+                ],
+            }])
+        );
+    }
+
+    #[test]
+    fn fixed_column_with_default() {
+        const K: u32 = 4;
+
+        /// Looks up 7 in a fixed column that only has 0, 1 and 2 assigned, and so
+        /// only contains 7 if its unassigned cells default to 7.
+        struct PaddedCircuit<const DEFAULT: bool> {}
+
+        impl<const DEFAULT: bool> Circuit<Fp> for PaddedCircuit<DEFAULT> {
+            type Config = (Column<Advice>, Column<Fixed>, Selector);
+            type FloorPlanner = SimpleFloorPlanner;
+
+            fn without_witnesses(&self) -> Self {
+                Self {}
+            }
+
+            fn configure(meta: &mut ConstraintSystem<Fp>) -> Self::Config {
+                let a = meta.advice_column();
+                let table = if DEFAULT {
+                    meta.fixed_column_with_default(Fp::from(7))
+                } else {
+                    meta.fixed_column()
+                };
+                let q = meta.complex_selector();
+
+                meta.lookup_any("padded table", |meta| {
+                    let q = meta.query_selector(q);
+                    let a = meta.query_advice(a, Rotation::cur());
+                    let table = meta.query_fixed(table, Rotation::cur());
+                    vec![(q * a, table)]
+                });
+
+                (a, table, q)
+            }
+
+            fn synthesize(
+                &self,
+                (a, table, q): Self::Config,
+                mut layouter: impl Layouter<Fp>,
+            ) -> Result<(), Error> {
+                layouter.assign_region(
+                    || "table",
+                    |mut region| {
+                        for value in 0..3u64 {
+                            region.assign_fixed(
+                                || "table",
+                                table,
+                                value as usize,
+                                || Ok(Fp::from(value)),
+                            )?;
+                        }
+                        Ok(())
+                    },
+                )?;
+                layouter.assign_region(
+                    || "lookup",
+                    |mut region| {
+                        q.enable(&mut region, 0)?;
+                        region.assign_advice(|| "a", a, 0, || Ok(Fp::from(7)))?;
+                        Ok(())
+                    },
+                )
+            }
+        }
+
+        let prover = MockProver::run(K, &PaddedCircuit::<true> {}, vec![]).unwrap();
+        assert_eq!(prover.verify(), Ok(()));
+        assert!(super::quick_prove_verify::<Bn256, _, _>(
+            K,
+            &PaddedCircuit::<true> {},
+            vec![],
+            test_rng()
+        )
+        .is_ok());
+
+        let prover = MockProver::run(K, &PaddedCircuit::<false> {}, vec![]).unwrap();
+        assert!(matches!(
+            &prover.verify().unwrap_err()[..],
+            [VerifyFailure::Lookup { .. }]
+        ));
+    }
+
+    #[test]
+    fn region_profile() {
+        const K: u32 = 4;
+
+        struct ThreeRegionCircuit {}
+
+        impl Circuit<Fp> for ThreeRegionCircuit {
+            type Config = Column<Advice>;
+            type FloorPlanner = SimpleFloorPlanner;
+
+            fn configure(meta: &mut ConstraintSystem<Fp>) -> Self::Config {
+                meta.advice_column()
+            }
+
+            fn without_witnesses(&self) -> Self {
+                Self {}
+            }
+
+            fn synthesize(
+                &self,
+                advice: Self::Config,
+                mut layouter: impl Layouter<Fp>,
+            ) -> Result<(), Error> {
+                for &(name, rows) in [("load", 1), ("compute", 3), ("expose", 2)].iter() {
+                    layouter.assign_region(
+                        || name,
+                        |mut region| {
+                            for offset in 0..rows {
+                                region.assign_advice(
+                                    || "value",
+                                    advice,
+                                    offset,
+                                    || Ok(Fp::from(offset as u64)),
+                                )?;
+                            }
+                            Ok(())
+                        },
+                    )?;
+                }
+                Ok(())
+            }
+        }
+
+        let prover = MockProver::run(K, &ThreeRegionCircuit {}, vec![]).unwrap();
+        let profile = prover.region_profile();
+        assert_eq!(
+            profile
+                .iter()
+                .map(|(name, rows, _)| (name.as_str(), *rows))
+                .collect::<Vec<_>>(),
+            vec![("load", 1), ("compute", 3), ("expose", 2)]
+        );
+    }
+
+    #[test]
+    fn failure_order() {
+        const K: u32 = 4;
+
+        #[derive(Clone)]
+        struct FaultyCircuitConfig {
+            a: Column<Advice>,
+            q: [Selector; 4],
+        }
+
+        struct FaultyCircuit {}
+
+        impl Circuit<Fp> for FaultyCircuit {
+            type Config = FaultyCircuitConfig;
+            type FloorPlanner = SimpleFloorPlanner;
+
+            fn configure(meta: &mut ConstraintSystem<Fp>) -> Self::Config {
+                let a = meta.advice_column();
+                let q = [
+                    meta.selector(),
+                    meta.selector(),
+                    meta.selector(),
+                    meta.selector(),
+                ];
+
+                meta.create_gate("zero", |meta| {
+                    let a = meta.query_advice(a, Rotation::cur());
+                    let q = meta.query_selector(q[0]);
+                    vec![q * a]
+                });
+                meta.create_gate("one", |meta| {
+                    let a = meta.query_advice(a, Rotation::cur());
+                    let q = meta.query_selector(q[1]);
+                    vec![q * (a - Expression::Constant(Fp::one()))]
+                });
+                // These gates query cells below the region, which are never assigned
+                // (and so evaluate to zero).
+                for &(name, q, rotation) in [("next", q[2], 1), ("skip", q[3], 2)].iter() {
+                    meta.create_gate(name, |meta| {
+                        let a = meta.query_advice(a, Rotation(rotation));
+                        let q = meta.query_selector(q);
+                        vec![q * a]
+                    });
+                }
+
+                FaultyCircuitConfig { a, q }
+            }
+
+            fn without_witnesses(&self) -> Self {
+                Self {}
+            }
+
+            fn synthesize(
+                &self,
+                config: Self::Config,
+                mut layouter: impl Layouter<Fp>,
+            ) -> Result<(), Error> {
+                layouter.assign_region(
+                    || "Faulty synthesis",
+                    |mut region| {
+                        for (offset, value) in [1u64, 5, 1, 1].iter().enumerate() {
+                            region.assign_advice(
+                                || "a",
+                                config.a,
+                                offset,
+                                || Ok(Fp::from(*value)),
+                            )?;
+                        }
+                        // "zero" is broken on rows 1 and 3, and "one" on row 1.
+                        config.q[0].enable(&mut region, 3)?;
+                        config.q[0].enable(&mut region, 1)?;
+                        config.q[1].enable(&mut region, 1)?;
+                        config.q[1].enable(&mut region, 0)?;
+                        // Several selectors in the region report unassigned cells.
+                        config.q[3].enable(&mut region, 2)?;
+                        config.q[2].enable(&mut region, 3)?;
+                        Ok(())
+                    },
+                )
+            }
+        }
+
+        let run = || {
+            MockProver::run(K, &FaultyCircuit {}, vec![])
+                .unwrap()
+                .verify()
+                .unwrap_err()
+        };
+        let errors = run();
+
+        let summary = errors
+            .iter()
+            .map(|error| match error {
+                VerifyFailure::CellNotAssigned { gate, offset, .. } => {
+                    format!("unassigned {} {}", gate, offset)
+                }
+                VerifyFailure::ConstraintNotSatisfied {
+                    constraint,
+                    location,
+                    ..
+                } => format!("unsatisfied {} {}", constraint, location),
+                _ => unreachable!(),
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            summary,
+            vec![
+                "unassigned Gate 2 ('next') 4",
+                "unassigned Gate 3 ('skip') 4",
+                "unsatisfied Constraint 0 in gate 0 ('zero') in Region 0 ('Faulty synthesis') at offset 1",
+                "unsatisfied Constraint 0 in gate 0 ('zero') in Region 0 ('Faulty synthesis') at offset 3",
+                "unsatisfied Constraint 0 in gate 1 ('one') in Region 0 ('Faulty synthesis') at offset 1",
+            ]
+        );
+
+        // Repeated runs produce identical failure lists.
+        for _ in 0..8 {
+            assert_eq!(run(), errors);
+        }
+    }
+
+    #[test]
+    fn checking_assignment() {
+        use std::cell::Cell;
+
+        const K: u32 = 4;
+
+        /// A `mul` region followed by a region recording that it was assigned.
+        struct CheckedCircuit {
+            out: u64,
+            later_region_assigned: Cell<bool>,
+        }
+
+        impl Circuit<Fp> for CheckedCircuit {
+            type Config = MulConfig;
+            type FloorPlanner = SimpleFloorPlanner;
+
+            fn configure(meta: &mut ConstraintSystem<Fp>) -> Self::Config {
+                MulConfig::configure(meta)
+            }
+
+            fn without_witnesses(&self) -> Self {
+                Self {
+                    out: 0,
+                    later_region_assigned: Cell::new(false),
+                }
+            }
+
+            fn synthesize(
+                &self,
+                config: Self::Config,
+                mut layouter: impl Layouter<Fp>,
+            ) -> Result<(), Error> {
+                layouter.assign_region(
+                    || "mul",
+                    |mut region| {
+                        config.mul(&mut region, Fp::from(2), Fp::from(3), Fp::from(self.out))?;
+                        Ok(())
+                    },
+                )?;
+
+                layouter.assign_region(
+                    || "later",
+                    |mut region| {
+                        region.assign_advice(|| "a", config.advice[1], 0, || Ok(Fp::zero()))?;
+                        self.later_region_assigned.set(true);
+                        Ok(())
+                    },
+                )
+            }
+        }
+
+        let circuit = CheckedCircuit {
+            out: 6,
+            later_region_assigned: Cell::new(false),
+        };
+        assert_eq!(CheckingAssignment::run(K, &circuit, vec![]).unwrap(), None);
+        assert!(circuit.later_region_assigned.get());
+
+        // The broken gate is reported at the `mul` region, and synthesis stops there.
+        let circuit = CheckedCircuit {
+            out: 7,
+            later_region_assigned: Cell::new(false),
+        };
+        assert_eq!(
+            CheckingAssignment::run(K, &circuit, vec![]).unwrap(),
+            Some(VerifyFailure::ConstraintNotSatisfied {
+                constraint: ((0, "mul").into(), 0, "").into(),
+                location: FailureLocation::InRegion {
+                    region: (0, "mul").into(),
+                    offset: 0,
+                },
+                cell_values: vec![
+                    (((Any::Advice, 0).into(), 0).into(), "0x2".to_string()),
+                    (((Any::Advice, 0).into(), 1).into(), "0x7".to_string()),
+                    (((Any::Advice, 1).into(), 0).into(), "0x3".to_string()),
+                ],
+            })
+        );
+        assert!(!circuit.later_region_assigned.get());
+    }
+
+    #[test]
+    fn bad_lookup() {
+        const K: u32 = 4;
+
+        #[derive(Clone)]
+        struct FaultyCircuitConfig {
+            a: Column<Advice>,
+            q: Selector,
+            table: TableColumn,
+        }
+
+        struct FaultyCircuit {}
+
+        impl Circuit<Fp> for FaultyCircuit {
+            type Config = FaultyCircuitConfig;
+            type FloorPlanner = SimpleFloorPlanner;
+
+            fn configure(meta: &mut ConstraintSystem<Fp>) -> Self::Config {
+                let a = meta.advice_column();
+                let q = meta.complex_selector();
+                let table = meta.lookup_table_column();
+
+                meta.lookup("lookup", |cells| {
+                    let a = cells.query_advice(a, Rotation::cur());
+                    let q = cells.query_selector(q);
+
+                    // If q is enabled, a must be in the table.
+                    // When q is not enabled, lookup the default value instead.
+                    let not_q = Expression::Constant(Fp::one()) - q.clone();
+                    let default = Expression::Constant(Fp::from(2));
+                    vec![(q * a + not_q * default, table)]
+                });
+
+                FaultyCircuitConfig { a, q, table }
+            }
+
+            fn without_witnesses(&self) -> Self {
+                Self {}
+            }
+
+            fn synthesize(
+                &self,
+                config: Self::Config,
+                mut layouter: impl Layouter<Fp>,
+            ) -> Result<(), Error> {
+                layouter.assign_table(
+                    || "Doubling table",
+                    |mut table| {
+                        (1..(1 << (K - 1)))
+                            .map(|i| {
+                                table.assign_cell(
+                                    || format!("table[{}] = {}", i, 2 * i),
+                                    config.table,
+                                    i - 1,
+                                    || Ok(Fp::from(2 * i as u64)),
+                                )
+                            })
+                            .fold(Ok(()), |acc, res| acc.and(res))
+                    },
+                )?;
+
+                layouter.assign_region(
+                    || "Good synthesis",
+                    |mut region| {
+                        // Enable the lookup on rows 0 and 1.
+                        config.q.enable(&mut region, 0)?;
+                        config.q.enable(&mut region, 1)?;
+
+                        // Assign a = 2 and a = 6.
+                        region.assign_advice(|| "a = 2", config.a, 0, || Ok(Fp::from(2)))?;
+                        region.assign_advice(|| "a = 6", config.a, 1, || Ok(Fp::from(6)))?;
+
+                        Ok(())
+                    },
+                )?;
+
+                layouter.assign_region(
+                    || "Faulty synthesis",
+                    |mut region| {
+                        // Enable the lookup on rows 0 and 1.
+                        config.q.enable(&mut region, 0)?;
+                        config.q.enable(&mut region, 1)?;
+
+                        // Assign a = 4.
+                        region.assign_advice(|| "a = 4", config.a, 0, || Ok(Fp::from(4)))?;
+
+                        // BUG: Assign a = 5, which doesn't exist in the table!
+                        region.assign_advice(|| "a = 5", config.a, 1, || Ok(Fp::from(5)))?;
+
+                        Ok(())
+                    },
+                )
+            }
+        }
+
+        let prover = MockProver::run(K, &FaultyCircuit {}, vec![]).unwrap();
+        assert_eq!(
+            prover.verify(),
+            Err(vec![VerifyFailure::Lookup {
+                name: "lookup",
+                lookup_index: 0,
+                location: FailureLocation::InRegion {
+                    region: (2, "Faulty synthesis").into(),
+                    offset: 1,
+                },
+                input_values: vec!["0x5".to_owned()],
+            }])
+        );
+    }
+
+    #[test]
+    fn constrain_instance() {
+        const K: u32 = 4;
+
+        #[derive(Clone)]
+        struct PublicOutputConfig {
+            a: Column<Advice>,
+            instance: Column<Instance>,
+        }
+
+        struct PublicOutputCircuit {}
+
+        impl Circuit<Fp> for PublicOutputCircuit {
+            type Config = PublicOutputConfig;
+            type FloorPlanner = SimpleFloorPlanner;
+
+            fn configure(meta: &mut ConstraintSystem<Fp>) -> Self::Config {
+                let a = meta.advice_column();
+                let instance = meta.instance_column();
+
+                meta.enable_equality(a);
+                meta.enable_equality(instance);
+
+                PublicOutputConfig { a, instance }
+            }
+
+            fn without_witnesses(&self) -> Self {
+                Self {}
+            }
+
+            fn synthesize(
+                &self,
+                config: Self::Config,
+                mut layouter: impl Layouter<Fp>,
+            ) -> Result<(), Error> {
                 layouter.assign_region(
-                    || "Faulty synthesis",
+                    || "expose output",
+                    |mut region| {
+                        let out =
+                            region.assign_advice(|| "out", config.a, 0, || Ok(Fp::from(3)))?;
+                        region.constrain_instance(out.cell(), config.instance, 0)
+                    },
+                )
+            }
+        }
+
+        let prover = MockProver::run(K, &PublicOutputCircuit {}, vec![vec![Fp::from(3)]]).unwrap();
+        assert_eq!(prover.verify(), Ok(()));
+
+        let prover = MockProver::run(K, &PublicOutputCircuit {}, vec![vec![Fp::from(4)]]).unwrap();
+        assert_eq!(
+            prover.verify(),
+            Err(vec![
+                VerifyFailure::Permutation {
+                    column: (Any::Advice, 0).into(),
+                    row: 0,
+                },
+                VerifyFailure::Permutation {
+                    column: (Any::Instance, 0).into(),
+                    row: 0,
+                },
+            ])
+        );
+    }
+
+    #[test]
+    fn queried_instance_cells() {
+        const K: u32 = 4;
+
+        #[derive(Clone)]
+        struct PublicConfig {
+            a: Column<Advice>,
+            instance: [Column<Instance>; 2],
+            q: Selector,
+        }
+
+        struct PublicCircuit {}
+
+        impl Circuit<Fp> for PublicCircuit {
+            type Config = PublicConfig;
+            type FloorPlanner = SimpleFloorPlanner;
+
+            fn configure(meta: &mut ConstraintSystem<Fp>) -> Self::Config {
+                let a = meta.advice_column();
+                let instance = [meta.instance_column(), meta.instance_column()];
+                let q = meta.selector();
+
+                meta.enable_equality(a);
+                meta.enable_equality(instance[0]);
+
+                // The gate reads the second instance column on the next row.
+                meta.create_gate("public", |meta| {
+                    let a = meta.query_advice(a, Rotation::cur());
+                    let i = meta.query_instance(instance[1], Rotation::next());
+                    let q = meta.query_selector(q);
+                    vec![q * (a - i)]
+                });
+
+                PublicConfig { a, instance, q }
+            }
+
+            fn without_witnesses(&self) -> Self {
+                Self {}
+            }
+
+            fn synthesize(
+                &self,
+                config: Self::Config,
+                mut layouter: impl Layouter<Fp>,
+            ) -> Result<(), Error> {
+                layouter.assign_region(
+                    || "public",
+                    |mut region| {
+                        let out =
+                            region.assign_advice(|| "out", config.a, 0, || Ok(Fp::from(3)))?;
+                        region.constrain_instance(out.cell(), config.instance[0], 6)?;
+
+                        region.assign_advice(|| "a", config.a, 1, || Ok(Fp::from(5)))?;
+                        config.q.enable(&mut region, 1)
+                    },
+                )
+            }
+        }
+
+        let mut instance = vec![vec![Fp::zero(); 7], vec![Fp::zero(); 3]];
+        instance[0][6] = Fp::from(3);
+        instance[1][2] = Fp::from(5);
+
+        let prover = MockProver::run(K, &PublicCircuit {}, instance).unwrap();
+        assert_eq!(prover.verify(), Ok(()));
+        assert_eq!(prover.queried_instance_cells(), vec![(0, 6), (1, 2)]);
+    }
+
+    #[test]
+    fn quick_prove_verify() {
+        const K: u32 = 4;
+
+        let circuit = PublicMulCircuit::new(2, 3);
+        assert!(super::quick_prove_verify::<Bn256, _, _>(
+            K,
+            &circuit,
+            vec![vec![Fp::from(6)]],
+            test_rng()
+        )
+        .is_ok());
+        assert!(matches!(
+            super::quick_prove_verify::<Bn256, _, _>(
+                K,
+                &circuit,
+                vec![vec![Fp::from(7)]],
+                test_rng()
+            ),
+            Err(Error::ConstraintSystemFailure)
+        ));
+    }
+
+    #[test]
+    fn cross_check() {
+        const K: u32 = 4;
+
+        assert!(super::cross_check::<Bn256, _, _>(
+            K,
+            &MulCircuit::new(2, 3, 6),
+            vec![],
+            test_rng()
+        ));
+        assert!(!super::cross_check::<Bn256, _, _>(
+            K,
+            &MulCircuit::new(2, 3, 7),
+            vec![],
+            test_rng()
+        ));
+    }
+
+    #[test]
+    #[should_panic(
+        expected = "the mock prover accepts the circuit, but the real prover rejects it: \
+                    Degree 33554432 is too high for a circuit with k = 4"
+    )]
+    fn cross_check_degree_overflow() {
+        const K: u32 = 4;
+
+        /// The `mul` circuit, with its constraint system raised to a degree whose
+        /// quotient polynomial does not fit in the field's power-of-two subgroups.
+        struct DegreeOverflowCircuit(MulCircuit);
+
+        impl Circuit<Fp> for DegreeOverflowCircuit {
+            type Config = MulConfig;
+            type FloorPlanner = SimpleFloorPlanner;
+
+            fn without_witnesses(&self) -> Self {
+                DegreeOverflowCircuit(self.0.without_witnesses())
+            }
+
+            fn configure(meta: &mut ConstraintSystem<Fp>) -> Self::Config {
+                let config = MulCircuit::configure(meta);
+                meta.set_minimum_degree(1 << 25);
+                config
+            }
+
+            fn synthesize(
+                &self,
+                config: Self::Config,
+                layouter: impl Layouter<Fp>,
+            ) -> Result<(), Error> {
+                self.0.synthesize(config, layouter)
+            }
+        }
+
+        // The mock prover never builds the extended domain, so it accepts the
+        // circuit; the real prover cannot.
+        super::cross_check::<Bn256, _, _>(
+            K,
+            &DegreeOverflowCircuit(MulCircuit::new(2, 3, 6)),
+            vec![],
+            test_rng(),
+        );
+    }
+
+    #[test]
+    fn constrain_constant() {
+        const K: u32 = 4;
+
+        /// Constrains the product of `a` and `b` to be 6.
+        struct ConstantCircuit {
+            a: u64,
+            b: u64,
+        }
+
+        impl Circuit<Fp> for ConstantCircuit {
+            type Config = MulConfig;
+            type FloorPlanner = SimpleFloorPlanner;
+
+            fn configure(meta: &mut ConstraintSystem<Fp>) -> Self::Config {
+                let config = MulConfig::configure(meta);
+                let constant = meta.fixed_column();
+                meta.enable_constant(constant);
+                config
+            }
+
+            fn without_witnesses(&self) -> Self {
+                Self { a: 0, b: 0 }
+            }
+
+            fn synthesize(
+                &self,
+                config: Self::Config,
+                mut layouter: impl Layouter<Fp>,
+            ) -> Result<(), Error> {
+                let (a, b) = (Fp::from(self.a), Fp::from(self.b));
+                let out = layouter
+                    .assign_region(|| "mul", |mut region| config.mul(&mut region, a, b, a * b))?;
+                layouter.constrain_constant(out.cell(), Fp::from(6))
+            }
+        }
+
+        let prover = MockProver::run(K, &ConstantCircuit { a: 2, b: 3 }, vec![]).unwrap();
+        assert_eq!(prover.verify(), Ok(()));
+
+        let prover = MockProver::run(K, &ConstantCircuit { a: 2, b: 4 }, vec![]).unwrap();
+        let errors = prover.verify().unwrap_err();
+        assert!(errors.contains(&VerifyFailure::Permutation {
+            column: (Any::Advice, 0).into(),
+            row: 1,
+        }));
+    }
+
+    #[test]
+    fn describe_constraint() {
+        const K: u32 = 4;
+
+        // BUG: 2 * 3 != 7
+        let prover = MockProver::run(K, &MulCircuit::new(2, 3, 7), vec![]).unwrap();
+        let failures = prover.verify().unwrap_err();
+        assert_eq!(failures.len(), 1);
+
+        let description = failures[0].describe(&prover);
+        assert!(description.contains("A0@0 * A1@0 - A0@1"));
+    }
+
+    #[test]
+    fn assert_satisfied_report() {
+        const K: u32 = 4;
+
+        // BUG: 2 * 3 != 7
+        let prover = MockProver::run(K, &MulCircuit::new(2, 3, 7), vec![]).unwrap();
+        let panic =
+            std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| prover.assert_satisfied()))
+                .unwrap_err();
+        let message = panic.downcast_ref::<String>().unwrap();
+        assert!(message.starts_with("circuit was not satisfied:\n\n"));
+        assert!(message.contains("is not satisfied in Region 0 ('mul') at offset 0"));
+        assert!(message.contains("  F0@0 * (A0@0 * A1@0 - A0@1)\n"));
+
+        MockProver::run(K, &MulCircuit::new(2, 3, 6), vec![])
+            .unwrap()
+            .assert_satisfied();
+    }
+
+    #[test]
+    fn no_instance_columns() {
+        const K: u32 = 4;
+
+        #[derive(Clone)]
+        struct BooleanConfig {
+            a: Column<Advice>,
+            q: Selector,
+        }
+
+        struct BooleanCircuit {
+            a: Option<Fp>,
+        }
+
+        impl Circuit<Fp> for BooleanCircuit {
+            type Config = BooleanConfig;
+            type FloorPlanner = SimpleFloorPlanner;
+
+            fn configure(meta: &mut ConstraintSystem<Fp>) -> Self::Config {
+                let a = meta.advice_column();
+                let q = meta.selector();
+
+                meta.create_gate("a * a = a", |meta| {
+                    let q = meta.query_selector(q);
+                    let a = meta.query_advice(a, Rotation::cur());
+                    vec![q * (a.clone() * a.clone() - a)]
+                });
+
+                BooleanConfig { a, q }
+            }
+
+            fn without_witnesses(&self) -> Self {
+                Self { a: None }
+            }
+
+            fn synthesize(
+                &self,
+                config: Self::Config,
+                mut layouter: impl Layouter<Fp>,
+            ) -> Result<(), Error> {
+                layouter.assign_region(
+                    || "witness a",
                     |mut region| {
-                        // Enable the lookup on rows 0 and 1.
                         config.q.enable(&mut region, 0)?;
-                        config.q.enable(&mut region, 1)?;
+                        region.assign_advice(
+                            || "a",
+                            config.a,
+                            0,
+                            || self.a.ok_or(Error::Synthesis),
+                        )?;
+                        Ok(())
+                    },
+                )
+            }
+        }
+
+        let circuit = BooleanCircuit {
+            a: Some(Fp::from(1)),
+        };
+        let prover = MockProver::run(K, &circuit, vec![]).unwrap();
+        assert_eq!(prover.verify(), Ok(()));
+
+        // Supplying instance values for a circuit without instance columns is an error.
+        assert!(matches!(
+            MockProver::run(K, &circuit, vec![vec![]]).unwrap_err(),
+            Error::InvalidInstances
+        ));
+
+        let circuit = BooleanCircuit {
+            a: Some(Fp::from(2)),
+        };
+        let prover = MockProver::run(K, &circuit, vec![]).unwrap();
+        assert_eq!(
+            prover.verify(),
+            Err(vec![VerifyFailure::ConstraintNotSatisfied {
+                constraint: ((0, "a * a = a").into(), 0, "").into(),
+                location: FailureLocation::InRegion {
+                    region: (0, "witness a").into(),
+                    offset: 0,
+                },
+                cell_values: vec![(((Any::Advice, 0).into(), 0).into(), "0x2".to_string())],
+            }])
+        );
+    }
+
+    #[test]
+    fn witness_matrix() {
+        const K: u32 = 4;
+
+        let (advice, fixed, instance) =
+            super::witness_matrix(K, &MulCircuit::new(2, 3, 6), vec![]).unwrap();
+
+        assert_eq!(advice.len(), 2);
+        assert!(advice.iter().all(|column| column.len() == 1 << K));
+        assert_eq!(advice[0][0], Some(Fp::from(2)));
+        assert_eq!(advice[1][0], Some(Fp::from(3)));
+        assert_eq!(advice[0][1], Some(Fp::from(6)));
+        assert_eq!(advice[1][1], None);
+
+        // The selector is compressed into a single fixed column.
+        assert_eq!(fixed.len(), 1);
+        assert!(instance.is_empty());
+    }
+
+    #[test]
+    fn lookup_input_values() {
+        const K: u32 = 6;
+
+        #[derive(Clone)]
+        struct RangeConfig {
+            q: Selector,
+            a: Column<Advice>,
+            table: TableColumn,
+        }
+
+        struct RangeCircuit {
+            value: u64,
+        }
+
+        impl Circuit<Fp> for RangeCircuit {
+            type Config = RangeConfig;
+            type FloorPlanner = SimpleFloorPlanner;
+
+            fn configure(meta: &mut ConstraintSystem<Fp>) -> Self::Config {
+                let q = meta.complex_selector();
+                let a = meta.advice_column();
+                let table = meta.lookup_table_column();
+
+                meta.lookup("range", |cells| {
+                    let q = cells.query_selector(q);
+                    let a = cells.query_advice(a, Rotation::cur());
+                    vec![(q * a, table)]
+                });
+
+                RangeConfig { q, a, table }
+            }
+
+            fn without_witnesses(&self) -> Self {
+                Self { value: 0 }
+            }
+
+            fn synthesize(
+                &self,
+                config: Self::Config,
+                mut layouter: impl Layouter<Fp>,
+            ) -> Result<(), Error> {
+                layouter.assign_table(
+                    || "0..16",
+                    |mut table| {
+                        for i in 0..16u64 {
+                            table.assign_cell(
+                                || format!("{}", i),
+                                config.table,
+                                i as usize,
+                                || Ok(Fp::from(i)),
+                            )?;
+                        }
+                        Ok(())
+                    },
+                )?;
+
+                layouter.assign_region(
+                    || "range check",
+                    |mut region| {
+                        config.q.enable(&mut region, 0)?;
+                        region.assign_advice(|| "a", config.a, 0, || Ok(Fp::from(self.value)))?;
+                        Ok(())
+                    },
+                )
+            }
+        }
+
+        let prover = MockProver::run(K, &RangeCircuit { value: 15 }, vec![]).unwrap();
+        assert_eq!(prover.verify(), Ok(()));
+
+        let prover = MockProver::run(K, &RangeCircuit { value: 100 }, vec![]).unwrap();
+        let errors = prover.verify().unwrap_err();
+        assert_eq!(
+            errors,
+            vec![VerifyFailure::Lookup {
+                name: "range",
+                lookup_index: 0,
+                location: FailureLocation::InRegion {
+                    region: (1, "range check").into(),
+                    offset: 0,
+                },
+                input_values: vec!["0x64".to_owned()],
+            }]
+        );
+        assert_eq!(
+            errors[0].to_string(),
+            "Lookup range(index: 0) is not satisfied in Region 1 ('range check') at offset 0 \
+             with inputs (0x64)"
+        );
+    }
+
+    #[test]
+    fn tuple_lookup() {
+        const K: u32 = 4;
+
+        #[derive(Clone)]
+        struct SquareConfig {
+            q: Selector,
+            x: Column<Advice>,
+            y: Column<Advice>,
+            table_x: TableColumn,
+            table_y: TableColumn,
+        }
+
+        struct SquareCircuit {
+            x: u64,
+            y: u64,
+        }
+
+        impl Circuit<Fp> for SquareCircuit {
+            type Config = SquareConfig;
+            type FloorPlanner = SimpleFloorPlanner;
+
+            fn configure(meta: &mut ConstraintSystem<Fp>) -> Self::Config {
+                let q = meta.complex_selector();
+                let x = meta.advice_column();
+                let y = meta.advice_column();
+                let table_x = meta.lookup_table_column();
+                let table_y = meta.lookup_table_column();
+
+                // (x, y) must be a row of the table (i, i^2).
+                meta.lookup("square", |cells| {
+                    let q = cells.query_selector(q);
+                    let x = cells.query_advice(x, Rotation::cur());
+                    let y = cells.query_advice(y, Rotation::cur());
+                    vec![(q.clone() * x, table_x), (q * y, table_y)]
+                });
 
-                        // Assign a = 4.
-                        region.assign_advice(|| "a = 4", config.a, 0, || Ok(Fp::from(4)))?;
+                SquareConfig {
+                    q,
+                    x,
+                    y,
+                    table_x,
+                    table_y,
+                }
+            }
 
-                        // BUG: Assign a = 5, which doesn't exist in the table!
-                        region.assign_advice(|| "a = 5", config.a, 1, || Ok(Fp::from(5)))?;
+            fn without_witnesses(&self) -> Self {
+                Self { x: 0, y: 0 }
+            }
+
+            fn synthesize(
+                &self,
+                config: Self::Config,
+                mut layouter: impl Layouter<Fp>,
+            ) -> Result<(), Error> {
+                layouter.assign_table(
+                    || "square table",
+                    |mut table| {
+                        for i in 0..4u64 {
+                            table.assign_cell(
+                                || format!("x = {}", i),
+                                config.table_x,
+                                i as usize,
+                                || Ok(Fp::from(i)),
+                            )?;
+                            table.assign_cell(
+                                || format!("y = {}", i * i),
+                                config.table_y,
+                                i as usize,
+                                || Ok(Fp::from(i * i)),
+                            )?;
+                        }
+                        Ok(())
+                    },
+                )?;
 
+                layouter.assign_region(
+                    || "square",
+                    |mut region| {
+                        config.q.enable(&mut region, 0)?;
+                        region.assign_advice(|| "x", config.x, 0, || Ok(Fp::from(self.x)))?;
+                        region.assign_advice(|| "y", config.y, 0, || Ok(Fp::from(self.y)))?;
                         Ok(())
                     },
                 )
             }
         }
 
-        let prover = MockProver::run(K, &FaultyCircuit {}, vec![]).unwrap();
+        let prover = MockProver::run(K, &SquareCircuit { x: 3, y: 9 }, vec![]).unwrap();
+        assert_eq!(prover.verify(), Ok(()));
+
+        // Both 3 and 4 appear in the table's columns, but (3, 4) is not a row of it.
+        let prover = MockProver::run(K, &SquareCircuit { x: 3, y: 4 }, vec![]).unwrap();
         assert_eq!(
             prover.verify(),
             Err(vec![VerifyFailure::Lookup {
-                name: "lookup",
+                name: "square",
                 lookup_index: 0,
                 location: FailureLocation::InRegion {
-                    region: (2, "Faulty synthesis").into(),
-                    offset: 1,
+                    region: (1, "square").into(),
+                    offset: 0,
+                },
+                input_values: vec!["0x3".to_owned(), "0x4".to_owned()],
+            }])
+        );
+    }
+
+    #[test]
+    fn copy_advice() {
+        const K: u32 = 4;
+
+        /// Loads `a` and `b`, and copies them into a `mul` region, either with
+        /// `copy_advice` or by assigning and constraining the copies by hand.
+        struct CopyCircuit {
+            use_copy_advice: bool,
+            out: u64,
+        }
+
+        impl Circuit<Fp> for CopyCircuit {
+            type Config = MulConfig;
+            type FloorPlanner = SimpleFloorPlanner;
+
+            fn configure(meta: &mut ConstraintSystem<Fp>) -> Self::Config {
+                MulConfig::configure(meta)
+            }
+
+            fn without_witnesses(&self) -> Self {
+                Self {
+                    use_copy_advice: self.use_copy_advice,
+                    out: self.out,
+                }
+            }
+
+            fn synthesize(
+                &self,
+                config: Self::Config,
+                mut layouter: impl Layouter<Fp>,
+            ) -> Result<(), Error> {
+                let (a, b) = layouter.assign_region(
+                    || "load",
+                    |mut region| config.load(&mut region, Fp::from(2), Fp::from(3)),
+                )?;
+
+                layouter.assign_region(
+                    || "mul",
+                    |mut region| {
+                        config.s_mul.enable(&mut region, 0)?;
+                        if self.use_copy_advice {
+                            region.copy_advice(|| "lhs", config.advice[0], 0, &a)?;
+                            region.copy_advice(|| "rhs", config.advice[1], 0, &b)?;
+                        } else {
+                            let lhs = region.assign_advice(
+                                || "lhs",
+                                config.advice[0],
+                                0,
+                                || a.value().cloned().ok_or(Error::Synthesis),
+                            )?;
+                            region.constrain_equal(a.cell(), lhs.cell())?;
+                            let rhs = region.assign_advice(
+                                || "rhs",
+                                config.advice[1],
+                                0,
+                                || b.value().cloned().ok_or(Error::Synthesis),
+                            )?;
+                            region.constrain_equal(b.cell(), rhs.cell())?;
+                        }
+                        region.assign_advice(
+                            || "out",
+                            config.advice[0],
+                            1,
+                            || Ok(Fp::from(self.out)),
+                        )?;
+                        Ok(())
+                    },
+                )
+            }
+        }
+
+        for &out in [6, 7].iter() {
+            let verify = |use_copy_advice| {
+                MockProver::run(
+                    K,
+                    &CopyCircuit {
+                        use_copy_advice,
+                        out,
+                    },
+                    vec![],
+                )
+                .unwrap()
+                .verify()
+            };
+
+            let result = verify(true);
+            assert_eq!(result.is_ok(), out == 6);
+            assert_eq!(result, verify(false));
+        }
+    }
+
+    #[derive(Clone)]
+    struct PublicConfig {
+        a: Column<Advice>,
+        instance: Column<Instance>,
+        s_pub: Selector,
+    }
+
+    /// Witnesses 5 in a region starting on row 1, and exposes it as the public input
+    /// on that row.
+    struct PublicCircuit {}
+
+    impl Circuit<Fp> for PublicCircuit {
+        type Config = PublicConfig;
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn configure(meta: &mut ConstraintSystem<Fp>) -> Self::Config {
+            let a = meta.advice_column();
+            let instance = meta.instance_column();
+            let s_pub = meta.selector();
+
+            meta.create_gate("public input", |meta| {
+                let a = meta.query_advice(a, Rotation::cur());
+                let p = meta.query_instance(instance, Rotation::cur());
+                let s_pub = meta.query_selector(s_pub);
+                vec![s_pub * (a - p)]
+            });
+
+            PublicConfig { a, instance, s_pub }
+        }
+
+        fn without_witnesses(&self) -> Self {
+            Self {}
+        }
+
+        fn synthesize(
+            &self,
+            config: Self::Config,
+            mut layouter: impl Layouter<Fp>,
+        ) -> Result<(), Error> {
+            layouter.assign_region(
+                || "private work",
+                |mut region| {
+                    region.assign_advice(|| "unused", config.a, 0, || Ok(Fp::from(1)))?;
+                    Ok(())
+                },
+            )?;
+            layouter.assign_region(
+                || "expose public",
+                |mut region| {
+                    config.s_pub.enable(&mut region, 0)?;
+                    region.assign_advice(|| "out", config.a, 0, || Ok(Fp::from(5)))?;
+                    Ok(())
+                },
+            )
+        }
+    }
+
+    #[test]
+    fn failure_location_in_region() {
+        const K: u32 = 4;
+
+        // The "expose public" region starts on row 1, but the failure is reported
+        // relative to the region.
+        let prover =
+            MockProver::run(K, &PublicCircuit {}, vec![vec![Fp::zero(), Fp::from(6)]]).unwrap();
+        let failures = prover.verify().unwrap_err();
+        assert_eq!(failures.len(), 1);
+        match &failures[0] {
+            VerifyFailure::ConstraintNotSatisfied { location, .. } => assert_eq!(
+                location,
+                &FailureLocation::InRegion {
+                    region: (1, "expose public").into(),
+                    offset: 0,
                 }
+            ),
+            failure => panic!("unexpected failure: {:?}", failure),
+        }
+    }
+
+    /// A multiplication circuit whose region asserts its expected two-row shape.
+    struct ShapeCircuit {
+        extra_row: bool,
+    }
+
+    impl Circuit<Fp> for ShapeCircuit {
+        type Config = MulConfig;
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn configure(meta: &mut ConstraintSystem<Fp>) -> Self::Config {
+            MulConfig::configure(meta)
+        }
+
+        fn without_witnesses(&self) -> Self {
+            Self {
+                extra_row: self.extra_row,
+            }
+        }
+
+        fn synthesize(
+            &self,
+            config: Self::Config,
+            mut layouter: impl Layouter<Fp>,
+        ) -> Result<(), Error> {
+            layouter.assign_region(
+                || "mul",
+                |mut region| {
+                    config.mul(&mut region, Fp::from(2), Fp::from(3), Fp::from(6))?;
+                    if self.extra_row {
+                        region.assign_advice(
+                            || "extra",
+                            config.advice[1],
+                            2,
+                            || Ok(Fp::from(0)),
+                        )?;
+                    }
+
+                    region.assert_shape(2, &[config.advice[0].into(), config.advice[1].into()]);
+                    Ok(())
+                },
+            )
+        }
+    }
+
+    #[test]
+    fn region_shape() {
+        let prover = MockProver::run(4, &ShapeCircuit { extra_row: false }, vec![]).unwrap();
+        assert_eq!(prover.verify(), Ok(()));
+    }
+
+    #[cfg(debug_assertions)]
+    #[test]
+    #[should_panic(expected = "region spans 3 rows, expected 2")]
+    fn region_shape_drift() {
+        let _ = MockProver::run(4, &ShapeCircuit { extra_row: true }, vec![]);
+    }
+
+    #[test]
+    fn unassigned_cell_in_permutation() {
+        const K: u32 = 4;
+
+        struct CopyCircuit {}
+
+        impl Circuit<Fp> for CopyCircuit {
+            type Config = Column<Advice>;
+            type FloorPlanner = SimpleFloorPlanner;
+
+            fn configure(meta: &mut ConstraintSystem<Fp>) -> Self::Config {
+                let a = meta.advice_column();
+                meta.enable_equality(a);
+                a
+            }
+
+            fn without_witnesses(&self) -> Self {
+                Self {}
+            }
+
+            fn synthesize(
+                &self,
+                config: Self::Config,
+                mut layouter: impl Layouter<Fp>,
+            ) -> Result<(), Error> {
+                layouter.assign_region(
+                    || "cells",
+                    |mut region| {
+                        region.assign_advice(|| "a[0]", config, 0, || Ok(Fp::from(1)))?;
+                        // Row 1 is left unassigned.
+                        region.assign_advice(|| "a[2]", config, 2, || Ok(Fp::from(2)))?;
+                        Ok(())
+                    },
+                )
+            }
+        }
+
+        let a = Column::<Any>::new(0, Any::Advice);
+
+        // A copy constraint to an unassigned cell.
+        let mut prover = MockProver::run(K, &CopyCircuit {}, vec![]).unwrap();
+        prover.copy(a, 0, a, 1).unwrap();
+        assert_eq!(
+            prover.verify(),
+            Err(vec![VerifyFailure::PermutationCellNotAssigned {
+                column: (Any::Advice, 0).into(),
+                row: 1,
             }])
         );
+
+        // A copy constraint between two assigned cells with different values.
+        let mut prover = MockProver::run(K, &CopyCircuit {}, vec![]).unwrap();
+        prover.copy(a, 0, a, 2).unwrap();
+        assert_eq!(
+            prover.verify(),
+            Err(vec![
+                VerifyFailure::Permutation {
+                    column: (Any::Advice, 0).into(),
+                    row: 0,
+                },
+                VerifyFailure::Permutation {
+                    column: (Any::Advice, 0).into(),
+                    row: 2,
+                },
+            ])
+        );
+    }
+
+    #[test]
+    fn diff_assignments() {
+        const K: u32 = 4;
+
+        /// Computes `(a * b)^2`.
+        struct SquareCircuit {
+            a: u64,
+            b: u64,
+        }
+
+        impl Circuit<Fp> for SquareCircuit {
+            type Config = MulConfig;
+            type FloorPlanner = SimpleFloorPlanner;
+
+            fn configure(meta: &mut ConstraintSystem<Fp>) -> Self::Config {
+                MulConfig::configure(meta)
+            }
+
+            fn without_witnesses(&self) -> Self {
+                Self { a: 0, b: 0 }
+            }
+
+            fn synthesize(
+                &self,
+                config: Self::Config,
+                mut layouter: impl Layouter<Fp>,
+            ) -> Result<(), Error> {
+                let (a, b) = (Fp::from(self.a), Fp::from(self.b));
+                layouter.assign_region(
+                    || "mul",
+                    |mut region| {
+                        config.s_mul.enable(&mut region, 0)?;
+                        config.s_mul.enable(&mut region, 1)?;
+                        region.assign_advice(|| "a", config.advice[0], 0, || Ok(a))?;
+                        region.assign_advice(|| "b", config.advice[1], 0, || Ok(b))?;
+                        region.assign_advice(|| "ab", config.advice[0], 1, || Ok(a * b))?;
+                        region.assign_advice(|| "ab", config.advice[1], 1, || Ok(a * b))?;
+                        region.assign_advice(
+                            || "ab^2",
+                            config.advice[0],
+                            2,
+                            || Ok(a * b * a * b),
+                        )?;
+                        Ok(())
+                    },
+                )
+            }
+        }
+
+        let diffs = super::diff_assignments(
+            K,
+            &SquareCircuit { a: 2, b: 3 },
+            &SquareCircuit { a: 2, b: 4 },
+            vec![],
+        )
+        .unwrap();
+
+        // Only `b` and the cells computed from it differ; `a` and the selector
+        // column are unchanged.
+        let diff = |index, row, a: u64, b: u64| CellDiff {
+            column: (Any::Advice, index).into(),
+            row,
+            a: Some(Fp::from(a)),
+            b: Some(Fp::from(b)),
+        };
+        assert_eq!(
+            diffs,
+            vec![
+                diff(0, 1, 6, 8),
+                diff(0, 2, 36, 64),
+                diff(1, 0, 3, 4),
+                diff(1, 1, 6, 8),
+            ]
+        );
+    }
+
+    #[test]
+    fn instance_builder() {
+        const K: u32 = 4;
+
+        let mut meta = ConstraintSystem::<Fp>::default();
+        let instance = [meta.instance_column(), meta.instance_column()];
+        let usable_rows = (1 << K) - (meta.blinding_factors() + 1);
+
+        // Unset cells are zero, and every column is padded to the usable rows.
+        let c = Fp::from(7);
+        let mut builder = InstanceBuilder::new(K, &meta);
+        builder.set(instance[1], 3, c).unwrap();
+        builder.set(instance[1], 1, c).unwrap();
+        let mut expected = vec![vec![Fp::zero(); usable_rows]; 2];
+        expected[1][1] = c;
+        expected[1][3] = c;
+        assert_eq!(builder.build(), expected);
+
+        // Positions outside the usable rows, or outside the circuit, are rejected.
+        let mut builder = InstanceBuilder::new(K, &meta);
+        assert!(builder.set(instance[0], usable_rows - 1, c).is_ok());
+        assert!(matches!(
+            builder.set(instance[0], usable_rows, c),
+            Err(Error::InstanceTooLarge)
+        ));
+        assert!(matches!(
+            builder.set(instance[0], 1 << K, c),
+            Err(Error::InstanceTooLarge)
+        ));
+        let mut other = ConstraintSystem::<Fp>::default();
+        let mut builder = InstanceBuilder::new(K, &other);
+        assert!(matches!(
+            builder.set(other.instance_column(), 0, c),
+            Err(Error::InvalidInstances)
+        ));
+
+        // The padded columns are accepted by the mock prover.
+        let mut cs = ConstraintSystem::<Fp>::default();
+        let (_, instance) = PublicMulCircuit::configure(&mut cs);
+        let mut builder = InstanceBuilder::new(K, &cs);
+        builder.set(instance, 0, Fp::from(6)).unwrap();
+        let prover = MockProver::run(K, &PublicMulCircuit::new(2, 3), builder.build()).unwrap();
+        assert_eq!(prover.verify(), Ok(()));
+    }
+
+    #[test]
+    fn snapshot() {
+        const K: u32 = 4;
+
+        /// Loads `a` and `b`, then copies them into a region computing `a * b`.
+        struct LoadedMulCircuit {
+            a: u64,
+            b: u64,
+        }
+
+        impl Circuit<Fp> for LoadedMulCircuit {
+            type Config = MulConfig;
+            type FloorPlanner = SimpleFloorPlanner;
+
+            fn configure(meta: &mut ConstraintSystem<Fp>) -> Self::Config {
+                MulConfig::configure(meta)
+            }
+
+            fn without_witnesses(&self) -> Self {
+                Self { a: 0, b: 0 }
+            }
+
+            fn synthesize(
+                &self,
+                config: Self::Config,
+                mut layouter: impl Layouter<Fp>,
+            ) -> Result<(), Error> {
+                let (a, b) = layouter.assign_region(
+                    || "load",
+                    |mut region| config.load(&mut region, Fp::from(self.a), Fp::from(self.b)),
+                )?;
+                layouter.assign_region(
+                    || "mul",
+                    |mut region| {
+                        config.s_mul.enable(&mut region, 0)?;
+                        a.copy_advice(|| "a", &mut region, config.advice[0], 0)?;
+                        b.copy_advice(|| "b", &mut region, config.advice[1], 0)?;
+                        region.assign_advice(
+                            || "ab",
+                            config.advice[0],
+                            1,
+                            || Ok(Fp::from(self.a * self.b)),
+                        )?;
+                        Ok(())
+                    },
+                )
+            }
+        }
+
+        let prover = MockProver::run(K, &LoadedMulCircuit { a: 2, b: 3 }, vec![]).unwrap();
+        let snapshot = prover.snapshot();
+        assert_eq!(snapshot.num_advice_columns, 2);
+        assert_eq!(snapshot.selectors, vec![(0, 1)]);
+        assert_eq!(
+            snapshot.cycles,
+            vec![vec![(0, 0), (0, 1)], vec![(1, 0), (1, 1)]]
+        );
+
+        // Synthesizing the same circuit again reproduces the snapshot exactly.
+        MockProver::run(K, &LoadedMulCircuit { a: 2, b: 3 }, vec![])
+            .unwrap()
+            .assert_matches_snapshot(&snapshot);
+
+        // Changing `b` first shows up in the product, which is in the column before it.
+        let modified = MockProver::run(K, &LoadedMulCircuit { a: 2, b: 4 }, vec![]).unwrap();
+        let diff = modified.snapshot().diff(&snapshot);
+        assert_eq!(
+            diff,
+            Some(SnapshotDiff::Cell {
+                column: (Any::Advice, 0).into(),
+                row: 2,
+                expected: Some("0x6".into()),
+                got: Some("0x8".into()),
+            })
+        );
+        assert_eq!(
+            diff.unwrap().to_string(),
+            "Column('Advice', 0), row 2: expected 0x6, got 0x8"
+        );
+    }
+
+    #[test]
+    fn copy_constraints() {
+        const K: u32 = 4;
+
+        /// Mirrors the simple example: loads `a` and `b`, multiplies them, and exposes
+        /// the product as a public input.
+        struct ExampleCircuit {
+            a: Fp,
+            b: Fp,
+        }
+
+        impl Circuit<Fp> for ExampleCircuit {
+            type Config = (MulConfig, Column<Instance>);
+            type FloorPlanner = SimpleFloorPlanner;
+
+            fn configure(meta: &mut ConstraintSystem<Fp>) -> Self::Config {
+                PublicMulCircuit::configure(meta)
+            }
+
+            fn without_witnesses(&self) -> Self {
+                Self {
+                    a: Fp::zero(),
+                    b: Fp::zero(),
+                }
+            }
+
+            fn synthesize(
+                &self,
+                (config, instance): Self::Config,
+                mut layouter: impl Layouter<Fp>,
+            ) -> Result<(), Error> {
+                let mut load = |value: Fp| {
+                    layouter.assign_region(
+                        || "load private",
+                        |mut region| {
+                            region.assign_advice(
+                                || "private input",
+                                config.advice[0],
+                                0,
+                                || Ok(value),
+                            )
+                        },
+                    )
+                };
+                let a = load(self.a)?;
+                let b = load(self.b)?;
+
+                let out = layouter.assign_region(
+                    || "mul",
+                    |mut region| {
+                        config.s_mul.enable(&mut region, 0)?;
+                        a.copy_advice(|| "lhs", &mut region, config.advice[0], 0)?;
+                        b.copy_advice(|| "rhs", &mut region, config.advice[1], 0)?;
+                        region.assign_advice(
+                            || "lhs * rhs",
+                            config.advice[0],
+                            1,
+                            || Ok(self.a * self.b),
+                        )
+                    },
+                )?;
+
+                layouter
+                    .namespace(|| "expose public")
+                    .constrain_instance(out.cell(), instance, 0)
+            }
+        }
+
+        let (a, b) = (Fp::from(2), Fp::from(3));
+        let prover = MockProver::run(K, &ExampleCircuit { a, b }, vec![vec![a * b]]).unwrap();
+        assert_eq!(prover.verify(), Ok(()));
+
+        // `load private` assigns `a` and `b` to rows 0 and 1, and `mul` starts at row 2.
+        let cell = |column_type: Any, index: usize, row: usize| {
+            (metadata::Column::from((column_type, index)), row)
+        };
+        assert_eq!(
+            prover.copy_constraints(),
+            vec![
+                // The two input copies in `mul`.
+                vec![(cell(Any::Advice, 0, 2), cell(Any::Advice, 0, 0))],
+                vec![(cell(Any::Advice, 1, 2), cell(Any::Advice, 0, 1))],
+                // The output copied to the instance column in `expose public`.
+                vec![(cell(Any::Advice, 0, 3), cell(Any::Instance, 0, 0))],
+            ]
+        );
+    }
+
+    #[test]
+    fn explain_failure_report() {
+        const K: u32 = 4;
+
+        // The public input is 6, but the circuit witnesses 5.
+        let instance = vec![vec![Fp::zero(), Fp::from(6)]];
+        let prover = MockProver::run(K, &PublicCircuit {}, instance).unwrap();
+        let failures = prover.verify().unwrap_err();
+        assert_eq!(failures.len(), 1);
+
+        let report = explain_failure(&prover, &failures[0]);
+        assert!(report.contains("Gate 0 ('public input'), constraint 0"));
+        assert!(report.contains("Region 1 ('expose public') at offset 0 (row 1)"));
+        assert!(report.contains("Column('Advice', 0)@0 = 0x5"));
+        assert!(report.contains("Column('Instance', 0)@0 = 0x6"));
+        assert!(report.contains("Result:\n  -1\n"));
     }
 }
@@ -0,0 +1,422 @@
+//! An [`Assignment`] that checks gates while the circuit is being synthesized.
+
+use std::collections::BTreeMap;
+
+use ff::Field;
+
+use super::{metadata, util, FailureLocation, VerifyFailure};
+use crate::{
+    arithmetic::FieldExt,
+    plonk::{
+        Advice, Any, Assigned, Assignment, Circuit, Column, ConstraintSystem, Error, Fixed,
+        FloorPlanner, Instance, Selector,
+    },
+    poly::Rotation,
+};
+
+/// A gate enabled on a particular row of a region, which has not been checked yet.
+#[derive(Debug)]
+struct PendingGate {
+    gate_index: usize,
+    row: usize,
+    region_index: usize,
+}
+
+/// The region currently being assigned to.
+#[derive(Debug)]
+struct CurrentRegion {
+    name: String,
+    /// The first row assigned to so far.
+    start: Option<usize>,
+    /// The selectors enabled in this region, with the rows they were enabled on.
+    enabled_selectors: Vec<(Selector, usize)>,
+}
+
+/// An [`Assignment`] that evaluates each gate as soon as its region has been
+/// assigned, and aborts synthesis at the first constraint that is not satisfied.
+///
+/// This gives faster feedback during development than [`MockProver`], which only
+/// checks the circuit after it has been fully synthesized. It only checks custom
+/// gates; use [`MockProver`] for a full check including lookups and equality
+/// constraints.
+///
+/// A gate is checked when the region that enables it is exited, if every cell it
+/// queries has been assigned by then. Otherwise the check is deferred until those
+/// cells are assigned, or until the end of synthesis, at which point any cells that
+/// are still unassigned are treated as zero.
+///
+/// [`MockProver`]: super::MockProver
+#[derive(Debug)]
+pub struct CheckingAssignment<F: Field> {
+    k: u32,
+    n: usize,
+    cs: ConstraintSystem<F>,
+
+    /// The names and starting rows of the regions that have been exited.
+    regions: Vec<(String, usize)>,
+    current_region: Option<CurrentRegion>,
+    pending: Vec<PendingGate>,
+    /// The first failure found, after which every assignment returns an error.
+    failure: Option<VerifyFailure>,
+
+    fixed: Vec<Vec<Option<F>>>,
+    advice: Vec<Vec<Option<F>>>,
+    instance: Vec<Vec<F>>,
+    selectors: Vec<Vec<bool>>,
+
+    usable_rows: usize,
+}
+
+impl<F: FieldExt> CheckingAssignment<F> {
+    /// Synthesizes `circuit` with the given `instance` values, checking each gate as
+    /// it is enabled.
+    ///
+    /// Returns the first constraint that is not satisfied, or `None` if every gate
+    /// is satisfied. Synthesis stops as soon as a failure is found, so regions after
+    /// the failing one are not assigned.
+    pub fn run<ConcreteCircuit: Circuit<F>>(
+        k: u32,
+        circuit: &ConcreteCircuit,
+        instance: Vec<Vec<F>>,
+    ) -> Result<Option<VerifyFailure>, Error> {
+        let n = 1 << k;
+
+        let mut cs = ConstraintSystem::default();
+        let config = ConcreteCircuit::configure(&mut cs);
+        let cs = cs;
+
+        if n < cs.minimum_rows() {
+            return Err(Error::not_enough_rows_available(k));
+        }
+
+        if instance.len() != cs.num_instance_columns {
+            return Err(Error::InvalidInstances);
+        }
+
+        let usable_rows = n - (cs.blinding_factors() + 1);
+        let instance = instance
+            .into_iter()
+            .map(|mut instance| {
+                if instance.len() > usable_rows {
+                    return Err(Error::InstanceTooLarge);
+                }
+
+                instance.resize(n, F::zero());
+                Ok(instance)
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let constants = cs.constants.clone();
+        let mut assignment = CheckingAssignment {
+            k,
+            n,
+            fixed: cs
+                .fixed_defaults()
+                .into_iter()
+                .map(|default| vec![default; n])
+                .collect(),
+            advice: vec![vec![None; n]; cs.num_advice_columns],
+            instance,
+            selectors: vec![vec![false; n]; cs.num_selectors],
+            cs,
+            regions: vec![],
+            current_region: None,
+            pending: vec![],
+            failure: None,
+            usable_rows,
+        };
+
+        match ConcreteCircuit::FloorPlanner::synthesize(&mut assignment, circuit, config, constants)
+        {
+            Ok(()) => (),
+            Err(_) if assignment.failure.is_some() => return Ok(assignment.failure),
+            Err(e) => return Err(e),
+        }
+
+        // Check the gates whose cells were never all assigned.
+        assignment.check_pending(true);
+        Ok(assignment.failure)
+    }
+
+    fn resolve(&self, row: usize, rotation: i32) -> usize {
+        ((row as i32 + self.n as i32 + rotation) % self.n as i32) as usize
+    }
+
+    fn value(&self, column: Column<Any>, row: usize) -> Option<F> {
+        match column.column_type() {
+            Any::Advice => self.advice[column.index()][row],
+            Any::Fixed => self.fixed[column.index()][row],
+            Any::Instance => Some(self.instance[column.index()][row]),
+        }
+    }
+
+    /// Checks the given enabled gate, returning the constraint that fails if there
+    /// is one. Returns `None` if the gate cannot be checked yet, because some of its
+    /// cells are unassigned and `force` is not set.
+    fn check(&self, pending: &PendingGate, force: bool) -> Option<Option<VerifyFailure>> {
+        let gate = &self.cs.gates[pending.gate_index];
+        let row = pending.row;
+
+        let cells = gate
+            .queried_cells()
+            .iter()
+            .map(|cell| {
+                (
+                    cell,
+                    self.value(cell.column, self.resolve(row, cell.rotation.0)),
+                )
+            })
+            .collect::<Vec<_>>();
+        if !force && cells.iter().any(|(_, value)| value.is_none()) {
+            return None;
+        }
+
+        let load = |column: Column<Any>, rotation: Rotation| {
+            self.value(column, self.resolve(row, rotation.0))
+                .unwrap_or_else(F::zero)
+        };
+        let failing = gate.polynomials().iter().position(|poly| {
+            !poly
+                .evaluate(
+                    &|scalar| scalar,
+                    &|selector| {
+                        if self.selectors[selector.0][row] {
+                            F::one()
+                        } else {
+                            F::zero()
+                        }
+                    },
+                    &|index, _, _| {
+                        let (column, at) = self.cs.fixed_queries[index];
+                        load(column.into(), at)
+                    },
+                    &|index, _, _| {
+                        let (column, at) = self.cs.advice_queries[index];
+                        load(column.into(), at)
+                    },
+                    &|index, _, _| {
+                        let (column, at) = self.cs.instance_queries[index];
+                        load(column.into(), at)
+                    },
+                    &|a| -a,
+                    &|a, b| a + b,
+                    &|a, b| a * b,
+                    &|a, scalar| a * scalar,
+                )
+                .is_zero_vartime()
+        });
+
+        Some(failing.map(|poly_index| {
+            let (region_name, region_start) = &self.regions[pending.region_index];
+            let cell_values: BTreeMap<metadata::VirtualCell, String> = cells
+                .into_iter()
+                .map(|(cell, value)| {
+                    (
+                        cell.clone().into(),
+                        util::format_value(value.unwrap_or_else(F::zero)),
+                    )
+                })
+                .collect();
+            VerifyFailure::ConstraintNotSatisfied {
+                constraint: (
+                    (pending.gate_index, gate.name()).into(),
+                    poly_index,
+                    gate.constraint_name(poly_index),
+                )
+                    .into(),
+                location: FailureLocation::InRegion {
+                    region: (pending.region_index, region_name.clone()).into(),
+                    offset: row - region_start,
+                },
+                cell_values: cell_values.into_iter().collect(),
+            }
+        }))
+    }
+
+    /// Checks every pending gate that can be checked, stopping at the first failure.
+    fn check_pending(&mut self, force: bool) {
+        let pending = std::mem::take(&mut self.pending);
+        for gate in pending {
+            if self.failure.is_some() {
+                break;
+            }
+            match self.check(&gate, force) {
+                Some(failure) => self.failure = failure,
+                None => self.pending.push(gate),
+            }
+        }
+    }
+
+    /// Returns an error if a failure has already been found, to abort synthesis.
+    fn check_failure(&self) -> Result<(), Error> {
+        if self.failure.is_some() {
+            Err(Error::ConstraintSystemFailure)
+        } else {
+            Ok(())
+        }
+    }
+
+    fn check_row(&self, row: usize) -> Result<(), Error> {
+        self.check_failure()?;
+        if row < self.usable_rows {
+            Ok(())
+        } else {
+            Err(Error::not_enough_rows_available(self.k))
+        }
+    }
+
+    fn track_row(&mut self, row: usize) {
+        if let Some(region) = self.current_region.as_mut() {
+            region.start = Some(region.start.map_or(row, |start| start.min(row)));
+        }
+    }
+}
+
+impl<F: FieldExt> Assignment<F> for CheckingAssignment<F> {
+    fn enter_region<NR, N>(&mut self, name: N)
+    where
+        NR: Into<String>,
+        N: FnOnce() -> NR,
+    {
+        assert!(self.current_region.is_none());
+        self.current_region = Some(CurrentRegion {
+            name: name().into(),
+            start: None,
+            enabled_selectors: vec![],
+        });
+    }
+
+    fn exit_region(&mut self) {
+        let region = self.current_region.take().unwrap();
+        let region_index = self.regions.len();
+        self.regions
+            .push((region.name, region.start.unwrap_or_default()));
+
+        for (selector, row) in region.enabled_selectors {
+            for (gate_index, gate) in self.cs.gates.iter().enumerate() {
+                if gate.queried_selectors().contains(&selector) {
+                    self.pending.push(PendingGate {
+                        gate_index,
+                        row,
+                        region_index,
+                    });
+                }
+            }
+        }
+        self.check_pending(false);
+    }
+
+    fn enable_selector<A, AR>(&mut self, _: A, selector: &Selector, row: usize) -> Result<(), Error>
+    where
+        A: FnOnce() -> AR,
+        AR: Into<String>,
+    {
+        self.check_row(row)?;
+        self.track_row(row);
+
+        self.current_region
+            .as_mut()
+            .unwrap()
+            .enabled_selectors
+            .push((*selector, row));
+        self.selectors[selector.0][row] = true;
+
+        Ok(())
+    }
+
+    fn query_instance(&self, column: Column<Instance>, row: usize) -> Result<Option<F>, Error> {
+        self.check_row(row)?;
+
+        self.instance
+            .get(column.index())
+            .and_then(|column| column.get(row))
+            .map(|v| Some(*v))
+            .ok_or(Error::BoundsFailure)
+    }
+
+    fn assign_advice<V, VR, A, AR>(
+        &mut self,
+        _: A,
+        column: Column<Advice>,
+        row: usize,
+        to: V,
+    ) -> Result<(), Error>
+    where
+        V: FnOnce() -> Result<VR, Error>,
+        VR: Into<Assigned<F>>,
+        A: FnOnce() -> AR,
+        AR: Into<String>,
+    {
+        self.check_row(row)?;
+        self.track_row(row);
+
+        *self
+            .advice
+            .get_mut(column.index())
+            .and_then(|v| v.get_mut(row))
+            .ok_or(Error::BoundsFailure)? = Some(to()?.into().evaluate());
+
+        Ok(())
+    }
+
+    fn assign_fixed<V, VR, A, AR>(
+        &mut self,
+        _: A,
+        column: Column<Fixed>,
+        row: usize,
+        to: V,
+    ) -> Result<(), Error>
+    where
+        V: FnOnce() -> Result<VR, Error>,
+        VR: Into<Assigned<F>>,
+        A: FnOnce() -> AR,
+        AR: Into<String>,
+    {
+        self.check_row(row)?;
+        self.track_row(row);
+
+        *self
+            .fixed
+            .get_mut(column.index())
+            .and_then(|v| v.get_mut(row))
+            .ok_or(Error::BoundsFailure)? = Some(to()?.into().evaluate());
+
+        Ok(())
+    }
+
+    fn copy(
+        &mut self,
+        _: Column<Any>,
+        left_row: usize,
+        _: Column<Any>,
+        right_row: usize,
+    ) -> Result<(), Error> {
+        // Equality constraints are not checked here.
+        self.check_row(left_row)?;
+        self.check_row(right_row)
+    }
+
+    fn fill_from_row(
+        &mut self,
+        col: Column<Fixed>,
+        from_row: usize,
+        to: Option<Assigned<F>>,
+    ) -> Result<(), Error> {
+        self.check_row(from_row)?;
+
+        for row in from_row..self.usable_rows {
+            self.assign_fixed(|| "", col, row, || to.ok_or(Error::Synthesis))?;
+        }
+
+        Ok(())
+    }
+
+    fn push_namespace<NR, N>(&mut self, _: N)
+    where
+        NR: Into<String>,
+        N: FnOnce() -> NR,
+    {
+    }
+
+    fn pop_namespace(&mut self, _: Option<String>) {}
+}
@@ -33,8 +33,12 @@ pub struct CircuitCost<G: PrimeGroup, ConcreteCircuit: Circuit<G::Scalar>> {
     fixed_queries: usize,
     /// Number of lookup arguments.
     lookups: usize,
-    /// Number of columns in the global permutation.
+    /// Number of columns in the permutation arguments.
     permutation_cols: usize,
+    /// Number of chunks the permutation arguments' columns are split into.
+    permutation_chunks: usize,
+    /// Number of permutation arguments involving any columns.
+    permutation_args: usize,
     /// Number of distinct sets of points in the multiopening argument.
     point_sets: usize,
 
@@ -72,6 +76,10 @@ impl<F: Field> Assignment<F> for Assembly {
         Ok(None)
     }
 
+    fn evaluates_witness(&self) -> bool {
+        false
+    }
+
     fn assign_advice<V, VR, A, AR>(
         &mut self,
         _: A,
@@ -163,9 +171,8 @@ impl<G: PrimeGroup, ConcreteCircuit: Circuit<G::Scalar>> CircuitCost<G, Concrete
             .chain(cs.instance_queries.iter().map(|(c, r)| ((*c).into(), *r)))
             .chain(cs.fixed_queries.iter().map(|(c, r)| ((*c).into(), *r)))
             .chain(
-                cs.permutation
-                    .get_columns()
-                    .into_iter()
+                cs.permutations()
+                    .flat_map(|argument| argument.get_columns())
                     .map(|c| (c, Rotation::cur())),
             )
         {
@@ -187,8 +194,12 @@ impl<G: PrimeGroup, ConcreteCircuit: Circuit<G::Scalar>> CircuitCost<G, Concrete
         // Include permutation polynomials in point sets.
         point_sets.insert(vec![0, 1]); // permutation_product_poly
         let max_deg = cs.degree();
-        let permutation_cols = cs.permutation.get_columns().len();
-        if permutation_cols > max_deg - 2 {
+        let permutation_lens: Vec<_> = cs
+            .permutations()
+            .map(|argument| argument.get_columns().len())
+            .filter(|len| *len > 0)
+            .collect();
+        if permutation_lens.iter().any(|len| *len > max_deg - 2) {
             // permutation_product_poly for chaining chunks.
             point_sets.insert(vec![-((cs.blinding_factors() + 1) as i32), 0, 1]);
         }
@@ -201,20 +212,17 @@ impl<G: PrimeGroup, ConcreteCircuit: Circuit<G::Scalar>> CircuitCost<G, Concrete
             advice_queries: cs.advice_queries.len(),
             fixed_queries: cs.fixed_queries.len(),
             lookups: cs.lookups.len(),
-            permutation_cols,
+            permutation_cols: permutation_lens.iter().sum(),
+            permutation_chunks: cs.num_permutation_chunks(),
+            permutation_args: permutation_lens.len(),
             point_sets: point_sets.len(),
             _marker: PhantomData::default(),
         }
     }
 
-    fn permutation_chunks(&self) -> usize {
-        let chunk_size = self.max_deg - 2;
-        (self.permutation_cols + chunk_size - 1) / chunk_size
-    }
-
     /// Returns the marginal proof size per instance of this circuit.
     pub fn marginal_proof_size(&self) -> MarginalProofSize<G> {
-        let chunks = self.permutation_chunks();
+        let chunks = self.permutation_chunks;
 
         MarginalProofSize {
             // Cells:
@@ -229,10 +237,10 @@ impl<G: PrimeGroup, ConcreteCircuit: Circuit<G::Scalar>> CircuitCost<G, Concrete
             // - 5 evals per lookup argument per instance
             lookups: ProofContribution::new(3 * self.lookups, 5 * self.lookups),
 
-            // Global permutation argument:
+            // Permutation arguments:
             // - chunks commitments per instance
-            // - 2*chunks + (chunks - 1) evals per instance
-            equality: ProofContribution::new(chunks, 3 * chunks - 1),
+            // - 2*chunks + (chunks - 1) evals per argument per instance
+            equality: ProofContribution::new(chunks, 3 * chunks - self.permutation_args),
 
             _marker: PhantomData::default(),
         }
@@ -254,7 +262,7 @@ impl<G: PrimeGroup, ConcreteCircuit: Circuit<G::Scalar>> CircuitCost<G, Concrete
             // - marginal cost per instance
             lookups: marginal.lookups * instances,
 
-            // Global permutation argument:
+            // Permutation arguments:
             // - marginal cost per instance
             // - 1 eval per column
             equality: marginal.equality * instances
@@ -100,6 +100,10 @@ impl<F: Field> Assignment<F> for Graph {
         Ok(None)
     }
 
+    fn evaluates_witness(&self) -> bool {
+        false
+    }
+
     fn assign_advice<V, VR, A, AR>(
         &mut self,
         _: A,
@@ -4,7 +4,7 @@ use crate::plonk::{self, Any};
 use std::fmt;
 
 /// Metadata about a column within a circuit.
-#[derive(Debug, PartialEq, Eq, PartialOrd, Ord)]
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
 pub struct Column {
     /// The type of the column.
     column_type: Any,
@@ -99,6 +99,13 @@ impl fmt::Display for Gate {
     }
 }
 
+impl Gate {
+    /// Returns the index of the gate.
+    pub(super) fn index(&self) -> usize {
+        self.index
+    }
+}
+
 impl From<(usize, &'static str)> for Gate {
     fn from((index, name): (usize, &'static str)) -> Self {
         Gate { index, name }
@@ -136,6 +143,18 @@ impl fmt::Display for Constraint {
     }
 }
 
+impl Constraint {
+    /// Returns the index of the gate containing the constraint.
+    pub(super) fn gate_index(&self) -> usize {
+        self.gate.index
+    }
+
+    /// Returns the index of the polynomial constraint within its gate.
+    pub(super) fn index(&self) -> usize {
+        self.index
+    }
+}
+
 impl From<(Gate, usize, &'static str)> for Constraint {
     fn from((gate, index, name): (Gate, usize, &'static str)) -> Self {
         Constraint { gate, index, name }
@@ -159,6 +178,13 @@ impl fmt::Display for Region {
     }
 }
 
+impl Region {
+    /// Returns the index of the region.
+    pub(super) fn index(&self) -> usize {
+        self.index
+    }
+}
+
 impl From<(usize, String)> for Region {
     fn from((index, name): (usize, String)) -> Self {
         Region { index, name }
@@ -0,0 +1,198 @@
+//! The simple example's `mul` gate, shared by tests as a fixture.
+
+use pairing::bn256::Fr as Fp;
+
+use crate::{
+    arithmetic::FieldExt,
+    circuit::{AssignedCell, Layouter, Region, SimpleFloorPlanner},
+    plonk::{Advice, Circuit, Column, ConstraintSystem, Error, Instance, Selector},
+    poly::Rotation,
+};
+
+/// Two advice columns with equality enabled, and the `mul` gate constraining
+/// `lhs * rhs = out`, with `lhs` and `rhs` on one row and `out` below `lhs`.
+#[derive(Clone, Debug)]
+pub(crate) struct MulConfig {
+    pub(crate) advice: [Column<Advice>; 2],
+    pub(crate) s_mul: Selector,
+}
+
+impl MulConfig {
+    pub(crate) fn configure<F: FieldExt>(meta: &mut ConstraintSystem<F>) -> Self {
+        let advice = [meta.advice_column(), meta.advice_column()];
+        for column in &advice {
+            meta.enable_equality(*column);
+        }
+        let s_mul = meta.selector();
+
+        meta.create_gate("mul", |meta| {
+            let lhs = meta.query_advice(advice[0], Rotation::cur());
+            let rhs = meta.query_advice(advice[1], Rotation::cur());
+            let out = meta.query_advice(advice[0], Rotation::next());
+            let s_mul = meta.query_selector(s_mul);
+            vec![s_mul * (lhs * rhs - out)]
+        });
+
+        MulConfig { advice, s_mul }
+    }
+
+    /// Assigns `a` and `b` next to each other at offset 0 of `region`.
+    pub(crate) fn load<F: FieldExt>(
+        &self,
+        region: &mut Region<'_, F>,
+        a: F,
+        b: F,
+    ) -> Result<(AssignedCell<F, F>, AssignedCell<F, F>), Error> {
+        let a = region.assign_advice(|| "a", self.advice[0], 0, || Ok(a))?;
+        let b = region.assign_advice(|| "b", self.advice[1], 0, || Ok(b))?;
+        Ok((a, b))
+    }
+
+    /// Enables the gate at offset 0 of `region` and assigns its cells, returning the
+    /// output cell. `out` is not checked against `lhs * rhs`, so that tests can break
+    /// the gate.
+    pub(crate) fn mul<F: FieldExt>(
+        &self,
+        region: &mut Region<'_, F>,
+        lhs: F,
+        rhs: F,
+        out: F,
+    ) -> Result<AssignedCell<F, F>, Error> {
+        self.s_mul.enable(region, 0)?;
+        region.assign_advice(|| "lhs", self.advice[0], 0, || Ok(lhs))?;
+        region.assign_advice(|| "rhs", self.advice[1], 0, || Ok(rhs))?;
+        region.assign_advice(|| "out", self.advice[0], 1, || Ok(out))
+    }
+}
+
+/// Assigns a single `mul` region computing `a * b = out`.
+#[derive(Clone, Debug, Default)]
+pub(crate) struct MulCircuit {
+    pub(crate) a: Option<Fp>,
+    pub(crate) b: Option<Fp>,
+    pub(crate) out: Option<Fp>,
+}
+
+impl MulCircuit {
+    pub(crate) fn new(a: u64, b: u64, out: u64) -> Self {
+        MulCircuit {
+            a: Some(Fp::from(a)),
+            b: Some(Fp::from(b)),
+            out: Some(Fp::from(out)),
+        }
+    }
+}
+
+impl Circuit<Fp> for MulCircuit {
+    type Config = MulConfig;
+    type FloorPlanner = SimpleFloorPlanner;
+
+    fn without_witnesses(&self) -> Self {
+        Self::default()
+    }
+
+    fn configure(meta: &mut ConstraintSystem<Fp>) -> Self::Config {
+        MulConfig::configure(meta)
+    }
+
+    fn synthesize(
+        &self,
+        config: Self::Config,
+        mut layouter: impl Layouter<Fp>,
+    ) -> Result<(), Error> {
+        layouter.assign_region(
+            || "mul",
+            |mut region| {
+                config.s_mul.enable(&mut region, 0)?;
+                region.assign_advice(
+                    || "lhs",
+                    config.advice[0],
+                    0,
+                    || self.a.ok_or(Error::Synthesis),
+                )?;
+                region.assign_advice(
+                    || "rhs",
+                    config.advice[1],
+                    0,
+                    || self.b.ok_or(Error::Synthesis),
+                )?;
+                region.assign_advice(
+                    || "out",
+                    config.advice[0],
+                    1,
+                    || self.out.ok_or(Error::Synthesis),
+                )?;
+                Ok(())
+            },
+        )
+    }
+}
+
+/// Computes `a * b` in a `mul` region, and exposes it in row 0 of an instance column.
+#[derive(Clone, Debug, Default)]
+pub(crate) struct PublicMulCircuit {
+    pub(crate) a: Option<Fp>,
+    pub(crate) b: Option<Fp>,
+}
+
+impl PublicMulCircuit {
+    pub(crate) fn new(a: u64, b: u64) -> Self {
+        PublicMulCircuit {
+            a: Some(Fp::from(a)),
+            b: Some(Fp::from(b)),
+        }
+    }
+}
+
+impl Circuit<Fp> for PublicMulCircuit {
+    type Config = (MulConfig, Column<Instance>);
+    type FloorPlanner = SimpleFloorPlanner;
+
+    fn without_witnesses(&self) -> Self {
+        Self::default()
+    }
+
+    fn configure(meta: &mut ConstraintSystem<Fp>) -> Self::Config {
+        let config = MulConfig::configure(meta);
+        let instance = meta.instance_column();
+        meta.enable_equality(instance);
+        (config, instance)
+    }
+
+    fn synthesize(
+        &self,
+        (config, instance): Self::Config,
+        mut layouter: impl Layouter<Fp>,
+    ) -> Result<(), Error> {
+        let out = layouter.assign_region(
+            || "mul",
+            |mut region| {
+                config.s_mul.enable(&mut region, 0)?;
+                region.assign_advice(
+                    || "lhs",
+                    config.advice[0],
+                    0,
+                    || self.a.ok_or(Error::Synthesis),
+                )?;
+                region.assign_advice(
+                    || "rhs",
+                    config.advice[1],
+                    0,
+                    || self.b.ok_or(Error::Synthesis),
+                )?;
+                region.assign_advice(
+                    || "out",
+                    config.advice[0],
+                    1,
+                    || {
+                        self.a
+                            .zip(self.b)
+                            .map(|(a, b)| a * b)
+                            .ok_or(Error::Synthesis)
+                    },
+                )
+            },
+        )?;
+        layouter.constrain_instance(out.cell(), instance, 0)
+    }
+}
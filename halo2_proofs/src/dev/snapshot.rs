@@ -0,0 +1,300 @@
+//! Golden snapshots of a circuit's assignment, for detecting unintended changes.
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt;
+
+use super::{metadata, util, CellValue, MockProver};
+use crate::{arithmetic::FieldExt, plonk::Any};
+
+/// The assignment of a circuit as recorded by [`MockProver::snapshot`]: its column
+/// counts, assigned cells, enabled selectors and permutation cycles.
+///
+/// Cell values are stored in their formatted form, so that a snapshot does not
+/// depend on the field and can be serialized (with the `serde` feature) and checked
+/// in alongside the circuit's tests.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct CircuitSnapshot {
+    /// The circuit has `2^k` rows.
+    pub k: u32,
+    /// The number of advice columns.
+    pub num_advice_columns: usize,
+    /// The number of fixed columns, including those that selectors were compressed
+    /// into.
+    pub num_fixed_columns: usize,
+    /// The number of instance columns.
+    pub num_instance_columns: usize,
+    /// The number of selectors.
+    pub num_selectors: usize,
+    /// The assigned advice cells as `(column, row, value)`, ordered by column and then
+    /// by row.
+    pub advice: Vec<(usize, usize, String)>,
+    /// The assigned fixed cells as `(column, row, value)`, ordered by column and then
+    /// by row.
+    pub fixed: Vec<(usize, usize, String)>,
+    /// The enabled selectors as `(selector, row)`, ordered by selector and then by row.
+    pub selectors: Vec<(usize, usize)>,
+    /// The permutation cycles containing more than one cell. Each cell is
+    /// `(column, row)`, where `column` indexes the columns of the permutation
+    /// arguments, numbered consecutively from one argument to the next. The cells of
+    /// a cycle are sorted, and cycles are ordered by their first cell.
+    pub cycles: Vec<Vec<(usize, usize)>>,
+}
+
+/// The first difference between a circuit and a [`CircuitSnapshot`], as reported by
+/// [`CircuitSnapshot::diff`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum SnapshotDiff {
+    /// The circuits differ in size or in their number of columns or selectors.
+    Shape {
+        /// The quantity that differs.
+        field: &'static str,
+        /// The snapshot's value.
+        expected: usize,
+        /// The circuit's value.
+        got: usize,
+    },
+    /// A cell has a different value, or is only assigned in one of the two.
+    Cell {
+        /// The column containing the cell.
+        column: metadata::Column,
+        /// The row of the cell.
+        row: usize,
+        /// The snapshot's value, if the cell was assigned.
+        expected: Option<String>,
+        /// The circuit's value, if the cell was assigned.
+        got: Option<String>,
+    },
+    /// A selector is enabled on a row in only one of the two.
+    Selector {
+        /// The index of the selector.
+        selector: usize,
+        /// The row the selector is enabled on.
+        row: usize,
+        /// Whether the snapshot enables the selector on this row.
+        expected: bool,
+    },
+    /// A permutation cycle differs.
+    Cycle {
+        /// The position of the cycle in [`CircuitSnapshot::cycles`].
+        index: usize,
+        /// The snapshot's cycle, if it has this many.
+        expected: Option<Vec<(usize, usize)>>,
+        /// The circuit's cycle, if it has this many.
+        got: Option<Vec<(usize, usize)>>,
+    },
+}
+
+impl fmt::Display for SnapshotDiff {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SnapshotDiff::Shape {
+                field,
+                expected,
+                got,
+            } => write!(f, "{}: expected {}, got {}", field, expected, got),
+            SnapshotDiff::Cell {
+                column,
+                row,
+                expected,
+                got,
+            } => {
+                let value = |v: &Option<String>| v.clone().unwrap_or_else(|| "unassigned".into());
+                write!(
+                    f,
+                    "{}, row {}: expected {}, got {}",
+                    column,
+                    row,
+                    value(expected),
+                    value(got)
+                )
+            }
+            SnapshotDiff::Selector {
+                selector,
+                row,
+                expected,
+            } => write!(
+                f,
+                "Selector {}, row {}: expected {}, got {}",
+                selector,
+                row,
+                if *expected { "enabled" } else { "disabled" },
+                if *expected { "disabled" } else { "enabled" },
+            ),
+            SnapshotDiff::Cycle {
+                index,
+                expected,
+                got,
+            } => write!(
+                f,
+                "Permutation cycle {}: expected {:?}, got {:?}",
+                index, expected, got
+            ),
+        }
+    }
+}
+
+impl CircuitSnapshot {
+    /// Compares this snapshot against `expected`, returning the first difference or
+    /// `None` if they match.
+    ///
+    /// The shape is compared first, then the advice and fixed cells, the selectors
+    /// and finally the permutation cycles, so the reported difference is the
+    /// earliest one found in that order.
+    pub fn diff(&self, expected: &CircuitSnapshot) -> Option<SnapshotDiff> {
+        let shape = |s: &CircuitSnapshot| {
+            [
+                ("k", s.k as usize),
+                ("advice columns", s.num_advice_columns),
+                ("fixed columns", s.num_fixed_columns),
+                ("instance columns", s.num_instance_columns),
+                ("selectors", s.num_selectors),
+            ]
+        };
+        let shape_diff = shape(expected)
+            .iter()
+            .zip(shape(self).iter())
+            .find(|((_, expected), (_, got))| expected != got)
+            .map(|(&(field, expected), &(_, got))| SnapshotDiff::Shape {
+                field,
+                expected,
+                got,
+            });
+
+        let cells = |column_type: Any,
+                     expected: &[(usize, usize, String)],
+                     got: &[(usize, usize, String)]| {
+            let by_cell = |cells: &[(usize, usize, String)]| {
+                cells
+                    .iter()
+                    .map(|(column, row, value)| ((*column, *row), value.clone()))
+                    .collect::<BTreeMap<_, _>>()
+            };
+            let (expected, got) = (by_cell(expected), by_cell(got));
+            expected
+                .keys()
+                .chain(got.keys())
+                .collect::<BTreeSet<_>>()
+                .into_iter()
+                .find_map(|cell| {
+                    let (expected, got) = (expected.get(cell), got.get(cell));
+                    (expected != got).then(|| SnapshotDiff::Cell {
+                        column: (column_type, cell.0).into(),
+                        row: cell.1,
+                        expected: expected.cloned(),
+                        got: got.cloned(),
+                    })
+                })
+        };
+
+        let selector_diff = || {
+            let expected = expected.selectors.iter().collect::<BTreeSet<_>>();
+            let got = self.selectors.iter().collect::<BTreeSet<_>>();
+            expected
+                .symmetric_difference(&got)
+                .next()
+                .map(|&&(selector, row)| SnapshotDiff::Selector {
+                    selector,
+                    row,
+                    expected: expected.contains(&&(selector, row)),
+                })
+        };
+
+        let cycle_diff = || {
+            (0..expected.cycles.len().max(self.cycles.len())).find_map(|index| {
+                let (expected, got) = (expected.cycles.get(index), self.cycles.get(index));
+                (expected != got).then(|| SnapshotDiff::Cycle {
+                    index,
+                    expected: expected.cloned(),
+                    got: got.cloned(),
+                })
+            })
+        };
+
+        shape_diff
+            .or_else(|| cells(Any::Advice, &expected.advice, &self.advice))
+            .or_else(|| cells(Any::Fixed, &expected.fixed, &self.fixed))
+            .or_else(selector_diff)
+            .or_else(cycle_diff)
+    }
+}
+
+impl<F: FieldExt> MockProver<F> {
+    /// Records the circuit's assignment as a [`CircuitSnapshot`], which can be
+    /// compared against later runs with [`MockProver::assert_matches_snapshot`].
+    pub fn snapshot(&self) -> CircuitSnapshot {
+        let cells = |columns: &[Vec<CellValue<F>>]| {
+            columns
+                .iter()
+                .enumerate()
+                .flat_map(|(column, cells)| {
+                    cells
+                        .iter()
+                        .enumerate()
+                        .filter_map(move |(row, cell)| match cell {
+                            CellValue::Assigned(v) => Some((column, row, util::format_value(*v))),
+                            CellValue::Unassigned | CellValue::Poison(_) => None,
+                        })
+                })
+                .collect()
+        };
+
+        let selectors = self
+            .selectors
+            .iter()
+            .enumerate()
+            .flat_map(|(selector, rows)| {
+                rows.iter()
+                    .enumerate()
+                    .filter(|(_, enabled)| **enabled)
+                    .map(move |(row, _)| (selector, row))
+            })
+            .collect();
+
+        // Walk every cycle of the permutations from its smallest cell, so that cycles
+        // come out sorted by their first cell.
+        let mut cycles = vec![];
+        let mut offset = 0;
+        for assembly in &self.permutations {
+            let mapping = &assembly.mapping;
+            let mut visited: Vec<Vec<bool>> =
+                mapping.iter().map(|c| vec![false; c.len()]).collect();
+            for (column, rows) in mapping.iter().enumerate() {
+                for row in 0..rows.len() {
+                    let mut cycle = vec![];
+                    let mut cell = (column, row);
+                    while !visited[cell.0][cell.1] {
+                        visited[cell.0][cell.1] = true;
+                        cycle.push((offset + cell.0, cell.1));
+                        cell = mapping[cell.0][cell.1];
+                    }
+                    if cycle.len() > 1 {
+                        cycle.sort_unstable();
+                        cycles.push(cycle);
+                    }
+                }
+            }
+            offset += mapping.len();
+        }
+
+        CircuitSnapshot {
+            k: self.k,
+            num_advice_columns: self.cs.num_advice_columns,
+            num_fixed_columns: self.cs.num_fixed_columns,
+            num_instance_columns: self.cs.num_instance_columns,
+            num_selectors: self.cs.num_selectors,
+            advice: cells(&self.advice),
+            fixed: cells(&self.fixed),
+            selectors,
+            cycles,
+        }
+    }
+
+    /// Panics with the first difference if this circuit's assignment does not match
+    /// `expected`, a snapshot recorded by [`MockProver::snapshot`].
+    pub fn assert_matches_snapshot(&self, expected: &CircuitSnapshot) {
+        if let Some(diff) = self.snapshot().diff(expected) {
+            panic!("circuit does not match snapshot: {}", diff);
+        }
+    }
+}
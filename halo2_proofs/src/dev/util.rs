@@ -26,6 +26,45 @@ pub(super) fn format_value<F: Field>(v: F) -> String {
     }
 }
 
+/// Renders an expression symbolically, with columns written as `<type><index>@<rotation>`
+/// (for example `A0@1` for advice column 0 at the next row).
+pub(super) fn format_expression<F: Field>(expr: &Expression<F>) -> String {
+    expr.evaluate(
+        &format_value,
+        &|selector| format!("S{}", selector.0),
+        &|_, column, rotation| format!("F{}@{}", column, rotation.0),
+        &|_, column, rotation| format!("A{}@{}", column, rotation.0),
+        &|_, column, rotation| format!("I{}@{}", column, rotation.0),
+        &|a| {
+            if a.contains(' ') {
+                format!("-({})", a)
+            } else {
+                format!("-{}", a)
+            }
+        },
+        &|a, b| {
+            if let Some(b) = b.strip_prefix('-') {
+                format!("{} - {}", a, b)
+            } else {
+                format!("{} + {}", a, b)
+            }
+        },
+        &|a, b| match (a.contains(' '), b.contains(' ')) {
+            (false, false) => format!("{} * {}", a, b),
+            (false, true) => format!("{} * ({})", a, b),
+            (true, false) => format!("({}) * {}", a, b),
+            (true, true) => format!("({}) * ({})", a, b),
+        },
+        &|a, s| {
+            if a.contains(' ') {
+                format!("({}) * {}", a, format_value(s))
+            } else {
+                format!("{} * {}", a, format_value(s))
+            }
+        },
+    )
+}
+
 fn cell_value<'a, F: FieldExt>(
     virtual_cells: &'a [VirtualCell],
     column_type: Any,
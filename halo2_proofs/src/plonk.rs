@@ -5,7 +5,8 @@
 //! [halo]: https://eprint.iacr.org/2019/1021
 //! [plonk]: https://eprint.iacr.org/2019/953
 
-use blake2b_simd::Params as Blake2bParams;
+use blake2b_simd::{Params as Blake2bParams, State as Blake2bState};
+use std::convert::TryInto;
 
 use crate::arithmetic::{BaseExt, CurveAffine, FieldExt};
 use crate::helpers::CurveRead;
@@ -44,8 +45,12 @@ use self::evaluation::Evaluator;
 pub struct VerifyingKey<C: CurveAffine> {
     domain: EvaluationDomain<C::Scalar>,
     fixed_commitments: Vec<C>,
-    permutation: permutation::VerifyingKey<C>,
+    /// The verifying keys of the permutation arguments, in the order of
+    /// `cs.permutations()`.
+    permutations: Vec<permutation::VerifyingKey<C>>,
     cs: ConstraintSystem<C::Scalar>,
+    /// The columns of the circuit that remain in `cs` after shrinking.
+    retained_columns: RetainedColumns,
 }
 
 impl<C: CurveAffine> VerifyingKey<C> {
@@ -54,7 +59,9 @@ impl<C: CurveAffine> VerifyingKey<C> {
         for commitment in &self.fixed_commitments {
             writer.write_all(commitment.to_bytes().as_ref())?;
         }
-        self.permutation.write(writer)?;
+        for permutation in &self.permutations {
+            permutation.write(writer)?;
+        }
 
         Ok(())
     }
@@ -64,19 +71,25 @@ impl<C: CurveAffine> VerifyingKey<C> {
         reader: &mut R,
         params: &Params<C>,
     ) -> io::Result<Self> {
-        let (domain, cs, _) = keygen::create_domain::<C, ConcreteCircuit>(params);
+        let (domain, cs, _) = keygen::create_domain::<C, ConcreteCircuit>(params)
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e.to_string()))?;
+        let (cs, retained_columns) = cs.shrink();
 
         let fixed_commitments: Vec<_> = (0..cs.num_fixed_columns)
             .map(|_| C::read(reader))
             .collect::<Result<_, _>>()?;
 
-        let permutation = permutation::VerifyingKey::read(reader, &cs.permutation)?;
+        let permutations = cs
+            .permutations()
+            .map(|argument| permutation::VerifyingKey::read(reader, argument))
+            .collect::<Result<_, _>>()?;
 
         Ok(VerifyingKey {
             domain,
             fixed_commitments,
-            permutation,
+            permutations,
             cs,
+            retained_columns,
         })
     }
 
@@ -85,20 +98,66 @@ impl<C: CurveAffine> VerifyingKey<C> {
         &self,
         transcript: &mut T,
     ) -> io::Result<()> {
+        transcript.common_scalar(self.transcript_repr())?;
+
+        Ok(())
+    }
+
+    /// Returns the digest of this verification key that is hashed into transcripts.
+    ///
+    /// Proofs declare the digest of the key they were created for right after their
+    /// format version, so that a verifier given the key of a different circuit
+    /// rejects them up front.
+    pub fn transcript_repr(&self) -> C::Scalar {
         let mut hasher = Blake2bParams::new()
             .hash_length(64)
             .personal(b"Halo2-Verify-Key")
             .to_state();
+        self.hash_pinned(&mut hasher);
+
+        C::Scalar::from_bytes_wide(hasher.finalize().as_array())
+    }
+
+    /// Returns a short, stable identifier for the circuit this verification key
+    /// belongs to.
+    ///
+    /// The fingerprint commits to the same contents as [`VerifyingKey::hash_into`]
+    /// (the evaluation domain, fixed commitments, permutation commitments and the
+    /// shape of the constraint system), under a distinct domain tag. Keys generated
+    /// for structurally identical circuits over the same parameters have the same
+    /// fingerprint.
+    pub fn fingerprint(&self) -> [u8; 32] {
+        let mut hasher = Blake2bParams::new()
+            .hash_length(32)
+            .personal(b"Halo2-VK-Fprint-")
+            .to_state();
+        self.hash_pinned(&mut hasher);
+
+        hasher
+            .finalize()
+            .as_bytes()
+            .try_into()
+            .expect("hash length is 32 bytes")
+    }
+
+    /// Returns the commitment to the lookup table stored in `column`, a column
+    /// allocated during `Circuit::configure`, or `None` if the column was removed by
+    /// [`ConstraintSystem::shrink_unused_columns`].
+    ///
+    /// Table commitments are computed once during key generation, so every proof
+    /// created with this key (and its proving key) reuses them; they are bound to
+    /// each proof's transcript through [`VerifyingKey::hash_into`].
+    pub fn table_commitment(&self, column: TableColumn) -> Option<C> {
+        self.retained_columns
+            .fixed_index(column.inner().index())
+            .map(|index| self.fixed_commitments[index])
+    }
 
+    fn hash_pinned(&self, hasher: &mut Blake2bState) {
         let s = format!("{:?}", self.pinned());
 
         hasher.update(&(s.len() as u64).to_le_bytes());
         hasher.update(s.as_bytes());
-
-        // Hash in final Blake2bState
-        transcript.common_scalar(C::Scalar::from_bytes_wide(hasher.finalize().as_array()))?;
-
-        Ok(())
     }
 
     /// Obtains a pinned representation of this verification key that contains
@@ -109,7 +168,7 @@ impl<C: CurveAffine> VerifyingKey<C> {
             scalar_modulus: C::Scalar::MODULUS,
             domain: self.domain.pinned(),
             fixed_commitments: &self.fixed_commitments,
-            permutation: &self.permutation,
+            permutations: &self.permutations,
             cs: self.cs.pinned(),
         }
     }
@@ -117,15 +176,32 @@ impl<C: CurveAffine> VerifyingKey<C> {
 
 /// Minimal representation of a verification key that can be used to identify
 /// its active contents.
-#[allow(dead_code)]
-#[derive(Debug)]
 pub struct PinnedVerificationKey<'a, C: CurveAffine> {
     base_modulus: &'static str,
     scalar_modulus: &'static str,
     domain: PinnedEvaluationDomain<'a, C::Scalar>,
     cs: PinnedConstraintSystem<'a, C::Scalar>,
     fixed_commitments: &'a Vec<C>,
-    permutation: &'a permutation::VerifyingKey<C>,
+    permutations: &'a [permutation::VerifyingKey<C>],
+}
+
+impl<'a, C: CurveAffine> std::fmt::Debug for PinnedVerificationKey<'a, C> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let mut debug_struct = f.debug_struct("PinnedVerificationKey");
+        debug_struct
+            .field("base_modulus", &self.base_modulus)
+            .field("scalar_modulus", &self.scalar_modulus)
+            .field("domain", &self.domain)
+            .field("cs", &self.cs)
+            .field("fixed_commitments", self.fixed_commitments)
+            .field("permutation", &self.permutations[0]);
+        // As in the pinned constraint system, the independent permutations are only
+        // included when there are any.
+        if self.permutations.len() > 1 {
+            debug_struct.field("independent_permutations", &&self.permutations[1..]);
+        }
+        debug_struct.finish()
+    }
 }
 /// This is a proving key which allows for the creation of proofs for a
 /// particular circuit.
@@ -138,7 +214,9 @@ pub struct ProvingKey<C: CurveAffine> {
     fixed_values: Vec<Polynomial<C::Scalar, LagrangeCoeff>>,
     fixed_polys: Vec<Polynomial<C::Scalar, Coeff>>,
     fixed_cosets: Vec<Polynomial<C::Scalar, ExtendedLagrangeCoeff>>,
-    permutation: permutation::ProvingKey<C>,
+    /// The proving keys of the permutation arguments, in the order of
+    /// `vk.cs.permutations()`.
+    permutations: Vec<permutation::ProvingKey<C>>,
     ev: Evaluator<C>,
 }
 
@@ -175,3 +253,869 @@ type ChallengeY<F> = ChallengeScalar<F, Y>;
 #[derive(Clone, Copy, Debug)]
 struct X;
 type ChallengeX<F> = ChallengeScalar<F, X>;
+
+#[cfg(test)]
+mod tests {
+    use pairing::bn256::{Bn256, Fr as Fp, G1Affine};
+
+    use group::{ff::Field, Curve};
+
+    use super::{
+        create_proof, create_proof_with_instance_commitments, keygen_pk, keygen_vk, prove_to_bytes,
+        verify_bytes, verify_proof, verify_with_challenges, Advice, Circuit, Column,
+        ConstraintSystem, Error, Instance, Selector, SingleVerifier, TableColumn,
+    };
+    use crate::{
+        circuit::{Layouter, SimpleFloorPlanner},
+        dev::test_rng,
+        poly::{commitment::Params, Rotation},
+        transcript::{
+            Blake2bRead, Blake2bWrite, Challenge255, EncodedChallenge, FixedChallenges, Transcript,
+            TranscriptRead, PROOF_VERSION,
+        },
+    };
+
+    #[derive(Clone)]
+    struct BooleanConfig {
+        a: Column<Advice>,
+        q: Selector,
+    }
+
+    /// A circuit constraining a single witness to be boolean, optionally with a
+    /// redundant second gate.
+    #[derive(Default)]
+    struct BooleanCircuit<const EXTRA_GATE: bool> {}
+
+    impl<const EXTRA_GATE: bool> Circuit<Fp> for BooleanCircuit<EXTRA_GATE> {
+        type Config = BooleanConfig;
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            Self::default()
+        }
+
+        fn configure(meta: &mut ConstraintSystem<Fp>) -> Self::Config {
+            let a = meta.advice_column();
+            let q = meta.selector();
+
+            meta.create_gate("boolean", |meta| {
+                let q = meta.query_selector(q);
+                let a = meta.query_advice(a, Rotation::cur());
+                vec![q * (a.clone() * a.clone() - a)]
+            });
+            if EXTRA_GATE {
+                meta.create_gate("extra", |meta| {
+                    let q = meta.query_selector(q);
+                    let a = meta.query_advice(a, Rotation::cur());
+                    vec![q * (a.clone() - a)]
+                });
+            }
+
+            BooleanConfig { a, q }
+        }
+
+        fn synthesize(
+            &self,
+            config: Self::Config,
+            mut layouter: impl Layouter<Fp>,
+        ) -> Result<(), Error> {
+            layouter.assign_region(
+                || "boolean",
+                |mut region| {
+                    config.q.enable(&mut region, 0)?;
+                    region.assign_advice(|| "a", config.a, 0, || Ok(Fp::from(1)))?;
+                    Ok(())
+                },
+            )
+        }
+    }
+
+    #[test]
+    fn vk_fingerprint() {
+        const K: u32 = 4;
+        let params: Params<G1Affine> = Params::<G1Affine>::unsafe_setup::<Bn256>(K);
+
+        let vk = keygen_vk(&params, &BooleanCircuit::<false>::default()).unwrap();
+        let vk_again = keygen_vk(&params, &BooleanCircuit::<false>::default()).unwrap();
+        assert_eq!(vk.fingerprint(), vk_again.fingerprint());
+
+        let vk_extra = keygen_vk(&params, &BooleanCircuit::<true>::default()).unwrap();
+        assert_ne!(vk.fingerprint(), vk_extra.fingerprint());
+    }
+
+    #[derive(Clone)]
+    struct PublicConfig {
+        a: Column<Advice>,
+        instance: Column<Instance>,
+    }
+
+    /// A circuit exposing a single witness as a public input.
+    #[derive(Default)]
+    struct PublicCircuit {
+        value: Option<Fp>,
+    }
+
+    impl Circuit<Fp> for PublicCircuit {
+        type Config = PublicConfig;
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            Self::default()
+        }
+
+        fn configure(meta: &mut ConstraintSystem<Fp>) -> Self::Config {
+            let a = meta.advice_column();
+            let instance = meta.instance_column();
+            meta.enable_equality(a);
+            meta.enable_equality(instance);
+
+            PublicConfig { a, instance }
+        }
+
+        fn synthesize(
+            &self,
+            config: Self::Config,
+            mut layouter: impl Layouter<Fp>,
+        ) -> Result<(), Error> {
+            let cell = layouter.assign_region(
+                || "public",
+                |mut region| {
+                    region.assign_advice(|| "a", config.a, 0, || self.value.ok_or(Error::Synthesis))
+                },
+            )?;
+            layouter.constrain_instance(cell.cell(), config.instance, 0)
+        }
+    }
+
+    #[test]
+    fn precomputed_instance_commitments() {
+        const K: u32 = 4;
+        let params: Params<G1Affine> = Params::<G1Affine>::unsafe_setup::<Bn256>(K);
+        let params_verifier = params.verifier::<Bn256>(1).unwrap();
+
+        let value = Fp::from(7);
+        let circuit = PublicCircuit { value: Some(value) };
+        let vk = keygen_vk(&params, &PublicCircuit::default()).unwrap();
+        let pk = keygen_pk(&params, vk, &PublicCircuit::default()).unwrap();
+
+        let mut instance_poly = pk.get_vk().get_domain().empty_lagrange();
+        instance_poly[0] = value;
+        let commitment = params.commit_lagrange(&instance_poly).to_affine();
+
+        let instances: &[&[&[Fp]]] = &[&[&[value]]];
+
+        let mut transcript = Blake2bWrite::<_, _, Challenge255<_>>::init(vec![]);
+        create_proof(
+            &params,
+            &pk,
+            &[PublicCircuit { value: Some(value) }],
+            instances,
+            test_rng(),
+            &mut transcript,
+        )
+        .unwrap();
+        let proof = transcript.finalize();
+
+        let mut transcript = Blake2bWrite::<_, _, Challenge255<_>>::init(vec![]);
+        create_proof_with_instance_commitments(
+            &params,
+            &pk,
+            &[circuit],
+            instances,
+            &[&[commitment]],
+            test_rng(),
+            &mut transcript,
+        )
+        .unwrap();
+        let precomputed_proof = transcript.finalize();
+
+        // With the same randomness, both paths must produce the same proof.
+        assert_eq!(proof, precomputed_proof);
+
+        for proof in [proof, precomputed_proof].iter() {
+            let strategy = SingleVerifier::new(&params_verifier);
+            let mut transcript = Blake2bRead::<_, _, Challenge255<_>>::init(&proof[..]);
+            assert!(verify_proof(
+                &params_verifier,
+                pk.get_vk(),
+                strategy,
+                instances,
+                &mut transcript,
+            )
+            .is_ok());
+        }
+    }
+
+    #[test]
+    fn keygen_is_witness_independent() {
+        const K: u32 = 4;
+        let params: Params<G1Affine> = Params::<G1Affine>::unsafe_setup::<Bn256>(K);
+        let params_verifier = params.verifier::<Bn256>(1).unwrap();
+
+        let value = Fp::from(3);
+        let circuit = PublicCircuit { value: Some(value) };
+
+        // Keys generated from the witness-free circuit match those generated from
+        // a circuit that happens to carry a witness.
+        let vk = keygen_vk(&params, &circuit.without_witnesses()).unwrap();
+        let vk_with_witness = keygen_vk(&params, &circuit).unwrap();
+        assert_eq!(vk.fingerprint(), vk_with_witness.fingerprint());
+
+        let pk = keygen_pk(&params, vk, &circuit.without_witnesses()).unwrap();
+
+        let instances: &[&[&[Fp]]] = &[&[&[value]]];
+        let mut transcript = Blake2bWrite::<_, _, Challenge255<_>>::init(vec![]);
+        create_proof(
+            &params,
+            &pk,
+            &[circuit],
+            instances,
+            test_rng(),
+            &mut transcript,
+        )
+        .unwrap();
+        let proof = transcript.finalize();
+
+        let strategy = SingleVerifier::new(&params_verifier);
+        let mut transcript = Blake2bRead::<_, _, Challenge255<_>>::init(&proof[..]);
+        assert!(verify_proof(
+            &params_verifier,
+            pk.get_vk(),
+            strategy,
+            instances,
+            &mut transcript,
+        )
+        .is_ok());
+    }
+
+    /// A circuit declaring a selector that no gate uses.
+    #[derive(Default)]
+    struct UnusedSelectorCircuit;
+
+    impl Circuit<Fp> for UnusedSelectorCircuit {
+        type Config = ();
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            Self
+        }
+
+        fn configure(meta: &mut ConstraintSystem<Fp>) -> Self::Config {
+            meta.selector();
+        }
+
+        fn synthesize(&self, _: Self::Config, _: impl Layouter<Fp>) -> Result<(), Error> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn keygen_rejects_unconstrained_selector() {
+        let params: Params<G1Affine> = Params::<G1Affine>::unsafe_setup::<Bn256>(4);
+        assert!(matches!(
+            keygen_vk(&params, &UnusedSelectorCircuit),
+            Err(Error::UnconstrainedSelector(0))
+        ));
+    }
+
+    #[derive(Clone)]
+    struct RangeConfig {
+        a: Column<Advice>,
+        q: Selector,
+        table: TableColumn,
+    }
+
+    /// A circuit looking up a single witness in a 3-bit range table.
+    #[derive(Default)]
+    struct RangeCircuit {
+        value: Option<Fp>,
+    }
+
+    impl Circuit<Fp> for RangeCircuit {
+        type Config = RangeConfig;
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            Self::default()
+        }
+
+        fn configure(meta: &mut ConstraintSystem<Fp>) -> Self::Config {
+            let a = meta.advice_column();
+            let q = meta.complex_selector();
+            let table = meta.lookup_table_column();
+
+            meta.lookup("range", |meta| {
+                let q = meta.query_selector(q);
+                let a = meta.query_advice(a, Rotation::cur());
+                vec![(q * a, table)]
+            });
+
+            RangeConfig { a, q, table }
+        }
+
+        fn synthesize(
+            &self,
+            config: Self::Config,
+            mut layouter: impl Layouter<Fp>,
+        ) -> Result<(), Error> {
+            layouter.assign_table(
+                || "range",
+                |mut table| {
+                    for i in 0..8 {
+                        table.assign_cell(
+                            || "value",
+                            config.table,
+                            i,
+                            || Ok(Fp::from(i as u64)),
+                        )?;
+                    }
+                    Ok(())
+                },
+            )?;
+            layouter.assign_region(
+                || "lookup",
+                |mut region| {
+                    config.q.enable(&mut region, 0)?;
+                    region.assign_advice(
+                        || "a",
+                        config.a,
+                        0,
+                        || self.value.ok_or(Error::Synthesis),
+                    )?;
+                    Ok(())
+                },
+            )
+        }
+    }
+
+    /// [`RangeCircuit`] with an unused fixed column allocated before the table, which
+    /// is removed when the circuit is shrunk.
+    #[derive(Default)]
+    struct PaddedRangeCircuit(RangeCircuit);
+
+    impl Circuit<Fp> for PaddedRangeCircuit {
+        type Config = RangeConfig;
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            Self::default()
+        }
+
+        fn configure(meta: &mut ConstraintSystem<Fp>) -> Self::Config {
+            meta.shrink_unused_columns();
+            meta.fixed_column();
+            RangeCircuit::configure(meta)
+        }
+
+        fn synthesize(
+            &self,
+            config: Self::Config,
+            layouter: impl Layouter<Fp>,
+        ) -> Result<(), Error> {
+            self.0.synthesize(config, layouter)
+        }
+    }
+
+    #[test]
+    fn shared_table_commitment() {
+        const K: u32 = 5;
+        let params: Params<G1Affine> = Params::<G1Affine>::unsafe_setup::<Bn256>(K);
+        let params_verifier = params.verifier::<Bn256>(0).unwrap();
+
+        // The table is committed to once, during key generation.
+        let vk = keygen_vk(&params, &PaddedRangeCircuit::default()).unwrap();
+        let pk = keygen_pk(&params, vk, &PaddedRangeCircuit::default()).unwrap();
+
+        // The table column is renumbered by shrinking, but is still looked up by
+        // the handle returned from `configure`.
+        let mut meta = ConstraintSystem::<Fp>::default();
+        let table = PaddedRangeCircuit::configure(&mut meta).table;
+        assert_eq!(table.inner().index(), 1);
+        let table_commitment = pk.get_vk().table_commitment(table).unwrap();
+
+        let mut meta = ConstraintSystem::<Fp>::default();
+        let unpadded_table = RangeCircuit::configure(&mut meta).table;
+        let unpadded_vk = keygen_vk(&params, &RangeCircuit::default()).unwrap();
+        assert_eq!(
+            unpadded_vk.table_commitment(unpadded_table),
+            Some(table_commitment)
+        );
+
+        for value in [2, 5].iter() {
+            let circuit = PaddedRangeCircuit(RangeCircuit {
+                value: Some(Fp::from(*value)),
+            });
+
+            let mut transcript = Blake2bWrite::<_, _, Challenge255<_>>::init(vec![]);
+            create_proof(
+                &params,
+                &pk,
+                &[circuit],
+                &[&[]],
+                test_rng(),
+                &mut transcript,
+            )
+            .unwrap();
+            let proof = transcript.finalize();
+
+            let strategy = SingleVerifier::new(&params_verifier);
+            let mut transcript = Blake2bRead::<_, _, Challenge255<_>>::init(&proof[..]);
+            assert!(verify_proof(
+                &params_verifier,
+                pk.get_vk(),
+                strategy,
+                &[&[]],
+                &mut transcript
+            )
+            .is_ok());
+        }
+    }
+
+    #[cfg(feature = "profile")]
+    #[test]
+    fn create_proof_profiled() {
+        const K: u32 = 5;
+        let params: Params<G1Affine> = Params::<G1Affine>::unsafe_setup::<Bn256>(K);
+
+        let vk = keygen_vk(&params, &RangeCircuit::default()).unwrap();
+        let pk = keygen_pk(&params, vk, &RangeCircuit::default()).unwrap();
+
+        let circuit = RangeCircuit {
+            value: Some(Fp::from(3)),
+        };
+        let mut transcript = Blake2bWrite::<_, _, Challenge255<_>>::init(vec![]);
+        let timings = super::create_proof_profiled(
+            &params,
+            &pk,
+            &[circuit],
+            &[&[]],
+            test_rng(),
+            &mut transcript,
+        )
+        .unwrap();
+
+        // The phases partition the whole proof creation.
+        let sum = timings.commit
+            + timings.fft
+            + timings.permutation
+            + timings.lookups
+            + timings.evaluation
+            + timings.multiopen;
+        let slack = std::time::Duration::from_micros(1);
+        assert!(sum <= timings.total + slack);
+        assert!(sum + slack >= timings.total);
+        assert!(timings.total > std::time::Duration::from_secs(0));
+    }
+
+    #[test]
+    fn params_from_seed() {
+        const K: u32 = 4;
+        let seed = [7u8; 32];
+        let params = Params::<G1Affine>::unsafe_setup_from_seed::<Bn256>(K, seed);
+        let params_again = Params::<G1Affine>::unsafe_setup_from_seed::<Bn256>(K, seed);
+        assert_eq!(params.g, params_again.g);
+        assert_eq!(params.g_lagrange, params_again.g_lagrange);
+        assert_eq!(params.additional_data, params_again.additional_data);
+
+        let other = Params::<G1Affine>::unsafe_setup_from_seed::<Bn256>(K, [8u8; 32]);
+        assert_ne!(params.g, other.g);
+
+        let params_verifier = params.verifier::<Bn256>(1).unwrap();
+        let value = Fp::from(5);
+        let vk = keygen_vk(&params, &PublicCircuit::default()).unwrap();
+        let pk = keygen_pk(&params, vk, &PublicCircuit::default()).unwrap();
+
+        let instances: &[&[&[Fp]]] = &[&[&[value]]];
+        let mut transcript = Blake2bWrite::<_, _, Challenge255<_>>::init(vec![]);
+        create_proof(
+            &params,
+            &pk,
+            &[PublicCircuit { value: Some(value) }],
+            instances,
+            test_rng(),
+            &mut transcript,
+        )
+        .unwrap();
+        let proof = transcript.finalize();
+
+        let strategy = SingleVerifier::new(&params_verifier);
+        let mut transcript = Blake2bRead::<_, _, Challenge255<_>>::init(&proof[..]);
+        assert!(verify_proof(
+            &params_verifier,
+            pk.get_vk(),
+            strategy,
+            instances,
+            &mut transcript,
+        )
+        .is_ok());
+    }
+
+    #[test]
+    fn proof_bytes_roundtrip() {
+        const K: u32 = 4;
+        let params: Params<G1Affine> = Params::<G1Affine>::unsafe_setup::<Bn256>(K);
+        let params_verifier = params.verifier::<Bn256>(1).unwrap();
+
+        let vk = keygen_vk(&params, &PublicCircuit::default()).unwrap();
+        let pk = keygen_pk(&params, vk, &PublicCircuit::default()).unwrap();
+
+        let value = Fp::from(7);
+        let instances: &[&[&[Fp]]] = &[&[&[value]]];
+        let mut proof = prove_to_bytes(
+            &params,
+            &pk,
+            &[PublicCircuit { value: Some(value) }],
+            instances,
+            test_rng(),
+        )
+        .unwrap();
+        assert!(verify_bytes(&params_verifier, pk.get_vk(), instances, &proof).is_ok());
+
+        // Corrupting any part of the proof causes verification to fail.
+        let last = proof.len() - 1;
+        proof[last] ^= 1;
+        assert!(verify_bytes(&params_verifier, pk.get_vk(), instances, &proof).is_err());
+    }
+
+    #[test]
+    fn unsupported_proof_version() {
+        const K: u32 = 4;
+        let params: Params<G1Affine> = Params::<G1Affine>::unsafe_setup::<Bn256>(K);
+        let params_verifier = params.verifier::<Bn256>(1).unwrap();
+
+        let vk = keygen_vk(&params, &PublicCircuit::default()).unwrap();
+        let pk = keygen_pk(&params, vk, &PublicCircuit::default()).unwrap();
+
+        let value = Fp::from(7);
+        let instances: &[&[&[Fp]]] = &[&[&[value]]];
+        let mut proof = prove_to_bytes(
+            &params,
+            &pk,
+            &[PublicCircuit { value: Some(value) }],
+            instances,
+            test_rng(),
+        )
+        .unwrap();
+
+        // The BLAKE2b transcript writes the version as the first byte of the proof.
+        proof[0] = PROOF_VERSION + 1;
+        assert!(matches!(
+            verify_bytes(&params_verifier, pk.get_vk(), instances, &proof),
+            Err(Error::UnsupportedProofVersion(version)) if version == PROOF_VERSION + 1
+        ));
+    }
+
+    #[cfg(feature = "tokio")]
+    #[test]
+    fn verify_proof_async() {
+        use std::sync::Arc;
+
+        const K: u32 = 4;
+        let params: Params<G1Affine> = Params::<G1Affine>::unsafe_setup::<Bn256>(K);
+        let params_verifier = Arc::new(params.verifier::<Bn256>(1).unwrap());
+
+        let vk = keygen_vk(&params, &PublicCircuit::default()).unwrap();
+        let pk = keygen_pk(&params, vk, &PublicCircuit::default()).unwrap();
+        // The verifier task needs its own key, so copy it out of the proving key.
+        let mut vk_bytes = vec![];
+        pk.get_vk().write(&mut vk_bytes).unwrap();
+        let vk = Arc::new(
+            super::VerifyingKey::read::<_, PublicCircuit>(&mut &vk_bytes[..], &params).unwrap(),
+        );
+
+        let value = Fp::from(7);
+        let mut proof = prove_to_bytes(
+            &params,
+            &pk,
+            &[PublicCircuit { value: Some(value) }],
+            &[&[&[value]]],
+            test_rng(),
+        )
+        .unwrap();
+        let instances = vec![vec![vec![value]]];
+
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .build()
+            .unwrap();
+        assert!(runtime
+            .block_on(super::verify_proof_async(
+                params_verifier.clone(),
+                vk.clone(),
+                proof.clone(),
+                instances.clone()
+            ))
+            .is_ok());
+
+        let last = proof.len() - 1;
+        proof[last] ^= 1;
+        assert!(runtime
+            .block_on(super::verify_proof_async(
+                params_verifier,
+                vk,
+                proof,
+                instances
+            ))
+            .is_err());
+    }
+
+    #[derive(Clone)]
+    struct DeadColumnConfig {
+        dead: Column<Advice>,
+        a: Column<Advice>,
+        q: Selector,
+    }
+
+    /// A boolean circuit with an advice column that is assigned to but never used.
+    #[derive(Default)]
+    struct DeadColumnCircuit {}
+
+    impl Circuit<Fp> for DeadColumnCircuit {
+        type Config = DeadColumnConfig;
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            Self::default()
+        }
+
+        fn configure(meta: &mut ConstraintSystem<Fp>) -> Self::Config {
+            meta.shrink_unused_columns();
+            // Allocate the dead column first, so that shrinking renumbers `a`.
+            let dead = meta.advice_column();
+            let a = meta.advice_column();
+            let q = meta.selector();
+
+            meta.create_gate("boolean", |meta| {
+                let q = meta.query_selector(q);
+                let a = meta.query_advice(a, Rotation::cur());
+                vec![q * (a.clone() * a.clone() - a)]
+            });
+
+            DeadColumnConfig { dead, a, q }
+        }
+
+        fn synthesize(
+            &self,
+            config: Self::Config,
+            mut layouter: impl Layouter<Fp>,
+        ) -> Result<(), Error> {
+            layouter.assign_region(
+                || "boolean",
+                |mut region| {
+                    config.q.enable(&mut region, 0)?;
+                    region.assign_advice(|| "a", config.a, 0, || Ok(Fp::from(1)))?;
+                    region.assign_advice(|| "dead", config.dead, 0, || Ok(Fp::from(5)))?;
+                    Ok(())
+                },
+            )
+        }
+    }
+
+    #[test]
+    fn shrink_dead_column() {
+        const K: u32 = 4;
+        let params: Params<G1Affine> = Params::<G1Affine>::unsafe_setup::<Bn256>(K);
+        let params_verifier = params.verifier::<Bn256>(0).unwrap();
+
+        // Circuits that have not opted in keep their unused columns.
+        let mut cs = ConstraintSystem::<Fp>::default();
+        cs.advice_column();
+        let (cs, _) = cs.shrink();
+        assert_eq!(cs.num_advice_columns(), 1);
+
+        let mut cs = ConstraintSystem::default();
+        DeadColumnCircuit::configure(&mut cs);
+        assert_eq!(cs.num_advice_columns(), 2);
+        let (cs, _) = cs.shrink();
+        assert_eq!(cs.num_advice_columns(), 1);
+        assert_eq!(cs.advice_queries[0].0.index(), 0);
+
+        let vk = keygen_vk(&params, &DeadColumnCircuit::default()).unwrap();
+        assert_eq!(vk.cs.num_advice_columns(), 1);
+        let pk = keygen_pk(&params, vk, &DeadColumnCircuit::default()).unwrap();
+
+        let instances: &[&[&[Fp]]] = &[&[]];
+        let proof = prove_to_bytes(
+            &params,
+            &pk,
+            &[DeadColumnCircuit::default()],
+            instances,
+            test_rng(),
+        )
+        .unwrap();
+        assert!(verify_bytes(&params_verifier, pk.get_vk(), instances, &proof).is_ok());
+    }
+
+    #[test]
+    fn instance_commitment_binding() {
+        const K: u32 = 4;
+        let params: Params<G1Affine> = Params::<G1Affine>::unsafe_setup::<Bn256>(K);
+        let params_verifier = params.verifier::<Bn256>(1).unwrap();
+
+        let vk = keygen_vk(&params, &PublicCircuit::default()).unwrap();
+        let pk = keygen_pk(&params, vk, &PublicCircuit::default()).unwrap();
+
+        let (value, other) = (Fp::from(7), Fp::from(8));
+        let instances: &[&[&[Fp]]] = &[&[&[value]]];
+        let other_instances: &[&[&[Fp]]] = &[&[&[other]]];
+        let proof = prove_to_bytes(
+            &params,
+            &pk,
+            &[PublicCircuit { value: Some(value) }],
+            instances,
+            test_rng(),
+        )
+        .unwrap();
+
+        assert!(verify_bytes(&params_verifier, pk.get_vk(), instances, &proof).is_ok());
+        assert!(verify_bytes(&params_verifier, pk.get_vk(), other_instances, &proof).is_err());
+
+        // The verifier absorbs its own commitment to the instance before reading the
+        // proof, so every challenge it derives depends on the public inputs.
+        let theta = |instance: Fp| {
+            let mut transcript = Blake2bRead::<_, _, Challenge255<_>>::init(&proof[..]);
+            transcript.read_version().unwrap();
+            transcript.read_scalar().unwrap();
+            transcript
+                .common_point(params_verifier.commit_lagrange(vec![instance]).to_affine())
+                .unwrap();
+            for _ in 0..pk.get_vk().cs.num_advice_columns() {
+                transcript.read_point().unwrap();
+            }
+            transcript.squeeze_challenge().get_scalar()
+        };
+        assert_ne!(theta(value), theta(other));
+    }
+
+    #[test]
+    fn fixed_challenges() {
+        const K: u32 = 4;
+        let params: Params<G1Affine> = Params::<G1Affine>::unsafe_setup::<Bn256>(K);
+        let params_verifier = params.verifier::<Bn256>(1).unwrap();
+
+        let vk = keygen_vk(&params, &PublicCircuit::default()).unwrap();
+        let pk = keygen_pk(&params, vk, &PublicCircuit::default()).unwrap();
+
+        let mut rng = test_rng();
+        let mut random_challenges = || (0..16).map(|_| Fp::random(&mut rng)).collect::<Vec<_>>();
+        let challenges = random_challenges();
+        let other_challenges = random_challenges();
+
+        let value = Fp::from(7);
+        let instances: &[&[&[Fp]]] = &[&[&[value]]];
+        let mut transcript = FixedChallenges::new(
+            Blake2bWrite::<_, _, Challenge255<_>>::init(vec![]),
+            &challenges,
+        );
+        create_proof(
+            &params,
+            &pk,
+            &[PublicCircuit { value: Some(value) }],
+            instances,
+            test_rng(),
+            &mut transcript,
+        )
+        .unwrap();
+        let proof = transcript.into_inner().finalize();
+
+        let verify = |challenges: &[Fp]| {
+            verify_with_challenges(
+                &params_verifier,
+                pk.get_vk(),
+                SingleVerifier::new(&params_verifier),
+                instances,
+                Blake2bRead::<_, _, Challenge255<_>>::init(&proof[..]),
+                challenges,
+            )
+        };
+        assert!(verify(&challenges).is_ok());
+
+        // The proof only holds for the challenges it was created with, and not for
+        // those squeezed from the transcript.
+        assert!(verify(&other_challenges).is_err());
+        assert!(verify_bytes(&params_verifier, pk.get_vk(), instances, &proof).is_err());
+    }
+
+    #[test]
+    fn downsized_params() {
+        let seed = [7u8; 32];
+        let mut params = Params::<G1Affine>::unsafe_setup_from_seed::<Bn256>(8, seed);
+        assert!(matches!(
+            params.downsize(9),
+            Err(Error::NotEnoughRowsAvailable { current_k: 8 })
+        ));
+
+        // Downsizing gives the same parameters as generating them at the smaller size.
+        params.downsize(4).unwrap();
+        let direct = Params::<G1Affine>::unsafe_setup_from_seed::<Bn256>(4, seed);
+        assert_eq!((params.k, params.n), (direct.k, direct.n));
+        assert_eq!(params.g, direct.g);
+        assert_eq!(params.g_lagrange, direct.g_lagrange);
+        assert_eq!(params.additional_data, direct.additional_data);
+
+        let params_verifier = params.verifier::<Bn256>(1).unwrap();
+        let vk = keygen_vk(&params, &PublicCircuit::default()).unwrap();
+        let pk = keygen_pk(&params, vk, &PublicCircuit::default()).unwrap();
+
+        let value = Fp::from(7);
+        let instances: &[&[&[Fp]]] = &[&[&[value]]];
+        let proof = prove_to_bytes(
+            &params,
+            &pk,
+            &[PublicCircuit { value: Some(value) }],
+            instances,
+            test_rng(),
+        )
+        .unwrap();
+        assert!(verify_bytes(&params_verifier, pk.get_vk(), instances, &proof).is_ok());
+    }
+
+    #[test]
+    fn circuit_mismatch() {
+        let params = Params::<G1Affine>::unsafe_setup::<Bn256>(4);
+        let params_verifier = params.verifier::<Bn256>(1).unwrap();
+        let vk = keygen_vk(&params, &PublicCircuit::default()).unwrap();
+        let pk = keygen_pk(&params, vk, &PublicCircuit::default()).unwrap();
+
+        let value = Fp::from(7);
+        let instances: &[&[&[Fp]]] = &[&[&[value]]];
+        let proof = prove_to_bytes(
+            &params,
+            &pk,
+            &[PublicCircuit { value: Some(value) }],
+            instances,
+            test_rng(),
+        )
+        .unwrap();
+
+        // A key for the same circuit at a different size is rejected up front.
+        let larger_params = Params::<G1Affine>::unsafe_setup::<Bn256>(5);
+        let larger_vk = keygen_vk(&larger_params, &PublicCircuit::default()).unwrap();
+        assert!(matches!(
+            verify_bytes(&params_verifier, &larger_vk, instances, &proof),
+            Err(Error::CircuitMismatch {
+                field: "k",
+                expected: 5,
+                got: 4,
+            })
+        ));
+
+        // So is a key for a structurally different circuit of the same size.
+        let vk = keygen_vk(&params, &BooleanCircuit::<false>::default()).unwrap();
+        let pk = keygen_pk(&params, vk, &BooleanCircuit::<false>::default()).unwrap();
+        let other_vk = keygen_vk(&params, &BooleanCircuit::<true>::default()).unwrap();
+        let instances: &[&[&[Fp]]] = &[&[]];
+        let proof = prove_to_bytes(
+            &params,
+            &pk,
+            &[BooleanCircuit::<false>::default()],
+            instances,
+            test_rng(),
+        )
+        .unwrap();
+        assert!(verify_bytes(&params_verifier, pk.get_vk(), instances, &proof).is_ok());
+        assert!(matches!(
+            verify_bytes(&params_verifier, &other_vk, instances, &proof),
+            Err(Error::VerifyingKeyMismatch)
+        ));
+    }
+}
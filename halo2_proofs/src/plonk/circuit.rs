@@ -3,6 +3,7 @@ use core::ops::{Add, Mul};
 use ff::Field;
 use std::{
     convert::TryFrom,
+    iter,
     ops::{Neg, Sub},
 };
 
@@ -332,6 +333,15 @@ pub trait Assignment<F: Field> {
     /// Returns the cell's value, if known.
     fn query_instance(&self, column: Column<Instance>, row: usize) -> Result<Option<F>, Error>;
 
+    /// Returns whether this assignment evaluates the values passed to
+    /// [`Assignment::assign_advice`]. Layouters that would otherwise evaluate those
+    /// values ahead of time skip them when this is `false`.
+    ///
+    /// Defaults to `true`.
+    fn evaluates_witness(&self) -> bool {
+        true
+    }
+
     /// Assign an advice column value (witness)
     fn assign_advice<V, VR, A, AR>(
         &mut self,
@@ -442,6 +452,95 @@ pub trait Circuit<F: Field> {
     fn synthesize(&self, config: Self::Config, layouter: impl Layouter<F>) -> Result<(), Error>;
 }
 
+/// The configuration of a [`WrappedCircuit`].
+#[derive(Clone, Debug)]
+pub struct WrappedConfig<Config> {
+    /// The configuration of the wrapped circuit.
+    pub inner: Config,
+    /// The instance column holding the wrapper's extra public inputs.
+    pub instance: Column<Instance>,
+    /// The advice column the extra public inputs are witnessed in.
+    pub advice: Column<Advice>,
+}
+
+/// A [`Circuit`] that wraps another circuit and exposes extra public inputs on top
+/// of the ones of the wrapped circuit, such as the accumulator of a recursive
+/// verifier.
+///
+/// The wrapped circuit is configured and synthesized unchanged. The extra public
+/// inputs are then witnessed and constrained to rows `0..extra.len()` of an
+/// additional instance column, which comes after the instance columns of the
+/// wrapped circuit.
+#[derive(Clone, Debug)]
+pub struct WrappedCircuit<F: Field, C: Circuit<F>> {
+    /// The wrapped circuit.
+    pub inner: C,
+    /// The values of the extra public inputs.
+    pub extra: Vec<Option<F>>,
+}
+
+impl<F: Field, C: Circuit<F>> Circuit<F> for WrappedCircuit<F, C> {
+    type Config = WrappedConfig<C::Config>;
+    type FloorPlanner = C::FloorPlanner;
+
+    fn without_witnesses(&self) -> Self {
+        WrappedCircuit {
+            inner: self.inner.without_witnesses(),
+            extra: vec![None; self.extra.len()],
+        }
+    }
+
+    fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+        let inner = C::configure(meta);
+
+        let instance = meta.instance_column();
+        let advice = meta.advice_column();
+        meta.enable_equality(instance);
+        meta.enable_equality(advice);
+
+        WrappedConfig {
+            inner,
+            instance,
+            advice,
+        }
+    }
+
+    fn synthesize(
+        &self,
+        config: Self::Config,
+        mut layouter: impl Layouter<F>,
+    ) -> Result<(), Error> {
+        self.inner
+            .synthesize(config.inner, layouter.namespace(|| "inner"))?;
+
+        let cells = layouter.assign_region(
+            || "extra public inputs",
+            |mut region| {
+                self.extra
+                    .iter()
+                    .enumerate()
+                    .map(|(row, value)| {
+                        region
+                            .assign_advice(
+                                || format!("extra public input {}", row),
+                                config.advice,
+                                row,
+                                || value.ok_or(Error::Synthesis),
+                            )
+                            .map(|cell| cell.cell())
+                    })
+                    .collect::<Result<Vec<_>, _>>()
+            },
+        )?;
+
+        for (row, cell) in cells.into_iter().enumerate() {
+            layouter.constrain_instance(cell, config.instance, row)?;
+        }
+
+        Ok(())
+    }
+}
+
 /// Low-degree expression representing an identity that must hold over the committed columns.
 #[derive(Clone, Debug)]
 pub enum Expression<F> {
@@ -817,6 +916,22 @@ impl<F: Field> Expression<F> {
         )
     }
 
+    /// Returns whether or not this expression queries the selector with the given
+    /// index.
+    fn contains_selector(&self, index: usize) -> bool {
+        self.evaluate(
+            &|_| false,
+            &|selector| selector.0 == index,
+            &|_, _, _| false,
+            &|_, _, _| false,
+            &|_, _, _| false,
+            &|a| a,
+            &|a, b| a || b,
+            &|a, b| a || b,
+            &|a, _| a,
+        )
+    }
+
     /// Extracts a simple selector from this gate, if present
     fn extract_simple_selector(&self) -> Option<Selector> {
         let op = |a, b| match (a, b) {
@@ -971,6 +1086,10 @@ impl<F: Field> Gate<F> {
     }
 }
 
+/// The default bound on the absolute rotation that a gate may query, enforced by
+/// [`ConstraintSystem::check_rotations`].
+pub const DEFAULT_ROTATION_BOUND: u32 = 256;
+
 /// This is a description of the circuit environment, such as the gate, column and
 /// permutation arrangements.
 #[derive(Debug, Clone)]
@@ -992,6 +1111,10 @@ pub struct ConstraintSystem<F: Field> {
     // Permutation argument for performing equality constraints
     pub(crate) permutation: permutation::Argument,
 
+    // The independent permutation arguments registered with
+    // `ConstraintSystem::permutation`, each with its own columns.
+    pub(crate) independent_permutations: Vec<permutation::Argument>,
+
     // Vector of lookup arguments, where each corresponds to a sequence of
     // input expressions and a sequence of table expressions involved in the lookup.
     pub(crate) lookups: Vec<lookup::Argument<F>>,
@@ -1000,12 +1123,112 @@ pub struct ConstraintSystem<F: Field> {
     // that are copied into advice columns.
     pub(crate) constants: Vec<Column<Fixed>>,
 
+    // The fixed columns whose unassigned cells are filled with a default value.
+    fixed_defaults: Vec<(Column<Fixed>, F)>,
+
     pub(crate) minimum_degree: Option<usize>,
+
+    // The largest absolute rotation a gate may query; see
+    // `ConstraintSystem::check_rotations`.
+    rotation_bound: u32,
+
+    // Whether `ConstraintSystem::shrink` removes unused columns; see
+    // `ConstraintSystem::shrink_unused_columns`.
+    shrink_unused_columns: bool,
+}
+
+/// The advice and fixed columns that remain after [`ConstraintSystem::shrink`],
+/// given by their indices before shrinking, in their new order.
+#[derive(Clone, Debug)]
+pub(crate) struct RetainedColumns {
+    advice: Vec<usize>,
+    fixed: Vec<usize>,
+}
+
+impl RetainedColumns {
+    /// Returns the index after shrinking of the fixed column with the given
+    /// original index, or `None` if the column was removed.
+    pub(crate) fn fixed_index(&self, index: usize) -> Option<usize> {
+        self.fixed.iter().position(|retained| *retained == index)
+    }
+
+    /// Selects the retained advice columns from all of the circuit's advice columns.
+    pub(crate) fn select_advice<T>(&self, columns: Vec<T>) -> Vec<T> {
+        select_columns(&self.advice, columns)
+    }
+
+    /// Selects the retained fixed columns from all of the circuit's fixed columns.
+    pub(crate) fn select_fixed<T>(&self, columns: Vec<T>) -> Vec<T> {
+        select_columns(&self.fixed, columns)
+    }
+}
+
+fn select_columns<T>(indices: &[usize], columns: Vec<T>) -> Vec<T> {
+    let mut columns: Vec<_> = columns.into_iter().map(Some).collect();
+    indices
+        .iter()
+        .map(|index| columns[*index].take().unwrap())
+        .collect()
+}
+
+/// The offsets by which [`ConstraintSystem::merge`] renumbers the columns and selectors
+/// of the merged system. Passing the columns of a configuration made against that
+/// system through these methods gives the columns it occupies in the merged system.
+#[derive(Clone, Copy, Debug)]
+pub struct MergedColumns {
+    advice: usize,
+    fixed: usize,
+    instance: usize,
+    selectors: usize,
+}
+
+impl MergedColumns {
+    fn shift<C: ColumnType>(column: Column<C>, offset: usize) -> Column<C> {
+        Column {
+            index: column.index + offset,
+            column_type: column.column_type,
+        }
+    }
+
+    /// Returns the merged index of an advice column.
+    pub fn advice(&self, column: Column<Advice>) -> Column<Advice> {
+        Self::shift(column, self.advice)
+    }
+
+    /// Returns the merged index of a fixed column.
+    pub fn fixed(&self, column: Column<Fixed>) -> Column<Fixed> {
+        Self::shift(column, self.fixed)
+    }
+
+    /// Returns the merged index of an instance column.
+    pub fn instance(&self, column: Column<Instance>) -> Column<Instance> {
+        Self::shift(column, self.instance)
+    }
+
+    /// Returns the merged index of a column of any type.
+    pub fn any(&self, column: Column<Any>) -> Column<Any> {
+        let offset = match column.column_type {
+            Any::Advice => self.advice,
+            Any::Fixed => self.fixed,
+            Any::Instance => self.instance,
+        };
+        Self::shift(column, offset)
+    }
+
+    /// Returns the merged index of a lookup table column.
+    pub fn table_column(&self, column: TableColumn) -> TableColumn {
+        TableColumn {
+            inner: self.fixed(column.inner),
+        }
+    }
+
+    /// Returns the merged index of a selector.
+    pub fn selector(&self, selector: Selector) -> Selector {
+        Selector(selector.0 + self.selectors, selector.1)
+    }
 }
 
 /// Represents the minimal parameters that determine a `ConstraintSystem`.
-#[allow(dead_code)]
-#[derive(Debug)]
 pub struct PinnedConstraintSystem<'a, F: Field> {
     num_fixed_columns: &'a usize,
     num_advice_columns: &'a usize,
@@ -1017,11 +1240,39 @@ pub struct PinnedConstraintSystem<'a, F: Field> {
     instance_queries: &'a Vec<(Column<Instance>, Rotation)>,
     fixed_queries: &'a Vec<(Column<Fixed>, Rotation)>,
     permutation: &'a permutation::Argument,
+    independent_permutations: &'a Vec<permutation::Argument>,
     lookups: &'a Vec<lookup::Argument<F>>,
     constants: &'a Vec<Column<Fixed>>,
     minimum_degree: &'a Option<usize>,
 }
 
+impl<'a, F: Field> std::fmt::Debug for PinnedConstraintSystem<'a, F> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> Result<(), std::fmt::Error> {
+        let mut debug_struct = f.debug_struct("PinnedConstraintSystem");
+        debug_struct
+            .field("num_fixed_columns", self.num_fixed_columns)
+            .field("num_advice_columns", self.num_advice_columns)
+            .field("num_instance_columns", self.num_instance_columns)
+            .field("num_selectors", self.num_selectors)
+            .field("selector_map", &self.selector_map)
+            .field("gates", &self.gates)
+            .field("advice_queries", self.advice_queries)
+            .field("instance_queries", self.instance_queries)
+            .field("fixed_queries", self.fixed_queries)
+            .field("permutation", self.permutation);
+        // Only circuits with independent permutations pin them, so that the keys of
+        // other circuits are unchanged.
+        if !self.independent_permutations.is_empty() {
+            debug_struct.field("independent_permutations", self.independent_permutations);
+        }
+        debug_struct
+            .field("lookups", self.lookups)
+            .field("constants", self.constants)
+            .field("minimum_degree", self.minimum_degree)
+            .finish()
+    }
+}
+
 struct PinnedGates<'a, F: Field>(&'a Vec<Gate<F>>);
 
 impl<'a, F: Field> std::fmt::Debug for PinnedGates<'a, F> {
@@ -1046,9 +1297,13 @@ impl<F: Field> Default for ConstraintSystem<F> {
             num_advice_queries: Vec::new(),
             instance_queries: Vec::new(),
             permutation: permutation::Argument::new(),
+            independent_permutations: Vec::new(),
             lookups: Vec::new(),
             constants: vec![],
+            fixed_defaults: Vec::new(),
             minimum_degree: None,
+            rotation_bound: DEFAULT_ROTATION_BOUND,
+            shrink_unused_columns: false,
         }
     }
 }
@@ -1069,6 +1324,7 @@ impl<F: Field> ConstraintSystem<F> {
             advice_queries: &self.advice_queries,
             instance_queries: &self.instance_queries,
             permutation: &self.permutation,
+            independent_permutations: &self.independent_permutations,
             lookups: &self.lookups,
             constants: &self.constants,
             minimum_degree: &self.minimum_degree,
@@ -1088,12 +1344,68 @@ impl<F: Field> ConstraintSystem<F> {
     }
 
     /// Enable the ability to enforce equality over cells in this column
+    ///
+    /// # Panics
+    ///
+    /// Panics if the column is part of an independent permutation registered with
+    /// [`ConstraintSystem::permutation`].
     pub fn enable_equality<C: Into<Column<Any>>>(&mut self, column: C) {
         let column = column.into();
+        self.assert_not_independent(column);
         self.query_any_index(column, Rotation::cur());
         self.permutation.add_column(column);
     }
 
+    /// Enables equality over the cells of `columns` as an independent permutation,
+    /// and returns the index of that permutation.
+    ///
+    /// Cells of these columns can only be constrained equal to each other, so that
+    /// the copies of separate sub-circuits cannot be joined by mistake; a copy to
+    /// any other column fails with [`Error::CrossPermutationCopy`]. Each independent
+    /// permutation is a separate permutation argument, with its own commitments in
+    /// the verifying key and its own grand products in proofs. Columns enabled with
+    /// [`ConstraintSystem::enable_equality`], including the columns enabled with
+    /// [`ConstraintSystem::enable_constant`], remain in a permutation of their own.
+    ///
+    /// # Panics
+    ///
+    /// Panics if one of the columns is already equality-enabled, or part of another
+    /// permutation.
+    pub fn permutation(&mut self, columns: &[Column<Any>]) -> usize {
+        let mut argument = permutation::Argument::new();
+        for column in columns {
+            assert!(
+                !self.permutation.columns.contains(column),
+                "column {:?} is already equality-enabled",
+                column
+            );
+            self.assert_not_independent(*column);
+            self.query_any_index(*column, Rotation::cur());
+            argument.add_column(*column);
+        }
+        self.independent_permutations.push(argument);
+        self.independent_permutations.len() - 1
+    }
+
+    fn assert_not_independent(&self, column: Column<Any>) {
+        if let Some(index) = self
+            .independent_permutations
+            .iter()
+            .position(|argument| argument.columns.contains(&column))
+        {
+            panic!(
+                "column {:?} is already part of permutation {}",
+                column, index
+            );
+        }
+    }
+
+    /// Returns the permutation arguments of this constraint system: the argument
+    /// over the equality-enabled columns, followed by the independent permutations.
+    pub(crate) fn permutations(&self) -> impl Iterator<Item = &permutation::Argument> + Clone {
+        iter::once(&self.permutation).chain(self.independent_permutations.iter())
+    }
+
     /// Add a lookup argument for some input expressions and table columns.
     ///
     /// `table_map` returns a map between input expressions and the table columns
@@ -1415,6 +1727,308 @@ impl<F: Field> ConstraintSystem<F> {
         (self, polys)
     }
 
+    /// Opts this circuit in to removing its unused advice and fixed columns during
+    /// key generation and proving, so that they do not cost a polynomial
+    /// commitment each.
+    ///
+    /// Shrinking renumbers the remaining columns, so the indices of the column
+    /// handles returned during `Circuit::configure` no longer match the columns of
+    /// the [`VerifyingKey`]; use accessors such as
+    /// [`VerifyingKey::table_commitment`], which translate the original indices.
+    /// It also changes the verifying key and the shape of the proof. The
+    /// [`MockProver`] checks the circuit against its original columns.
+    ///
+    /// [`VerifyingKey`]: crate::plonk::VerifyingKey
+    /// [`VerifyingKey::table_commitment`]: crate::plonk::VerifyingKey::table_commitment
+    /// [`MockProver`]: crate::dev::MockProver
+    pub fn shrink_unused_columns(&mut self) {
+        self.shrink_unused_columns = true;
+    }
+
+    /// Removes the advice and fixed columns that are neither queried by a gate or
+    /// lookup nor part of the permutation argument, and renumbers the remaining
+    /// columns so that their indices stay contiguous. All queries, expressions and
+    /// equality-enabled columns are updated to the new indices. Unless the circuit
+    /// has opted in with [`ConstraintSystem::shrink_unused_columns`], every column
+    /// is retained and the constraint system is returned unchanged.
+    ///
+    /// Keygen and the prover run this pass once the circuit has been synthesized,
+    /// before the selectors are compressed. Circuits are synthesized against the
+    /// original column indices; the returned [`RetainedColumns`] selects the
+    /// assignments of the remaining columns.
+    pub(crate) fn shrink(mut self) -> (Self, RetainedColumns) {
+        if !self.shrink_unused_columns {
+            let retained = RetainedColumns {
+                advice: (0..self.num_advice_columns).collect(),
+                fixed: (0..self.num_fixed_columns).collect(),
+            };
+            return (self, retained);
+        }
+
+        let mut used_advice = vec![false; self.num_advice_columns];
+        let mut used_fixed = vec![false; self.num_fixed_columns];
+        for (column, _) in &self.advice_queries {
+            used_advice[column.index] = true;
+        }
+        for (column, _) in &self.fixed_queries {
+            used_fixed[column.index] = true;
+        }
+        for column in self
+            .permutations()
+            .flat_map(|argument| argument.columns.iter())
+        {
+            match column.column_type {
+                Any::Advice => used_advice[column.index] = true,
+                Any::Fixed => used_fixed[column.index] = true,
+                Any::Instance => (),
+            }
+        }
+
+        // Maps each original column index to its new index, if the column is kept.
+        fn remap(used: &[bool]) -> (Vec<Option<usize>>, Vec<usize>) {
+            let mut retained = vec![];
+            let map = used
+                .iter()
+                .enumerate()
+                .map(|(index, used)| {
+                    if *used {
+                        retained.push(index);
+                        Some(retained.len() - 1)
+                    } else {
+                        None
+                    }
+                })
+                .collect();
+            (map, retained)
+        }
+        let (advice_map, advice) = remap(&used_advice);
+        let (fixed_map, fixed) = remap(&used_fixed);
+
+        // Every column that is referenced anywhere was marked as used above.
+        let remap_any = |column: &mut Column<Any>| match column.column_type {
+            Any::Advice => column.index = advice_map[column.index].unwrap(),
+            Any::Fixed => column.index = fixed_map[column.index].unwrap(),
+            Any::Instance => (),
+        };
+
+        for (column, _) in self.advice_queries.iter_mut() {
+            column.index = advice_map[column.index].unwrap();
+        }
+        for (column, _) in self.fixed_queries.iter_mut() {
+            column.index = fixed_map[column.index].unwrap();
+        }
+        for column in self
+            .constants
+            .iter_mut()
+            .chain(self.selector_map.iter_mut())
+        {
+            column.index = fixed_map[column.index].unwrap();
+        }
+        for column in iter::once(&mut self.permutation)
+            .chain(self.independent_permutations.iter_mut())
+            .flat_map(|argument| argument.columns.iter_mut())
+        {
+            remap_any(column);
+        }
+        self.fixed_defaults = self
+            .fixed_defaults
+            .into_iter()
+            .filter_map(|(column, default)| {
+                fixed_map[column.index].map(|index| {
+                    (
+                        Column {
+                            index,
+                            column_type: Fixed,
+                        },
+                        default,
+                    )
+                })
+            })
+            .collect();
+        for cell in self
+            .gates
+            .iter_mut()
+            .flat_map(|gate| gate.queried_cells.iter_mut())
+        {
+            remap_any(&mut cell.column);
+        }
+
+        for expr in self
+            .gates
+            .iter_mut()
+            .flat_map(|gate| gate.polys.iter_mut())
+            .chain(self.lookups.iter_mut().flat_map(|lookup| {
+                lookup
+                    .input_expressions
+                    .iter_mut()
+                    .chain(lookup.table_expressions.iter_mut())
+            }))
+        {
+            *expr = expr.evaluate(
+                &|constant| Expression::Constant(constant),
+                &|selector| Expression::Selector(selector),
+                &|query_index, column_index, rotation| Expression::Fixed {
+                    query_index,
+                    column_index: fixed_map[column_index].unwrap(),
+                    rotation,
+                },
+                &|query_index, column_index, rotation| Expression::Advice {
+                    query_index,
+                    column_index: advice_map[column_index].unwrap(),
+                    rotation,
+                },
+                &|query_index, column_index, rotation| Expression::Instance {
+                    query_index,
+                    column_index,
+                    rotation,
+                },
+                &|a| -a,
+                &|a, b| a + b,
+                &|a, b| a * b,
+                &|a, f| a * f,
+            );
+        }
+
+        self.num_advice_queries = advice
+            .iter()
+            .map(|index| self.num_advice_queries[*index])
+            .collect();
+        self.num_advice_columns = advice.len();
+        self.num_fixed_columns = fixed.len();
+
+        (self, RetainedColumns { advice, fixed })
+    }
+
+    /// Appends the columns, selectors, gates, lookups and equality-enabled columns of
+    /// `other` to this constraint system, renumbering them after the ones already
+    /// allocated here.
+    ///
+    /// This lets chips be configured against their own constraint systems and combined
+    /// into one circuit. The returned [`MergedColumns`] maps the columns and selectors
+    /// of `other` to their indices in the merged system.
+    pub fn merge(&mut self, other: ConstraintSystem<F>) -> MergedColumns {
+        let map = MergedColumns {
+            advice: self.num_advice_columns,
+            fixed: self.num_fixed_columns,
+            instance: self.num_instance_columns,
+            selectors: self.num_selectors,
+        };
+        let fixed_queries = self.fixed_queries.len();
+        let advice_queries = self.advice_queries.len();
+        let instance_queries = self.instance_queries.len();
+
+        let merge_expr = |expr: &Expression<F>| {
+            expr.evaluate(
+                &|constant| Expression::Constant(constant),
+                &|selector| Expression::Selector(map.selector(selector)),
+                &|query_index, column_index, rotation| Expression::Fixed {
+                    query_index: query_index + fixed_queries,
+                    column_index: column_index + map.fixed,
+                    rotation,
+                },
+                &|query_index, column_index, rotation| Expression::Advice {
+                    query_index: query_index + advice_queries,
+                    column_index: column_index + map.advice,
+                    rotation,
+                },
+                &|query_index, column_index, rotation| Expression::Instance {
+                    query_index: query_index + instance_queries,
+                    column_index: column_index + map.instance,
+                    rotation,
+                },
+                &|a| -a,
+                &|a, b| a + b,
+                &|a, b| a * b,
+                &|a, f| a * f,
+            )
+        };
+
+        self.fixed_queries.extend(
+            other
+                .fixed_queries
+                .into_iter()
+                .map(|(column, at)| (map.fixed(column), at)),
+        );
+        self.advice_queries.extend(
+            other
+                .advice_queries
+                .into_iter()
+                .map(|(column, at)| (map.advice(column), at)),
+        );
+        self.instance_queries.extend(
+            other
+                .instance_queries
+                .into_iter()
+                .map(|(column, at)| (map.instance(column), at)),
+        );
+        self.num_advice_queries.extend(other.num_advice_queries);
+
+        self.gates.extend(other.gates.into_iter().map(|gate| {
+            Gate {
+                name: gate.name,
+                constraint_names: gate.constraint_names,
+                polys: gate.polys.iter().map(merge_expr).collect(),
+                queried_selectors: gate
+                    .queried_selectors
+                    .into_iter()
+                    .map(|selector| map.selector(selector))
+                    .collect(),
+                queried_cells: gate
+                    .queried_cells
+                    .into_iter()
+                    .map(|cell| VirtualCell {
+                        column: map.any(cell.column),
+                        rotation: cell.rotation,
+                    })
+                    .collect(),
+            }
+        }));
+        self.lookups
+            .extend(other.lookups.into_iter().map(|lookup| lookup::Argument {
+                name: lookup.name,
+                input_expressions: lookup.input_expressions.iter().map(merge_expr).collect(),
+                table_expressions: lookup.table_expressions.iter().map(merge_expr).collect(),
+            }));
+
+        for column in other.permutation.columns {
+            self.permutation.add_column(map.any(column));
+        }
+        self.independent_permutations
+            .extend(other.independent_permutations.into_iter().map(|argument| {
+                permutation::Argument {
+                    columns: argument
+                        .columns
+                        .into_iter()
+                        .map(|column| map.any(column))
+                        .collect(),
+                }
+            }));
+        self.constants
+            .extend(other.constants.into_iter().map(|column| map.fixed(column)));
+        self.selector_map.extend(
+            other
+                .selector_map
+                .into_iter()
+                .map(|column| map.fixed(column)),
+        );
+        self.fixed_defaults.extend(
+            other
+                .fixed_defaults
+                .into_iter()
+                .map(|(column, default)| (map.fixed(column), default)),
+        );
+
+        self.num_fixed_columns += other.num_fixed_columns;
+        self.num_advice_columns += other.num_advice_columns;
+        self.num_instance_columns += other.num_instance_columns;
+        self.num_selectors += other.num_selectors;
+        self.minimum_degree = max(self.minimum_degree, other.minimum_degree);
+        self.rotation_bound = max(self.rotation_bound, other.rotation_bound);
+        self.shrink_unused_columns |= other.shrink_unused_columns;
+
+        map
+    }
+
     /// Allocate a new (simple) selector. Simple selectors cannot be added to
     /// expressions nor multiplied by other expressions containing simple
     /// selectors. Also, simple selectors may not appear in lookup argument
@@ -1450,6 +2064,24 @@ impl<F: Field> ConstraintSystem<F> {
         tmp
     }
 
+    /// Allocate a new fixed column whose unassigned cells are filled with `default`,
+    /// for example to pad a lookup table with a value that is in the table.
+    pub fn fixed_column_with_default(&mut self, default: F) -> Column<Fixed> {
+        let column = self.fixed_column();
+        self.fixed_defaults.push((column, default));
+        column
+    }
+
+    /// Returns the value that the unassigned cells of each fixed column are filled
+    /// with, or `None` for columns that were allocated without a default.
+    pub(crate) fn fixed_defaults(&self) -> Vec<Option<F>> {
+        let mut defaults = vec![None; self.num_fixed_columns];
+        for (column, default) in &self.fixed_defaults {
+            defaults[column.index] = Some(*default);
+        }
+        defaults
+    }
+
     /// Allocate a new advice column
     pub fn advice_column(&mut self) -> Column<Advice> {
         let tmp = Column {
@@ -1543,6 +2175,137 @@ impl<F: Field> ConstraintSystem<F> {
                 // and the interstitial values.)
             + 1 // for at least one row
     }
+
+    /// Checks that every declared selector is used by at least one gate or lookup
+    /// argument.
+    ///
+    /// A selector that is never queried does not constrain anything, and usually
+    /// indicates a wiring mistake. This must be called before the selectors are
+    /// compressed into fixed columns. Both the [`MockProver`] and key generation
+    /// run this check.
+    ///
+    /// [`MockProver`]: crate::dev::MockProver
+    pub fn check_selectors(&self) -> Result<(), Error> {
+        for index in 0..self.num_selectors {
+            let in_gate = self
+                .gates
+                .iter()
+                .any(|gate| gate.queried_selectors.iter().any(|s| s.0 == index));
+            let in_lookup = || {
+                self.lookups.iter().any(|lookup| {
+                    lookup
+                        .input_expressions
+                        .iter()
+                        .chain(lookup.table_expressions.iter())
+                        .any(|expr| expr.contains_selector(index))
+                })
+            };
+            if !in_gate && !in_lookup() {
+                return Err(Error::UnconstrainedSelector(index));
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Returns the largest absolute rotation queried by any gate so far.
+    pub fn max_rotation(&self) -> u32 {
+        self.gates
+            .iter()
+            .flat_map(|gate| gate.queried_cells.iter())
+            .map(|cell| cell.rotation.0.unsigned_abs())
+            .max()
+            .unwrap_or(0)
+    }
+
+    /// Sets the largest absolute rotation that a gate may query. Defaults to
+    /// [`DEFAULT_ROTATION_BOUND`].
+    pub fn set_rotation_bound(&mut self, bound: u32) {
+        self.rotation_bound = bound;
+    }
+
+    /// Checks that no gate queries a rotation beyond the configured bound.
+    ///
+    /// Every rotation widens the set of rows each gate touches, so an absurd
+    /// rotation (usually a typo) silently inflates the prover's cost.
+    pub fn check_rotations(&self) -> Result<(), Error> {
+        for gate in self.gates.iter() {
+            for cell in gate.queried_cells.iter() {
+                if cell.rotation.0.unsigned_abs() > self.rotation_bound {
+                    return Err(Error::RotationOutOfBounds {
+                        gate: gate.name,
+                        rotation: cell.rotation.0,
+                        bound: self.rotation_bound,
+                    });
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Returns the number of fixed columns registered so far. Selectors are not
+    /// counted until they are compressed into fixed columns during keygen.
+    pub fn num_fixed_columns(&self) -> usize {
+        self.num_fixed_columns
+    }
+
+    /// Returns the number of advice columns registered so far.
+    pub fn num_advice_columns(&self) -> usize {
+        self.num_advice_columns
+    }
+
+    /// Returns the number of instance columns registered so far.
+    pub fn num_instance_columns(&self) -> usize {
+        self.num_instance_columns
+    }
+
+    /// Returns the number of selectors registered so far.
+    pub fn num_selectors(&self) -> usize {
+        self.num_selectors
+    }
+
+    /// Returns the deduplicated `(column, rotation)` queries on advice columns that
+    /// proofs open, in the order in which they were first made.
+    pub fn advice_queries(&self) -> &[(Column<Advice>, Rotation)] {
+        &self.advice_queries
+    }
+
+    /// Returns the distinct rotations at which `column` is queried, in the order in
+    /// which they were first made. Each of these is opened exactly once per proof,
+    /// however many gates query it.
+    pub fn advice_rotations(&self, column: Column<Advice>) -> Vec<Rotation> {
+        self.advice_queries
+            .iter()
+            .filter(|(c, _)| *c == column)
+            .map(|(_, rotation)| *rotation)
+            .collect()
+    }
+
+    /// Returns the deduplicated `(column, rotation)` queries on instance columns that
+    /// proofs open, in the order in which they were first made.
+    pub fn instance_queries(&self) -> &[(Column<Instance>, Rotation)] {
+        &self.instance_queries
+    }
+
+    /// Returns the columns involved in the permutation argument, in the order in
+    /// which they were equality-enabled.
+    pub fn permutation_columns(&self) -> &[Column<Any>] {
+        &self.permutation.columns
+    }
+
+    /// Returns the number of chunks the permutation arguments' columns are split
+    /// into, each of which is committed to with its own grand product polynomial.
+    /// This counts the chunks of the independent permutations registered with
+    /// [`ConstraintSystem::permutation`] as well.
+    ///
+    /// Each chunk contains at most `self.degree() - 2` columns.
+    pub fn num_permutation_chunks(&self) -> usize {
+        let chunk_len = self.degree() - 2;
+        self.permutations()
+            .map(|argument| (argument.columns.len() + chunk_len - 1) / chunk_len)
+            .sum()
+    }
 }
 
 /// Exposes the "virtual cells" that can be queried while creating a custom gate or lookup
@@ -1609,3 +2372,524 @@ impl<'a, F: Field> VirtualCells<'a, F> {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use pairing::bn256::{Bn256, Fr as Fp, G1Affine};
+
+    use super::{
+        Advice, Any, Circuit, Column, ConstraintSystem, Expression, Instance, Selector,
+        WrappedCircuit,
+    };
+    use crate::{
+        circuit::{Layouter, SimpleFloorPlanner},
+        dev::{
+            metadata,
+            mul_circuit::{MulConfig, PublicMulCircuit},
+            test_rng, MockProver, VerifyFailure,
+        },
+        plonk::{keygen_pk, keygen_vk, prove_to_bytes, verify_bytes, Error},
+        poly::{commitment::Params, Rotation},
+    };
+
+    #[test]
+    fn permutation_columns() {
+        let mut meta = ConstraintSystem::<Fp>::default();
+
+        // Mirrors `FieldChip::configure` from the simple example.
+        let advice = [meta.advice_column(), meta.advice_column()];
+        let instance = meta.instance_column();
+        let constant = meta.fixed_column();
+
+        meta.enable_equality(instance);
+        meta.enable_constant(constant);
+        for column in &advice {
+            meta.enable_equality(*column);
+        }
+        let s_mul = meta.selector();
+
+        meta.create_gate("mul", |meta| {
+            let lhs = meta.query_advice(advice[0], Rotation::cur());
+            let rhs = meta.query_advice(advice[1], Rotation::cur());
+            let out = meta.query_advice(advice[0], Rotation::next());
+            let s_mul = meta.query_selector(s_mul);
+            vec![s_mul * (lhs * rhs - out)]
+        });
+
+        assert_eq!(
+            meta.advice_queries(),
+            &[
+                (advice[0], Rotation::cur()),
+                (advice[1], Rotation::cur()),
+                (advice[0], Rotation::next()),
+            ]
+        );
+        assert_eq!(meta.instance_queries(), &[(instance, Rotation::cur())]);
+
+        let permuted_advice: Vec<Column<Any>> = meta
+            .permutation_columns()
+            .iter()
+            .filter(|column| *column.column_type() == Any::Advice)
+            .cloned()
+            .collect();
+        assert_eq!(
+            permuted_advice,
+            advice
+                .iter()
+                .map(|column: &Column<Advice>| (*column).into())
+                .collect::<Vec<_>>()
+        );
+        let expected: Vec<Column<Any>> = vec![
+            instance.into(),
+            constant.into(),
+            advice[0].into(),
+            advice[1].into(),
+        ];
+        assert_eq!(meta.permutation_columns(), &expected[..]);
+
+        // The degree is 3, so every column lives in its own chunk.
+        assert_eq!(meta.degree(), 3);
+        assert_eq!(meta.num_permutation_chunks(), 4);
+    }
+
+    #[test]
+    fn unconstrained_selector() {
+        let mut meta = ConstraintSystem::<Fp>::default();
+
+        let a = meta.advice_column();
+        let s_mul = meta.selector();
+        let q_lookup = meta.complex_selector();
+        let table = meta.lookup_table_column();
+
+        meta.create_gate("square", |meta| {
+            let a = meta.query_advice(a, Rotation::cur());
+            let s_mul = meta.query_selector(s_mul);
+            vec![s_mul * (a.clone() * a.clone() - a)]
+        });
+        meta.lookup("lookup", |meta| {
+            let q_lookup = meta.query_selector(q_lookup);
+            let a = meta.query_advice(a, Rotation::cur());
+            vec![(q_lookup * a, table)]
+        });
+        assert!(meta.check_selectors().is_ok());
+
+        // A selector that no gate or lookup queries is flagged.
+        let orphan = meta.selector();
+        assert!(matches!(
+            meta.check_selectors(),
+            Err(Error::UnconstrainedSelector(index)) if index == orphan.0
+        ));
+    }
+
+    #[test]
+    fn rotation_bound() {
+        let mut meta = ConstraintSystem::<Fp>::default();
+        let a = meta.advice_column();
+        let q = meta.selector();
+
+        meta.create_gate("next", |meta| {
+            let q = meta.query_selector(q);
+            let cur = meta.query_advice(a, Rotation::cur());
+            let next = meta.query_advice(a, Rotation::next());
+            vec![q * (next - cur)]
+        });
+        assert_eq!(meta.max_rotation(), 1);
+        assert!(meta.check_rotations().is_ok());
+
+        meta.create_gate("far", |meta| {
+            let q = meta.query_selector(q);
+            let far = meta.query_advice(a, Rotation(-1_000_000));
+            vec![q * far]
+        });
+        assert_eq!(meta.max_rotation(), 1_000_000);
+        assert!(matches!(
+            meta.check_rotations(),
+            Err(Error::RotationOutOfBounds {
+                gate: "far",
+                rotation: -1_000_000,
+                bound: super::DEFAULT_ROTATION_BOUND,
+            })
+        ));
+
+        // The bound is configurable.
+        meta.set_rotation_bound(1_000_000);
+        assert!(meta.check_rotations().is_ok());
+    }
+
+    #[test]
+    fn expression_fold() {
+        // The `mul` gate from the simple example.
+        let mut meta = ConstraintSystem::<Fp>::default();
+        MulConfig::configure(&mut meta);
+
+        let count_products = |expr: &super::Expression<Fp>| {
+            expr.evaluate(
+                &|_| 0,
+                &|_| 0,
+                &|_, _, _| 0,
+                &|_, _, _| 0,
+                &|_, _, _| 0,
+                &|a| a,
+                &|a, b| a + b,
+                &|a, b| a + b + 1,
+                &|a, _| a,
+            )
+        };
+
+        // `s_mul * (...)` and `lhs * rhs`.
+        let polys = meta.gates[0].polynomials();
+        assert_eq!(polys.len(), 1);
+        assert_eq!(count_products(&polys[0]), 2);
+    }
+
+    #[test]
+    fn deduplicated_rotations() {
+        let mut meta = ConstraintSystem::<Fp>::default();
+        let a = meta.advice_column();
+        let b = meta.advice_column();
+        let q = meta.selector();
+
+        meta.create_gate("window", |meta| {
+            let q = meta.query_selector(q);
+            let prev = meta.query_advice(a, Rotation::prev());
+            let cur = meta.query_advice(a, Rotation::cur());
+            let next = meta.query_advice(a, Rotation::next());
+            // Querying the same cell twice within a gate does not add a query.
+            let cur_again = meta.query_advice(a, Rotation::cur());
+            vec![q * (prev + next - cur - cur_again)]
+        });
+        meta.create_gate("other", |meta| {
+            let q = meta.query_selector(q);
+            let a = meta.query_advice(a, Rotation::next());
+            let b = meta.query_advice(b, Rotation::cur());
+            vec![q * (a - b)]
+        });
+
+        assert_eq!(
+            meta.advice_rotations(a),
+            vec![Rotation::prev(), Rotation::cur(), Rotation::next()]
+        );
+        assert_eq!(meta.advice_rotations(b), vec![Rotation::cur()]);
+        assert_eq!(meta.advice_queries().len(), 4);
+    }
+
+    #[test]
+    fn column_counts() {
+        // The configuration of the simple example's `FieldChip`.
+        let mut meta = ConstraintSystem::<Fp>::default();
+        MulConfig::configure(&mut meta);
+        let instance = meta.instance_column();
+        let constant = meta.fixed_column();
+        meta.enable_equality(instance);
+        meta.enable_constant(constant);
+
+        assert_eq!(meta.num_advice_columns(), 2);
+        assert_eq!(meta.num_instance_columns(), 1);
+        assert_eq!(meta.num_fixed_columns(), 1);
+        assert_eq!(meta.num_selectors(), 1);
+
+        // Keygen compresses the selector into an additional fixed column.
+        let (meta, _) = meta.compress_selectors(vec![vec![true, false]]);
+        assert_eq!(meta.num_fixed_columns(), 2);
+    }
+
+    #[test]
+    fn merge() {
+        // Each chip is configured against its own constraint system, so both of them
+        // allocate advice column 0 and selector 0.
+        fn configure_bool(meta: &mut ConstraintSystem<Fp>) -> (Column<Advice>, Selector) {
+            let a = meta.advice_column();
+            let q = meta.selector();
+            meta.create_gate("bool", |meta| {
+                let q = meta.query_selector(q);
+                let a = meta.query_advice(a, Rotation::cur());
+                vec![q * a.clone() * (Expression::Constant(Fp::one()) - a)]
+            });
+            (a, q)
+        }
+
+        fn configure_double(meta: &mut ConstraintSystem<Fp>) -> (Column<Advice>, Selector) {
+            let b = meta.advice_column();
+            let q = meta.selector();
+            meta.create_gate("double", |meta| {
+                let q = meta.query_selector(q);
+                let cur = meta.query_advice(b, Rotation::cur());
+                let next = meta.query_advice(b, Rotation::next());
+                vec![q * (cur.clone() + cur - next)]
+            });
+            (b, q)
+        }
+
+        #[derive(Clone)]
+        struct MergedConfig {
+            boolean: (Column<Advice>, Selector),
+            double: (Column<Advice>, Selector),
+        }
+
+        struct MergedCircuit {
+            a: u64,
+            b: u64,
+            doubled: u64,
+        }
+
+        impl Circuit<Fp> for MergedCircuit {
+            type Config = MergedConfig;
+            type FloorPlanner = SimpleFloorPlanner;
+
+            fn without_witnesses(&self) -> Self {
+                MergedCircuit {
+                    a: 0,
+                    b: 0,
+                    doubled: 0,
+                }
+            }
+
+            fn configure(meta: &mut ConstraintSystem<Fp>) -> Self::Config {
+                let mut bool_meta = ConstraintSystem::default();
+                let (a, q_bool) = configure_bool(&mut bool_meta);
+                let mut double_meta = ConstraintSystem::default();
+                let (b, q_double) = configure_double(&mut double_meta);
+
+                let bool_map = meta.merge(bool_meta);
+                let double_map = meta.merge(double_meta);
+                MergedConfig {
+                    boolean: (bool_map.advice(a), bool_map.selector(q_bool)),
+                    double: (double_map.advice(b), double_map.selector(q_double)),
+                }
+            }
+
+            fn synthesize(
+                &self,
+                config: Self::Config,
+                mut layouter: impl Layouter<Fp>,
+            ) -> Result<(), Error> {
+                layouter.assign_region(
+                    || "bool",
+                    |mut region| {
+                        let (a, q) = config.boolean;
+                        q.enable(&mut region, 0)?;
+                        region.assign_advice(|| "a", a, 0, || Ok(Fp::from(self.a)))?;
+                        Ok(())
+                    },
+                )?;
+                layouter.assign_region(
+                    || "double",
+                    |mut region| {
+                        let (b, q) = config.double;
+                        q.enable(&mut region, 0)?;
+                        region.assign_advice(|| "b", b, 0, || Ok(Fp::from(self.b)))?;
+                        region.assign_advice(|| "doubled", b, 1, || Ok(Fp::from(self.doubled)))?;
+                        Ok(())
+                    },
+                )
+            }
+        }
+
+        let config = MergedCircuit::configure(&mut ConstraintSystem::default());
+        assert_eq!(config.boolean.0, Column::new(0, Advice));
+        assert_eq!(config.double.0, Column::new(1, Advice));
+        assert_eq!(config.boolean.1 .0, 0);
+        assert_eq!(config.double.1 .0, 1);
+
+        let failing_gates = |circuit: MergedCircuit| -> Vec<metadata::Constraint> {
+            match MockProver::run(4, &circuit, vec![]).unwrap().verify() {
+                Ok(()) => vec![],
+                Err(failures) => failures
+                    .into_iter()
+                    .map(|failure| match failure {
+                        VerifyFailure::ConstraintNotSatisfied { constraint, .. } => constraint,
+                        _ => panic!("unexpected failure: {}", failure),
+                    })
+                    .collect(),
+            }
+        };
+        let constraint = |gate_index: usize, gate: &'static str| -> metadata::Constraint {
+            ((gate_index, gate).into(), 0, "").into()
+        };
+
+        assert!(failing_gates(MergedCircuit {
+            a: 1,
+            b: 3,
+            doubled: 6
+        })
+        .is_empty());
+        assert_eq!(
+            failing_gates(MergedCircuit {
+                a: 2,
+                b: 3,
+                doubled: 6
+            }),
+            vec![constraint(0, "bool")]
+        );
+        assert_eq!(
+            failing_gates(MergedCircuit {
+                a: 1,
+                b: 3,
+                doubled: 7
+            }),
+            vec![constraint(1, "double")]
+        );
+    }
+
+    #[test]
+    fn wrapped_circuit() {
+        const K: u32 = 4;
+
+        // The inner circuit exposes `3 * 3` as its only public input.
+        let circuit = WrappedCircuit {
+            inner: PublicMulCircuit::new(3, 3),
+            extra: vec![Some(Fp::from(42))],
+        };
+        let run = |inner: u64, extra: u64| {
+            MockProver::run(
+                K,
+                &circuit,
+                vec![vec![Fp::from(inner)], vec![Fp::from(extra)]],
+            )
+            .unwrap()
+            .verify()
+        };
+
+        // The wrapper's instance column comes after the inner circuit's.
+        assert_eq!(run(9, 42), Ok(()));
+
+        // Both the inner public input and the extra one are checked.
+        assert!(run(8, 42).is_err());
+        assert!(run(9, 41).is_err());
+    }
+
+    #[test]
+    fn independent_permutations() {
+        const K: u32 = 4;
+
+        /// Copies within each of two permutations, and optionally across them.
+        #[derive(Clone, Copy)]
+        struct CopyCircuit {
+            first: Option<Fp>,
+            second: Option<Fp>,
+            cross: bool,
+        }
+
+        impl Circuit<Fp> for CopyCircuit {
+            type Config = [Column<Advice>; 4];
+            type FloorPlanner = SimpleFloorPlanner;
+
+            fn without_witnesses(&self) -> Self {
+                CopyCircuit {
+                    first: None,
+                    second: None,
+                    cross: self.cross,
+                }
+            }
+
+            fn configure(meta: &mut ConstraintSystem<Fp>) -> Self::Config {
+                let advice = [
+                    meta.advice_column(),
+                    meta.advice_column(),
+                    meta.advice_column(),
+                    meta.advice_column(),
+                ];
+                assert_eq!(meta.permutation(&[advice[0].into(), advice[1].into()]), 0);
+                assert_eq!(meta.permutation(&[advice[2].into(), advice[3].into()]), 1);
+                advice
+            }
+
+            fn synthesize(
+                &self,
+                advice: Self::Config,
+                mut layouter: impl Layouter<Fp>,
+            ) -> Result<(), Error> {
+                layouter.assign_region(
+                    || "copies",
+                    |mut region| {
+                        // Copies 1 to `first` in the first permutation, and 2 to `second`
+                        // in the second.
+                        let a = region.assign_advice(|| "a", advice[0], 0, || Ok(Fp::from(1)))?;
+                        let b = region.assign_advice(
+                            || "b",
+                            advice[1],
+                            0,
+                            || self.first.ok_or(Error::Synthesis),
+                        )?;
+                        region.constrain_equal(a.cell(), b.cell())?;
+
+                        let c = region.assign_advice(|| "c", advice[2], 0, || Ok(Fp::from(2)))?;
+                        let d = region.assign_advice(
+                            || "d",
+                            advice[3],
+                            0,
+                            || self.second.ok_or(Error::Synthesis),
+                        )?;
+                        region.constrain_equal(c.cell(), d.cell())?;
+
+                        if self.cross {
+                            a.copy_advice(|| "a", &mut region, advice[2], 1)?;
+                        }
+                        Ok(())
+                    },
+                )
+            }
+        }
+
+        let circuit = |first: u64, second: u64, cross| CopyCircuit {
+            first: Some(Fp::from(first)),
+            second: Some(Fp::from(second)),
+            cross,
+        };
+        let mut meta = ConstraintSystem::<Fp>::default();
+        let advice = CopyCircuit::configure(&mut meta);
+
+        // The mock prover checks the copies of each permutation.
+        let run = |circuit: CopyCircuit| MockProver::run(K, &circuit, vec![]);
+        assert_eq!(run(circuit(1, 2, false)).unwrap().verify(), Ok(()));
+        let broken = |columns: [Column<Advice>; 2]| {
+            Err(columns
+                .iter()
+                .map(|column| VerifyFailure::Permutation {
+                    column: Column::<Any>::from(*column).into(),
+                    row: 0,
+                })
+                .collect::<Vec<_>>())
+        };
+        assert_eq!(
+            run(circuit(3, 2, false)).unwrap().verify(),
+            broken([advice[0], advice[1]])
+        );
+        assert_eq!(
+            run(circuit(1, 3, false)).unwrap().verify(),
+            broken([advice[2], advice[3]])
+        );
+
+        // Copying between the two permutations is rejected during synthesis.
+        assert!(matches!(
+            run(circuit(1, 2, true)),
+            Err(Error::CrossPermutationCopy(left, right))
+                if left == advice[2].into() && right == advice[0].into()
+        ));
+
+        // Each permutation is its own argument in the keys, alongside the (empty)
+        // argument over the equality-enabled columns, and proofs check all of them.
+        let params: Params<G1Affine> = Params::<G1Affine>::unsafe_setup::<Bn256>(K);
+        let params_verifier = params.verifier::<Bn256>(0).unwrap();
+        let vk = keygen_vk(&params, &circuit(1, 2, false)).unwrap();
+        assert_eq!(vk.permutations.len(), 3);
+        assert_eq!(vk.cs.num_permutation_chunks(), 2);
+        let pk = keygen_pk(&params, vk, &circuit(1, 2, false)).unwrap();
+
+        let instances: &[&[&[Fp]]] = &[&[]];
+        let verify = |first, second| {
+            let proof = prove_to_bytes(
+                &params,
+                &pk,
+                &[circuit(first, second, false)],
+                instances,
+                test_rng(),
+            )
+            .unwrap();
+            verify_bytes(&params_verifier, pk.get_vk(), instances, &proof)
+        };
+        assert!(verify(1, 2).is_ok());
+        assert!(verify(3, 2).is_err());
+        assert!(verify(1, 3).is_err());
+    }
+}
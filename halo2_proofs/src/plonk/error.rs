@@ -4,6 +4,7 @@ use std::fmt;
 use std::io;
 
 use super::{Any, Column};
+use crate::transcript::{ProofTooLarge, UnsupportedVersion};
 
 /// This is an error that could occur during proving or circuit synthesis.
 // TODO: these errors need to be cleaned up
@@ -27,6 +28,15 @@ pub enum Error {
         /// The current value of `k` being used.
         current_k: u32,
     },
+    /// The degree of the constraint system is too high for a circuit of size `2^k`:
+    /// its extended evaluation domain would not fit in the scalar field's largest
+    /// power-of-two subgroup.
+    DegreeTooHigh {
+        /// The degree of the constraint system.
+        degree: usize,
+        /// The value of `k` being used.
+        k: u32,
+    },
     /// Instance provided exceeds number of available rows
     InstanceTooLarge,
     /// Circuit synthesis requires global constants, but circuit configuration did not
@@ -37,12 +47,70 @@ pub enum Error {
     /// The instance sets up a copy constraint involving a column that has not been
     /// included in the permutation.
     ColumnNotInPermutation(Column<Any>),
+    /// A copy constraint involves two columns that are part of different independent
+    /// permutations registered with [`ConstraintSystem::permutation`].
+    ///
+    /// [`ConstraintSystem::permutation`]: crate::plonk::ConstraintSystem::permutation
+    CrossPermutationCopy(Column<Any>, Column<Any>),
+    /// A selector with the given index was declared, but is not used by any gate or
+    /// lookup argument.
+    UnconstrainedSelector(usize),
+    /// The proof was produced with a proof format version that this verifier does
+    /// not support.
+    UnsupportedProofVersion(u8),
+    /// A gate queries a rotation whose absolute value exceeds the bound configured
+    /// on the constraint system.
+    RotationOutOfBounds {
+        /// The name of the offending gate.
+        gate: &'static str,
+        /// The queried rotation.
+        rotation: i32,
+        /// The configured bound.
+        bound: u32,
+    },
+    /// The proof is larger than the budget of the [`LimitedRead`] transcript it was
+    /// read through.
+    ///
+    /// [`LimitedRead`]: crate::transcript::LimitedRead
+    ProofTooLarge,
+    /// The verifying key describes a different circuit structure from the other inputs
+    /// to the verifier.
+    CircuitMismatch {
+        /// The name of the mismatched dimension, for example `"k"`.
+        field: &'static str,
+        /// The value described by the verifying key.
+        expected: usize,
+        /// The value of the other input.
+        got: usize,
+    },
+    /// The proof was created for a different verifying key, for example one for a
+    /// structurally different circuit of the same size.
+    VerifyingKeyMismatch,
+    /// Two regions assigned to the same cell, for example because a region assigned
+    /// cells outside of the shape it was laid out with.
+    RegionOverlap {
+        /// The column of the cell.
+        column: Column<Any>,
+        /// The absolute row of the cell.
+        row: usize,
+        /// The index of the region that assigned the cell first.
+        region_a: usize,
+        /// The index of the region that assigned the cell again.
+        region_b: usize,
+    },
 }
 
 impl From<io::Error> for Error {
     fn from(error: io::Error) -> Self {
         // The only place we can get io::Error from is the transcript.
-        Error::Transcript(error)
+        match error.get_ref() {
+            Some(inner) if inner.is::<ProofTooLarge>() => Error::ProofTooLarge,
+            Some(inner) if inner.is::<UnsupportedVersion>() => {
+                let version = inner.downcast_ref::<UnsupportedVersion>().unwrap();
+                Error::UnsupportedProofVersion(version.0)
+            }
+            _ => Error::Transcript(error),
+        }
     }
 }
 
@@ -67,6 +135,11 @@ impl fmt::Display for Error {
                 "k = {} is too small for the given circuit. Try using a larger value of k",
                 current_k,
             ),
+            Error::DegreeTooHigh { degree, k } => write!(
+                f,
+                "Degree {} is too high for a circuit with k = {}",
+                degree, k
+            ),
             Error::InstanceTooLarge => write!(f, "Instance vectors are larger than the circuit"),
             Error::NotEnoughColumnsForConstants => {
                 write!(
@@ -79,6 +152,52 @@ impl fmt::Display for Error {
                 "Column {:?} must be included in the permutation. Help: try applying `meta.enable_equalty` on the column",
                 column
             ),
+            Error::CrossPermutationCopy(left, right) => write!(
+                f,
+                "Columns {:?} and {:?} are part of different permutations and cannot be copied to each other",
+                left, right
+            ),
+            Error::UnconstrainedSelector(index) => write!(
+                f,
+                "Selector {} is not used by any gate or lookup argument",
+                index
+            ),
+            Error::UnsupportedProofVersion(version) => {
+                write!(f, "Unsupported proof format version {}", version)
+            }
+            Error::RotationOutOfBounds {
+                gate,
+                rotation,
+                bound,
+            } => write!(
+                f,
+                "Gate {} queries rotation {}, which exceeds the bound of {}",
+                gate, rotation, bound
+            ),
+            Error::ProofTooLarge => write!(f, "The proof exceeds the maximum allowed size"),
+            Error::CircuitMismatch {
+                field,
+                expected,
+                got,
+            } => write!(
+                f,
+                "The verifying key expects {} = {}, but got {}",
+                field, expected, got
+            ),
+            Error::VerifyingKeyMismatch => write!(
+                f,
+                "The proof was created for a different verifying key"
+            ),
+            Error::RegionOverlap {
+                column,
+                row,
+                region_a,
+                region_b,
+            } => write!(
+                f,
+                "Regions {} and {} both assign to column {:?} at row {}",
+                region_a, region_b, column, row
+            ),
         }
     }
 }
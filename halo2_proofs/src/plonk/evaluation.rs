@@ -466,7 +466,7 @@ impl<C: CurveAffine> Evaluator<C> {
         gamma: C::ScalarExt,
         theta: C::ScalarExt,
         lookups: &[Vec<lookup::prover::Committed<C>>],
-        permutations: &[permutation::prover::Committed<C>],
+        permutations: &[Vec<permutation::prover::Committed<C>>],
     ) -> Polynomial<C::ScalarExt, ExtendedLagrangeCoeff> {
         let domain = &pk.vk.domain;
         let size = domain.extended_len();
@@ -479,7 +479,6 @@ impl<C: CurveAffine> Evaluator<C> {
         let l0 = &pk.l0;
         let l_last = &pk.l_last;
         let l_active_row = &pk.l_active_row;
-        let p = &pk.vk.cs.permutation;
 
         let mut values = domain.empty_extended();
         let mut lookup_values = vec![C::Scalar::zero(); size * num_lookups];
@@ -487,7 +486,7 @@ impl<C: CurveAffine> Evaluator<C> {
         // Core expression evaluations
         let num_threads = multicore::current_num_threads();
         let mut table_values_box = ThreadBox::wrap(&mut lookup_values);
-        for (((advice, instance), lookups), permutation) in advice
+        for (((advice, instance), lookups), permutations) in advice
             .iter()
             .zip(instance.iter())
             .zip(lookups.iter())
@@ -558,85 +557,95 @@ impl<C: CurveAffine> Evaluator<C> {
             });
 
             // Permutations
-            let sets = &permutation.sets;
-            if !sets.is_empty() {
-                let blinding_factors = pk.vk.cs.blinding_factors();
-                let last_rotation = Rotation(-((blinding_factors + 1) as i32));
-                let chunk_len = pk.vk.cs.degree() - 2;
-                let delta_start = beta * &C::Scalar::ZETA;
-
-                let first_set = sets.first().unwrap();
-                let last_set = sets.last().unwrap();
-
-                // Permutation constraints
-                parallelize(&mut values, |values, start| {
-                    let mut beta_term = extended_omega.pow_vartime(&[start as u64, 0, 0, 0]);
-                    for (i, value) in values.iter_mut().enumerate() {
-                        let idx = start + i;
-                        let r_next = get_rotation_idx(idx, 1, rot_scale, isize);
-                        let r_last = get_rotation_idx(idx, last_rotation.0, rot_scale, isize);
-
-                        // Enforce only for the first set.
-                        // l_0(X) * (1 - z_0(X)) = 0
-                        *value = *value * y
-                            + ((one - first_set.permutation_product_coset[idx]) * l0[idx]);
-                        // Enforce only for the last set.
-                        // l_last(X) * (z_l(X)^2 - z_l(X)) = 0
-                        *value = *value * y
-                            + ((last_set.permutation_product_coset[idx]
-                                * last_set.permutation_product_coset[idx]
-                                - last_set.permutation_product_coset[idx])
-                                * l_last[idx]);
-                        // Except for the first set, enforce.
-                        // l_0(X) * (z_i(X) - z_{i-1}(\omega^(last) X)) = 0
-                        for (set_idx, set) in sets.iter().enumerate() {
-                            if set_idx != 0 {
-                                *value = *value * y
-                                    + ((set.permutation_product_coset[idx]
-                                        - permutation.sets[set_idx - 1].permutation_product_coset
-                                            [r_last])
-                                        * l0[idx]);
+            for ((p, pkey), permutation) in pk
+                .vk
+                .cs
+                .permutations()
+                .zip(pk.permutations.iter())
+                .zip(permutations.iter())
+            {
+                let sets = &permutation.sets;
+                if !sets.is_empty() {
+                    let blinding_factors = pk.vk.cs.blinding_factors();
+                    let last_rotation = Rotation(-((blinding_factors + 1) as i32));
+                    let chunk_len = pk.vk.cs.degree() - 2;
+                    let delta_start = beta * &C::Scalar::ZETA;
+
+                    let first_set = sets.first().unwrap();
+                    let last_set = sets.last().unwrap();
+
+                    // Permutation constraints
+                    parallelize(&mut values, |values, start| {
+                        let mut beta_term = extended_omega.pow_vartime(&[start as u64, 0, 0, 0]);
+                        for (i, value) in values.iter_mut().enumerate() {
+                            let idx = start + i;
+                            let r_next = get_rotation_idx(idx, 1, rot_scale, isize);
+                            let r_last = get_rotation_idx(idx, last_rotation.0, rot_scale, isize);
+
+                            // Enforce only for the first set.
+                            // l_0(X) * (1 - z_0(X)) = 0
+                            *value = *value * y
+                                + ((one - first_set.permutation_product_coset[idx]) * l0[idx]);
+                            // Enforce only for the last set.
+                            // l_last(X) * (z_l(X)^2 - z_l(X)) = 0
+                            *value = *value * y
+                                + ((last_set.permutation_product_coset[idx]
+                                    * last_set.permutation_product_coset[idx]
+                                    - last_set.permutation_product_coset[idx])
+                                    * l_last[idx]);
+                            // Except for the first set, enforce.
+                            // l_0(X) * (z_i(X) - z_{i-1}(\omega^(last) X)) = 0
+                            for (set_idx, set) in sets.iter().enumerate() {
+                                if set_idx != 0 {
+                                    *value = *value * y
+                                        + ((set.permutation_product_coset[idx]
+                                            - permutation.sets[set_idx - 1]
+                                                .permutation_product_coset[r_last])
+                                            * l0[idx]);
+                                }
                             }
-                        }
-                        // And for all the sets we enforce:
-                        // (1 - (l_last(X) + l_blind(X))) * (
-                        //   z_i(\omega X) \prod_j (p(X) + \beta s_j(X) + \gamma)
-                        // - z_i(X) \prod_j (p(X) + \delta^j \beta X + \gamma)
-                        // )
-                        let mut current_delta = delta_start * beta_term;
-                        for ((set, columns), cosets) in sets
-                            .iter()
-                            .zip(p.columns.chunks(chunk_len))
-                            .zip(pk.permutation.cosets.chunks(chunk_len))
-                        {
-                            let mut left = set.permutation_product_coset[r_next];
-                            for (values, permutation) in columns
+                            // And for all the sets we enforce:
+                            // (1 - (l_last(X) + l_blind(X))) * (
+                            //   z_i(\omega X) \prod_j (p(X) + \beta s_j(X) + \gamma)
+                            // - z_i(X) \prod_j (p(X) + \delta^j \beta X + \gamma)
+                            // )
+                            let mut current_delta = delta_start * beta_term;
+                            for ((set, columns), cosets) in sets
                                 .iter()
-                                .map(|&column| match column.column_type() {
-                                    Any::Advice => &advice[column.index()],
-                                    Any::Fixed => &fixed[column.index()],
-                                    Any::Instance => &instance[column.index()],
-                                })
-                                .zip(cosets.iter())
+                                .zip(p.columns.chunks(chunk_len))
+                                .zip(pkey.cosets.chunks(chunk_len))
                             {
-                                left *= values[idx] + beta * permutation[idx] + gamma;
-                            }
-
-                            let mut right = set.permutation_product_coset[idx];
-                            for values in columns.iter().map(|&column| match column.column_type() {
-                                Any::Advice => &advice[column.index()],
-                                Any::Fixed => &fixed[column.index()],
-                                Any::Instance => &instance[column.index()],
-                            }) {
-                                right *= values[idx] + current_delta + gamma;
-                                current_delta *= &C::Scalar::DELTA;
+                                let mut left = set.permutation_product_coset[r_next];
+                                for (values, permutation) in columns
+                                    .iter()
+                                    .map(|&column| match column.column_type() {
+                                        Any::Advice => &advice[column.index()],
+                                        Any::Fixed => &fixed[column.index()],
+                                        Any::Instance => &instance[column.index()],
+                                    })
+                                    .zip(cosets.iter())
+                                {
+                                    left *= values[idx] + beta * permutation[idx] + gamma;
+                                }
+
+                                let mut right = set.permutation_product_coset[idx];
+                                for values in
+                                    columns.iter().map(|&column| match column.column_type() {
+                                        Any::Advice => &advice[column.index()],
+                                        Any::Fixed => &fixed[column.index()],
+                                        Any::Instance => &instance[column.index()],
+                                    })
+                                {
+                                    right *= values[idx] + current_delta + gamma;
+                                    current_delta *= &C::Scalar::DELTA;
+                                }
+
+                                *value = *value * y + ((left - right) * l_active_row[idx]);
                             }
-
-                            *value = *value * y + ((left - right) * l_active_row[idx]);
+                            beta_term *= &extended_omega;
                         }
-                        beta_term *= &extended_omega;
-                    }
-                });
+                    });
+                }
             }
 
             // Lookups
@@ -2,7 +2,7 @@
 
 use std::ops::Range;
 
-use ff::Field;
+use ff::{Field, PrimeField};
 use group::Curve;
 
 use super::{
@@ -24,13 +24,17 @@ use crate::{
 
 use crate::arithmetic::parallelize;
 
+#[allow(clippy::type_complexity)]
 pub(crate) fn create_domain<C, ConcreteCircuit>(
     params: &Params<C>,
-) -> (
-    EvaluationDomain<C::Scalar>,
-    ConstraintSystem<C::Scalar>,
-    ConcreteCircuit::Config,
-)
+) -> Result<
+    (
+        EvaluationDomain<C::Scalar>,
+        ConstraintSystem<C::Scalar>,
+        ConcreteCircuit::Config,
+    ),
+    Error,
+>
 where
     C: CurveAffine,
     ConcreteCircuit: Circuit<C::Scalar>,
@@ -40,9 +44,38 @@ where
 
     let degree = cs.degree();
 
+    // The extended domain must fit in the largest power-of-two subgroup of the
+    // scalar field.
+    if (params.n as u128) * (degree as u128 - 1) > 1 << C::Scalar::S {
+        return Err(Error::DegreeTooHigh {
+            degree,
+            k: params.k,
+        });
+    }
+
     let domain = EvaluationDomain::new(degree as u32, params.k);
 
-    (domain, cs, config)
+    Ok((domain, cs, config))
+}
+
+/// Returns the fixed columns of `cs` before synthesis, with every cell of a column
+/// that has a default set to that default.
+fn empty_fixed_columns<F: Field>(
+    domain: &EvaluationDomain<F>,
+    cs: &ConstraintSystem<F>,
+) -> Vec<Polynomial<Assigned<F>, LagrangeCoeff>> {
+    cs.fixed_defaults()
+        .into_iter()
+        .map(|default| {
+            let mut column = domain.empty_lagrange_assigned();
+            if let Some(default) = default {
+                for cell in column.iter_mut() {
+                    *cell = default.into();
+                }
+            }
+            column
+        })
+        .collect()
 }
 
 /// Assembly to be used in circuit synthesis.
@@ -50,7 +83,7 @@ where
 struct Assembly<F: Field> {
     k: u32,
     fixed: Vec<Polynomial<Assigned<F>, LagrangeCoeff>>,
-    permutation: permutation::keygen::Assembly,
+    permutations: Vec<permutation::keygen::Assembly>,
     selectors: Vec<Vec<bool>>,
     // A range of available rows for assignment and copies.
     usable_rows: Range<usize>,
@@ -93,6 +126,10 @@ impl<F: Field> Assignment<F> for Assembly<F> {
         Ok(None)
     }
 
+    fn evaluates_witness(&self) -> bool {
+        false
+    }
+
     fn assign_advice<V, VR, A, AR>(
         &mut self,
         _: A,
@@ -147,8 +184,13 @@ impl<F: Field> Assignment<F> for Assembly<F> {
             return Err(Error::not_enough_rows_available(self.k));
         }
 
-        self.permutation
-            .copy(left_column, left_row, right_column, right_row)
+        permutation::keygen::copy(
+            &mut self.permutations,
+            left_column,
+            left_row,
+            right_column,
+            right_row,
+        )
     }
 
     fn fill_from_row(
@@ -195,7 +237,9 @@ where
     C: CurveAffine,
     ConcreteCircuit: Circuit<C::Scalar>,
 {
-    let (domain, cs, config) = create_domain::<C, ConcreteCircuit>(params);
+    let (domain, cs, config) = create_domain::<C, ConcreteCircuit>(params)?;
+    cs.check_selectors()?;
+    cs.check_rotations()?;
 
     if (params.n as usize) < cs.minimum_rows() {
         return Err(Error::not_enough_rows_available(params.k));
@@ -203,8 +247,11 @@ where
 
     let mut assembly: Assembly<C::Scalar> = Assembly {
         k: params.k,
-        fixed: vec![domain.empty_lagrange_assigned(); cs.num_fixed_columns],
-        permutation: permutation::keygen::Assembly::new(params.n as usize, &cs.permutation),
+        fixed: empty_fixed_columns(&domain, &cs),
+        permutations: cs
+            .permutations()
+            .map(|argument| permutation::keygen::Assembly::new(params.n as usize, argument))
+            .collect(),
         selectors: vec![vec![false; params.n as usize]; cs.num_selectors],
         usable_rows: 0..params.n as usize - (cs.blinding_factors() + 1),
         _marker: std::marker::PhantomData,
@@ -218,7 +265,8 @@ where
         cs.constants.clone(),
     )?;
 
-    let mut fixed = batch_invert_assigned(assembly.fixed);
+    let (cs, retained_columns) = cs.shrink();
+    let mut fixed = batch_invert_assigned(retained_columns.select_fixed(assembly.fixed));
     let (cs, selector_polys) = cs.compress_selectors(assembly.selectors);
     fixed.extend(
         selector_polys
@@ -226,9 +274,12 @@ where
             .map(|poly| domain.lagrange_from_vec(poly)),
     );
 
-    let permutation_vk = assembly
-        .permutation
-        .build_vk(params, &domain, &cs.permutation);
+    let permutation_vks = assembly
+        .permutations
+        .into_iter()
+        .zip(cs.permutations())
+        .map(|(permutation, argument)| permutation.build_vk(params, &domain, argument))
+        .collect();
 
     let fixed_commitments = fixed
         .iter()
@@ -238,8 +289,9 @@ where
     Ok(VerifyingKey {
         domain,
         fixed_commitments,
-        permutation: permutation_vk,
+        permutations: permutation_vks,
         cs,
+        retained_columns,
     })
 }
 
@@ -264,8 +316,11 @@ where
 
     let mut assembly: Assembly<C::Scalar> = Assembly {
         k: params.k,
-        fixed: vec![vk.domain.empty_lagrange_assigned(); cs.num_fixed_columns],
-        permutation: permutation::keygen::Assembly::new(params.n as usize, &cs.permutation),
+        fixed: empty_fixed_columns(&vk.domain, &cs),
+        permutations: cs
+            .permutations()
+            .map(|argument| permutation::keygen::Assembly::new(params.n as usize, argument))
+            .collect(),
         selectors: vec![vec![false; params.n as usize]; cs.num_selectors],
         usable_rows: 0..params.n as usize - (cs.blinding_factors() + 1),
         _marker: std::marker::PhantomData,
@@ -279,7 +334,8 @@ where
         cs.constants.clone(),
     )?;
 
-    let mut fixed = batch_invert_assigned(assembly.fixed);
+    let (cs, retained_columns) = cs.shrink();
+    let mut fixed = batch_invert_assigned(retained_columns.select_fixed(assembly.fixed));
     let (cs, selector_polys) = cs.compress_selectors(assembly.selectors);
     fixed.extend(
         selector_polys
@@ -297,9 +353,12 @@ where
         .map(|poly| vk.domain.coeff_to_extended(poly.clone()))
         .collect();
 
-    let permutation_pk = assembly
-        .permutation
-        .build_pk(params, &vk.domain, &cs.permutation);
+    let permutation_pks = assembly
+        .permutations
+        .into_iter()
+        .zip(cs.permutations())
+        .map(|(permutation, argument)| permutation.build_pk(params, &vk.domain, argument))
+        .collect();
 
     // Compute l_0(X)
     // TODO: this can be done more efficiently
@@ -345,7 +404,7 @@ where
         fixed_values: fixed,
         fixed_polys,
         fixed_cosets,
-        permutation: permutation_pk,
+        permutations: permutation_pks,
         ev,
     })
 }
@@ -97,6 +97,18 @@ impl Assembly {
         Ok(())
     }
 
+    /// Returns whether `column` is involved in this permutation argument.
+    fn contains(&self, column: Column<Any>) -> bool {
+        self.columns.contains(&column)
+    }
+
+    /// Returns the distinguished element of the cycle containing the cell at `row`
+    /// of `column`, or `None` if `column` is not involved in this argument.
+    pub(crate) fn cycle(&self, column: Column<Any>, row: usize) -> Option<(usize, usize)> {
+        let index = self.columns.iter().position(|c| *c == column)?;
+        Some(self.aux[index][row])
+    }
+
     pub(crate) fn build_vk<C: CurveAffine>(
         self,
         params: &Params<C>,
@@ -210,3 +222,31 @@ impl Assembly {
         }
     }
 }
+
+/// Applies a copy constraint to whichever of a circuit's permutation `assemblies`
+/// involves both columns.
+///
+/// Returns [`Error::CrossPermutationCopy`] if the columns are involved in different
+/// permutation arguments.
+pub(crate) fn copy(
+    assemblies: &mut [Assembly],
+    left_column: Column<Any>,
+    left_row: usize,
+    right_column: Column<Any>,
+    right_row: usize,
+) -> Result<(), Error> {
+    let assembly = assemblies
+        .iter_mut()
+        .find(|assembly| assembly.contains(left_column))
+        .ok_or(Error::ColumnNotInPermutation(left_column))?;
+    if assembly.contains(right_column) {
+        assembly.copy(left_column, left_row, right_column, right_row)
+    } else if assemblies
+        .iter()
+        .any(|assembly| assembly.contains(right_column))
+    {
+        Err(Error::CrossPermutationCopy(left_column, right_column))
+    } else {
+        Err(Error::ColumnNotInPermutation(right_column))
+    }
+}
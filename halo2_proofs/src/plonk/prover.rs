@@ -4,6 +4,8 @@ use rand_core::RngCore;
 use std::env::var;
 use std::ops::RangeTo;
 use std::sync::atomic::AtomicUsize;
+#[cfg(feature = "profile")]
+use std::time::Duration;
 use std::time::Instant;
 use std::{iter, sync::atomic::Ordering};
 
@@ -30,7 +32,7 @@ use crate::{
 };
 use crate::{
     poly::batch_invert_assigned,
-    transcript::{EncodedChallenge, TranscriptWrite},
+    transcript::{Blake2bWrite, Challenge255, EncodedChallenge, TranscriptWrite, PROOF_VERSION},
 };
 
 /// This creates a proof for the provided `circuit` when given the public
@@ -48,8 +50,209 @@ pub fn create_proof<
     pk: &ProvingKey<C>,
     circuits: &[ConcreteCircuit],
     instances: &[&[&[C::Scalar]]],
+    rng: R,
+    transcript: &mut T,
+) -> Result<(), Error> {
+    create_proof_inner(
+        params,
+        pk,
+        circuits,
+        instances,
+        None,
+        rng,
+        transcript,
+        &mut Profiler::start(),
+    )
+}
+
+/// This creates a proof exactly like [`create_proof`], using a Blake2b transcript,
+/// and returns the proof bytes. Use [`verify_bytes`] to verify the result.
+///
+/// [`verify_bytes`]: crate::plonk::verify_bytes
+pub fn prove_to_bytes<C: CurveAffine, R: RngCore, ConcreteCircuit: Circuit<C::Scalar>>(
+    params: &Params<C>,
+    pk: &ProvingKey<C>,
+    circuits: &[ConcreteCircuit],
+    instances: &[&[&[C::Scalar]]],
+    rng: R,
+) -> Result<Vec<u8>, Error> {
+    let mut transcript = Blake2bWrite::<_, C, Challenge255<C>>::init(vec![]);
+    create_proof(params, pk, circuits, instances, rng, &mut transcript)?;
+    Ok(transcript.finalize())
+}
+
+/// The time spent in each phase of [`create_proof_profiled`].
+#[cfg(feature = "profile")]
+#[derive(Clone, Debug, Default)]
+pub struct ProofTimings {
+    /// Synthesizing the witness, and committing to the instance and advice
+    /// columns and to the vanishing argument's polynomials.
+    pub commit: Duration,
+    /// Evaluating the quotient polynomial `h(X)` over the extended domain.
+    pub fft: Duration,
+    /// Committing to the permutation argument's grand products.
+    pub permutation: Duration,
+    /// Permuting the lookup arguments' columns and committing to their products.
+    pub lookups: Duration,
+    /// Evaluating the polynomials at the challenge point `x`.
+    pub evaluation: Duration,
+    /// Creating the multi-point opening proof, whose cost is dominated by its
+    /// multi-scalar multiplications.
+    pub multiopen: Duration,
+    /// The total time taken to create the proof.
+    pub total: Duration,
+}
+
+/// This creates a proof exactly like [`create_proof`], and returns the time
+/// spent in each of its phases.
+#[cfg(feature = "profile")]
+pub fn create_proof_profiled<
+    C: CurveAffine,
+    E: EncodedChallenge<C>,
+    R: RngCore,
+    T: TranscriptWrite<C, E>,
+    ConcreteCircuit: Circuit<C::Scalar>,
+>(
+    params: &Params<C>,
+    pk: &ProvingKey<C>,
+    circuits: &[ConcreteCircuit],
+    instances: &[&[&[C::Scalar]]],
+    rng: R,
+    transcript: &mut T,
+) -> Result<ProofTimings, Error> {
+    let mut profiler = Profiler::start();
+    create_proof_inner(
+        params,
+        pk,
+        circuits,
+        instances,
+        None,
+        rng,
+        transcript,
+        &mut profiler,
+    )?;
+
+    Ok(profiler.timings)
+}
+
+#[derive(Clone, Copy)]
+enum Phase {
+    Commit,
+    Fft,
+    Permutation,
+    Lookups,
+    Evaluation,
+    Multiopen,
+}
+
+/// Attributes the time between consecutive laps to proof creation phases when
+/// the `profile` feature is enabled, and is a no-op otherwise.
+struct Profiler {
+    #[cfg(feature = "profile")]
+    timings: ProofTimings,
+    #[cfg(feature = "profile")]
+    start: Instant,
+    #[cfg(feature = "profile")]
+    last: Instant,
+}
+
+impl Profiler {
+    fn start() -> Self {
+        #[cfg(feature = "profile")]
+        {
+            let now = Instant::now();
+            Profiler {
+                timings: ProofTimings::default(),
+                start: now,
+                last: now,
+            }
+        }
+        #[cfg(not(feature = "profile"))]
+        Profiler {}
+    }
+
+    /// Attributes the time since the previous lap to `phase`.
+    #[cfg_attr(not(feature = "profile"), allow(unused_variables))]
+    fn lap(&mut self, phase: Phase) {
+        #[cfg(feature = "profile")]
+        {
+            let now = Instant::now();
+            let elapsed = now - self.last;
+            self.last = now;
+
+            let timings = &mut self.timings;
+            *match phase {
+                Phase::Commit => &mut timings.commit,
+                Phase::Fft => &mut timings.fft,
+                Phase::Permutation => &mut timings.permutation,
+                Phase::Lookups => &mut timings.lookups,
+                Phase::Evaluation => &mut timings.evaluation,
+                Phase::Multiopen => &mut timings.multiopen,
+            } += elapsed;
+            timings.total = now - self.start;
+        }
+    }
+}
+
+/// This creates a proof exactly like [`create_proof`], but absorbs the
+/// caller-supplied `instance_commitments` (one slice per circuit, one
+/// commitment per instance column) into the transcript instead of
+/// recomputing them from `instances`. The instance values are still needed
+/// to evaluate the instance polynomials.
+///
+/// In debug builds the supplied commitments are checked against the ones
+/// computed from `instances`.
+pub fn create_proof_with_instance_commitments<
+    C: CurveAffine,
+    E: EncodedChallenge<C>,
+    R: RngCore,
+    T: TranscriptWrite<C, E>,
+    ConcreteCircuit: Circuit<C::Scalar>,
+>(
+    params: &Params<C>,
+    pk: &ProvingKey<C>,
+    circuits: &[ConcreteCircuit],
+    instances: &[&[&[C::Scalar]]],
+    instance_commitments: &[&[C]],
+    rng: R,
+    transcript: &mut T,
+) -> Result<(), Error> {
+    if instance_commitments.len() != instances.len() {
+        return Err(Error::InvalidInstances);
+    }
+    for commitments in instance_commitments.iter() {
+        if commitments.len() != pk.vk.cs.num_instance_columns {
+            return Err(Error::InvalidInstances);
+        }
+    }
+
+    create_proof_inner(
+        params,
+        pk,
+        circuits,
+        instances,
+        Some(instance_commitments),
+        rng,
+        transcript,
+        &mut Profiler::start(),
+    )
+}
+
+fn create_proof_inner<
+    C: CurveAffine,
+    E: EncodedChallenge<C>,
+    R: RngCore,
+    T: TranscriptWrite<C, E>,
+    ConcreteCircuit: Circuit<C::Scalar>,
+>(
+    params: &Params<C>,
+    pk: &ProvingKey<C>,
+    circuits: &[ConcreteCircuit],
+    instances: &[&[&[C::Scalar]]],
+    precommitted: Option<&[&[C]]>,
     mut rng: R,
     transcript: &mut T,
+    profiler: &mut Profiler,
 ) -> Result<(), Error> {
     for instance in instances.iter() {
         if instance.len() != pk.vk.cs.num_instance_columns {
@@ -57,13 +260,21 @@ pub fn create_proof<
         }
     }
 
-    // Hash verification key into transcript
-    pk.vk.hash_into(transcript)?;
+    // Write the proof format version
+    transcript.write_version(PROOF_VERSION)?;
+
+    // Declare the verification key the proof is for, hashing it into the transcript
+    transcript.write_scalar(pk.vk.transcript_repr())?;
 
     let domain = &pk.vk.domain;
     let mut meta = ConstraintSystem::default();
     let config = ConcreteCircuit::configure(&mut meta);
 
+    // The circuit assigns to its original advice columns, of which only those
+    // retained by keygen are committed to.
+    let num_advice_columns = meta.num_advice_columns;
+    let (_, retained_columns) = meta.shrink();
+
     // Selector optimizations cannot be applied here; use the ConstraintSystem
     // from the verification key.
     let meta = &pk.vk.cs;
@@ -76,54 +287,73 @@ pub fn create_proof<
 
     let instance: Vec<InstanceSingle<C>> = instances
         .iter()
-        .map(|instance| -> Result<InstanceSingle<C>, Error> {
-            let instance_values = instance
-                .iter()
-                .map(|values| {
-                    let mut poly = domain.empty_lagrange();
-                    assert_eq!(poly.len(), params.n as usize);
-                    if values.len() > (poly.len() - (meta.blinding_factors() + 1)) {
-                        return Err(Error::InstanceTooLarge);
-                    }
-                    for (poly, value) in poly.iter_mut().zip(values.iter()) {
-                        *poly = *value;
+        .enumerate()
+        .map(
+            |(circuit_index, instance)| -> Result<InstanceSingle<C>, Error> {
+                let instance_values = instance
+                    .iter()
+                    .map(|values| {
+                        let mut poly = domain.empty_lagrange();
+                        assert_eq!(poly.len(), params.n as usize);
+                        if values.len() > (poly.len() - (meta.blinding_factors() + 1)) {
+                            return Err(Error::InstanceTooLarge);
+                        }
+                        for (poly, value) in poly.iter_mut().zip(values.iter()) {
+                            *poly = *value;
+                        }
+                        Ok(poly)
+                    })
+                    .collect::<Result<Vec<_>, _>>()?;
+                let compute_commitments = || {
+                    let instance_commitments_projective: Vec<_> = instance_values
+                        .iter()
+                        .map(|poly| params.commit_lagrange(poly))
+                        .collect();
+                    let mut instance_commitments =
+                        vec![C::identity(); instance_commitments_projective.len()];
+                    C::Curve::batch_normalize(
+                        &instance_commitments_projective,
+                        &mut instance_commitments,
+                    );
+                    instance_commitments
+                };
+                let instance_commitments = match precommitted {
+                    Some(precommitted) => {
+                        let instance_commitments = precommitted[circuit_index].to_vec();
+                        debug_assert_eq!(
+                            instance_commitments,
+                            compute_commitments(),
+                            "precomputed instance commitments do not match the instance values"
+                        );
+                        instance_commitments
                     }
-                    Ok(poly)
-                })
-                .collect::<Result<Vec<_>, _>>()?;
-            let instance_commitments_projective: Vec<_> = instance_values
-                .iter()
-                .map(|poly| params.commit_lagrange(poly))
-                .collect();
-            let mut instance_commitments =
-                vec![C::identity(); instance_commitments_projective.len()];
-            C::Curve::batch_normalize(&instance_commitments_projective, &mut instance_commitments);
-            let instance_commitments = instance_commitments;
-            drop(instance_commitments_projective);
-
-            for commitment in &instance_commitments {
-                transcript.common_point(*commitment)?;
-            }
-
-            let instance_polys: Vec<_> = instance_values
-                .iter()
-                .map(|poly| {
-                    let lagrange_vec = domain.lagrange_from_vec(poly.to_vec());
-                    domain.lagrange_to_coeff(lagrange_vec)
-                })
-                .collect();
+                    None => compute_commitments(),
+                };
 
-            let instance_cosets: Vec<_> = instance_polys
-                .iter()
-                .map(|poly| domain.coeff_to_extended(poly.clone()))
-                .collect();
+                for commitment in &instance_commitments {
+                    transcript.common_point(*commitment)?;
+                }
 
-            Ok(InstanceSingle {
-                instance_values,
-                instance_polys,
-                instance_cosets,
-            })
-        })
+                let instance_polys: Vec<_> = instance_values
+                    .iter()
+                    .map(|poly| {
+                        let lagrange_vec = domain.lagrange_from_vec(poly.to_vec());
+                        domain.lagrange_to_coeff(lagrange_vec)
+                    })
+                    .collect();
+
+                let instance_cosets: Vec<_> = instance_polys
+                    .iter()
+                    .map(|poly| domain.coeff_to_extended(poly.clone()))
+                    .collect();
+
+                Ok(InstanceSingle {
+                    instance_values,
+                    instance_polys,
+                    instance_cosets,
+                })
+            },
+        )
         .collect::<Result<Vec<_>, _>>()?;
 
     struct AdviceSingle<C: CurveAffine> {
@@ -270,7 +500,7 @@ pub fn create_proof<
 
             let mut witness = WitnessCollection {
                 k: params.k,
-                advice: vec![domain.empty_lagrange_assigned(); meta.num_advice_columns],
+                advice: vec![domain.empty_lagrange_assigned(); num_advice_columns],
                 instances,
                 // The prover will not be allowed to assign values to advice
                 // cells that exist within inactive rows, which include some
@@ -288,7 +518,7 @@ pub fn create_proof<
                 meta.constants.clone(),
             )?;
 
-            let mut advice = batch_invert_assigned(witness.advice);
+            let mut advice = batch_invert_assigned(retained_columns.select_advice(witness.advice));
 
             // Add blinding factors to advice columns
             for advice in &mut advice {
@@ -328,6 +558,7 @@ pub fn create_proof<
             })
         })
         .collect::<Result<Vec<_>, _>>()?;
+    profiler.lap(Phase::Commit);
 
     // Sample theta challenge for keeping lookup columns linearly independent
     let theta: ChallengeTheta<_> = transcript.squeeze_challenge_scalar();
@@ -357,6 +588,7 @@ pub fn create_proof<
                 .collect()
         })
         .collect::<Result<Vec<_>, _>>()?;
+    profiler.lap(Phase::Lookups);
 
     // Sample beta challenge
     let beta: ChallengeBeta<_> = transcript.squeeze_challenge_scalar();
@@ -365,24 +597,33 @@ pub fn create_proof<
     let gamma: ChallengeGamma<_> = transcript.squeeze_challenge_scalar();
 
     // Commit to permutations.
-    let permutations: Vec<permutation::prover::Committed<C>> = instance
+    let permutations: Vec<Vec<permutation::prover::Committed<C>>> = instance
         .iter()
         .zip(advice.iter())
-        .map(|(instance, advice)| {
-            pk.vk.cs.permutation.commit(
-                params,
-                pk,
-                &pk.permutation,
-                &advice.advice_values,
-                &pk.fixed_values,
-                &instance.instance_values,
-                beta,
-                gamma,
-                &mut rng,
-                transcript,
-            )
+        .map(|(instance, advice)| -> Result<Vec<_>, _> {
+            // Commit to the products of each permutation argument
+            pk.vk
+                .cs
+                .permutations()
+                .zip(pk.permutations.iter())
+                .map(|(argument, pkey)| {
+                    argument.commit(
+                        params,
+                        pk,
+                        pkey,
+                        &advice.advice_values,
+                        &pk.fixed_values,
+                        &instance.instance_values,
+                        beta,
+                        gamma,
+                        &mut rng,
+                        transcript,
+                    )
+                })
+                .collect::<Result<Vec<_>, _>>()
         })
         .collect::<Result<Vec<_>, _>>()?;
+    profiler.lap(Phase::Permutation);
 
     let lookups: Vec<Vec<lookup::prover::Committed<C>>> = lookups
         .into_iter()
@@ -394,9 +635,11 @@ pub fn create_proof<
                 .collect::<Result<Vec<_>, _>>()
         })
         .collect::<Result<Vec<_>, _>>()?;
+    profiler.lap(Phase::Lookups);
 
     // Commit to the vanishing argument's random polynomial for blinding h(x_3)
     let vanishing = vanishing::Argument::commit(params, domain, rng, transcript)?;
+    profiler.lap(Phase::Commit);
 
     // Obtain challenge for keeping all separate gates linearly independent
     let y: ChallengeY<_> = transcript.squeeze_challenge_scalar();
@@ -413,9 +656,11 @@ pub fn create_proof<
         &lookups,
         &permutations,
     );
+    profiler.lap(Phase::Fft);
 
     // Construct the vanishing argument's h(X) commitments
     let vanishing = vanishing.construct(params, domain, h_poly, transcript)?;
+    profiler.lap(Phase::Commit);
 
     let x: ChallengeX<_> = transcript.squeeze_challenge_scalar();
     let xn = x.pow(&[params.n as u64, 0, 0, 0]);
@@ -477,12 +722,19 @@ pub fn create_proof<
     let vanishing = vanishing.evaluate(x, xn, domain, transcript)?;
 
     // Evaluate common permutation data
-    pk.permutation.evaluate(x, transcript)?;
+    for permutation in &pk.permutations {
+        permutation.evaluate(x, transcript)?;
+    }
 
     // Evaluate the permutations, if any, at omega^i x.
-    let permutations: Vec<permutation::prover::Evaluated<C>> = permutations
+    let permutations: Vec<Vec<permutation::prover::Evaluated<C>>> = permutations
         .into_iter()
-        .map(|permutation| -> Result<_, _> { permutation.construct().evaluate(pk, x, transcript) })
+        .map(|permutations| -> Result<Vec<_>, _> {
+            permutations
+                .into_iter()
+                .map(|permutation| permutation.construct().evaluate(pk, x, transcript))
+                .collect::<Result<Vec<_>, _>>()
+        })
         .collect::<Result<Vec<_>, _>>()?;
 
     // Evaluate the lookups, if any, at omega^i x.
@@ -495,13 +747,14 @@ pub fn create_proof<
                 .collect::<Result<Vec<_>, _>>()
         })
         .collect::<Result<Vec<_>, _>>()?;
+    profiler.lap(Phase::Evaluation);
 
     let instances = instance
         .iter()
         .zip(advice.iter())
         .zip(permutations.iter())
         .zip(lookups.iter())
-        .flat_map(|(((instance, advice), permutation), lookups)| {
+        .flat_map(|(((instance, advice), permutations), lookups)| {
             iter::empty()
                 .chain(
                     pk.vk
@@ -525,7 +778,7 @@ pub fn create_proof<
                             poly: &advice.advice_polys[column.index()],
                         }),
                 )
-                .chain(permutation.open(pk, x))
+                .chain(permutations.iter().flat_map(move |p| p.open(pk, x)))
                 .chain(lookups.iter().flat_map(move |p| p.open(pk, x)).into_iter())
         })
         .chain(
@@ -539,9 +792,12 @@ pub fn create_proof<
                     poly: &pk.fixed_polys[column.index()],
                 }),
         )
-        .chain(pk.permutation.open(x))
+        .chain(pk.permutations.iter().flat_map(move |p| p.open(x)))
         // We query the h(X) polynomial at x
         .chain(vanishing.open(x));
 
-    multiopen::create_proof(params, transcript, instances).map_err(|_| Error::Opening)
+    let result = multiopen::create_proof(params, transcript, instances).map_err(|_| Error::Opening);
+    profiler.lap(Phase::Multiopen);
+
+    result
 }
@@ -4,6 +4,8 @@ use rand_core::RngCore;
 use std::iter;
 use std::marker::PhantomData;
 use std::ops::Mul;
+#[cfg(feature = "tokio")]
+use std::sync::Arc;
 
 use super::{
     vanishing, ChallengeBeta, ChallengeGamma, ChallengeTheta, ChallengeX, ChallengeY, Error,
@@ -17,7 +19,12 @@ use crate::poly::{
     multiopen::{self, VerifierQuery},
     PairMSM, MSM,
 };
-use crate::transcript::{read_n_points, read_n_scalars, EncodedChallenge, TranscriptRead};
+#[cfg(test)]
+use crate::transcript::FixedChallenges;
+use crate::transcript::{
+    read_n_points, read_n_scalars, Blake2bRead, Challenge255, EncodedChallenge, TranscriptRead,
+    PROOF_VERSION,
+};
 
 /// Trait representing a strategy for verifying Halo 2 proofs.
 pub trait VerificationStrategy<C: CurveAffine> {
@@ -124,6 +131,16 @@ pub fn verify_proof<
     instances: &[&[&[C::Scalar]]],
     transcript: &mut T,
 ) -> Result<V::Output, Error> {
+    // Check that the parameters are for the size of circuit that the key describes,
+    // before using them for any commitments
+    if params.k != vk.domain.k() {
+        return Err(Error::CircuitMismatch {
+            field: "k",
+            expected: vk.domain.k() as usize,
+            got: params.k as usize,
+        });
+    }
+
     // Check that instances matches the expected number of instance columns
     for instances in instances.iter() {
         if instances.len() != vk.cs.num_instance_columns {
@@ -149,8 +166,17 @@ pub fn verify_proof<
 
     let num_proofs = instance_commitments.len();
 
-    // Hash verification key into transcript
-    vk.hash_into(transcript)?;
+    // Check the proof format version before interpreting the rest of the proof
+    let version = transcript.read_version()?;
+    if version != PROOF_VERSION {
+        return Err(Error::UnsupportedProofVersion(version));
+    }
+
+    // Check that the proof was created for this verification key, hashing it into
+    // the transcript
+    if transcript.read_scalar()? != vk.transcript_repr() {
+        return Err(Error::VerifyingKeyMismatch);
+    }
 
     for instance_commitments in instance_commitments.iter() {
         // Hash the instance (external) commitments into the transcript
@@ -187,9 +213,12 @@ pub fn verify_proof<
     let gamma: ChallengeGamma<_> = transcript.squeeze_challenge_scalar();
 
     let permutations_committed = (0..num_proofs)
-        .map(|_| {
+        .map(|_| -> Result<Vec<_>, _> {
             // Hash each permutation product commitment
-            vk.cs.permutation.read_product_commitments(vk, transcript)
+            vk.cs
+                .permutations()
+                .map(|argument| argument.read_product_commitments(vk, transcript))
+                .collect::<Result<Vec<_>, _>>()
         })
         .collect::<Result<Vec<_>, _>>()?;
 
@@ -226,11 +255,20 @@ pub fn verify_proof<
 
     let vanishing = vanishing.evaluate_after_x(transcript)?;
 
-    let permutations_common = vk.permutation.evaluate(transcript)?;
+    let permutations_common = vk
+        .permutations
+        .iter()
+        .map(|permutation| permutation.evaluate(transcript))
+        .collect::<Result<Vec<_>, _>>()?;
 
     let permutations_evaluated = permutations_committed
         .into_iter()
-        .map(|permutation| permutation.evaluate(transcript))
+        .map(|permutations| -> Result<Vec<_>, _> {
+            permutations
+                .into_iter()
+                .map(|permutation| permutation.evaluate(transcript))
+                .collect::<Result<Vec<_>, _>>()
+        })
         .collect::<Result<Vec<_>, _>>()?;
 
     let lookups_evaluated = lookups_committed
@@ -266,60 +304,73 @@ pub fn verify_proof<
             .zip(instance_evals.iter())
             .zip(permutations_evaluated.iter())
             .zip(lookups_evaluated.iter())
-            .flat_map(|(((advice_evals, instance_evals), permutation), lookups)| {
-                let fixed_evals = &fixed_evals;
-                std::iter::empty()
-                    // Evaluate the circuit using the custom gates provided
-                    .chain(vk.cs.gates.iter().flat_map(move |gate| {
-                        gate.polynomials().iter().map(move |poly| {
-                            poly.evaluate(
-                                &|scalar| scalar,
-                                &|_| panic!("virtual selectors are removed during optimization"),
-                                &|index, _, _| fixed_evals[index],
-                                &|index, _, _| advice_evals[index],
-                                &|index, _, _| instance_evals[index],
-                                &|a| -a,
-                                &|a, b| a + &b,
-                                &|a, b| a * &b,
-                                &|a, scalar| a * &scalar,
-                            )
-                        })
-                    }))
-                    .chain(permutation.expressions(
-                        vk,
-                        &vk.cs.permutation,
-                        &permutations_common,
-                        advice_evals,
-                        fixed_evals,
-                        instance_evals,
-                        l_0,
-                        l_last,
-                        l_blind,
-                        beta,
-                        gamma,
-                        x,
-                    ))
-                    .chain(
-                        lookups
-                            .iter()
-                            .zip(vk.cs.lookups.iter())
-                            .flat_map(move |(p, argument)| {
-                                p.expressions(
-                                    l_0,
-                                    l_last,
-                                    l_blind,
-                                    argument,
-                                    theta,
-                                    beta,
-                                    gamma,
-                                    advice_evals,
-                                    fixed_evals,
-                                    instance_evals,
+            .flat_map(
+                |(((advice_evals, instance_evals), permutations), lookups)| {
+                    let fixed_evals = &fixed_evals;
+                    let permutations_common = &permutations_common;
+                    std::iter::empty()
+                        // Evaluate the circuit using the custom gates provided
+                        .chain(vk.cs.gates.iter().flat_map(move |gate| {
+                            gate.polynomials().iter().map(move |poly| {
+                                poly.evaluate(
+                                    &|scalar| scalar,
+                                    &|_| {
+                                        panic!("virtual selectors are removed during optimization")
+                                    },
+                                    &|index, _, _| fixed_evals[index],
+                                    &|index, _, _| advice_evals[index],
+                                    &|index, _, _| instance_evals[index],
+                                    &|a| -a,
+                                    &|a, b| a + &b,
+                                    &|a, b| a * &b,
+                                    &|a, scalar| a * &scalar,
                                 )
                             })
-                            .into_iter(),
-                    )
-            });
+                        }))
+                        .chain(
+                            permutations
+                                .iter()
+                                .zip(vk.cs.permutations())
+                                .zip(permutations_common.iter())
+                                .flat_map(move |((p, argument), common)| {
+                                    p.expressions(
+                                        vk,
+                                        argument,
+                                        common,
+                                        advice_evals,
+                                        fixed_evals,
+                                        instance_evals,
+                                        l_0,
+                                        l_last,
+                                        l_blind,
+                                        beta,
+                                        gamma,
+                                        x,
+                                    )
+                                }),
+                        )
+                        .chain(
+                            lookups
+                                .iter()
+                                .zip(vk.cs.lookups.iter())
+                                .flat_map(move |(p, argument)| {
+                                    p.expressions(
+                                        l_0,
+                                        l_last,
+                                        l_blind,
+                                        argument,
+                                        theta,
+                                        beta,
+                                        gamma,
+                                        advice_evals,
+                                        fixed_evals,
+                                        instance_evals,
+                                    )
+                                })
+                                .into_iter(),
+                        )
+                },
+            );
 
         vanishing.verify(expressions, y, xn)
     };
@@ -335,7 +386,7 @@ pub fn verify_proof<
             |(
                 (
                     (((instance_commitments, instance_evals), advice_commitments), advice_evals),
-                    permutation,
+                    permutations,
                 ),
                 lookups,
             )| {
@@ -360,7 +411,7 @@ pub fn verify_proof<
                             )
                         },
                     ))
-                    .chain(permutation.queries(vk, x))
+                    .chain(permutations.iter().flat_map(move |p| p.queries(vk, x)))
                     .chain(
                         lookups
                             .iter()
@@ -383,7 +434,12 @@ pub fn verify_proof<
                     )
                 }),
         )
-        .chain(permutations_common.queries(&vk.permutation, x))
+        .chain(
+            permutations_common
+                .iter()
+                .zip(vk.permutations.iter())
+                .flat_map(move |(common, vkey)| common.queries(vkey, x)),
+        )
         .chain(vanishing.queries(x));
 
     // We are now convinced the circuit is satisfied so long as the
@@ -392,3 +448,74 @@ pub fn verify_proof<
         multiopen::verify_proof(params, transcript, queries).map_err(|_| Error::Opening)
     })
 }
+
+/// Verifies a proof produced by [`prove_to_bytes`], using a Blake2b transcript and
+/// a [`SingleVerifier`].
+///
+/// [`prove_to_bytes`]: crate::plonk::prove_to_bytes
+pub fn verify_bytes<C: MultiMillerLoop>(
+    params: &ParamsVerifier<C>,
+    vk: &VerifyingKey<C::G1Affine>,
+    instances: &[&[&[C::Scalar]]],
+    proof: &[u8],
+) -> Result<(), Error> {
+    let strategy = SingleVerifier::new(params);
+    let mut transcript = Blake2bRead::<_, _, Challenge255<_>>::init(proof);
+    verify_proof(params, vk, strategy, instances, &mut transcript)
+}
+
+/// Verifies a proof produced by [`prove_to_bytes`] as [`verify_bytes`] does, on
+/// tokio's blocking thread pool so that the calling task's executor is not stalled
+/// by the pairing checks.
+///
+/// A panic inside the verifier is resumed on the awaiting task.
+///
+/// [`prove_to_bytes`]: crate::plonk::prove_to_bytes
+#[cfg(feature = "tokio")]
+pub async fn verify_proof_async<C>(
+    params: Arc<ParamsVerifier<C>>,
+    vk: Arc<VerifyingKey<C::G1Affine>>,
+    proof: Vec<u8>,
+    instances: Vec<Vec<Vec<C::Scalar>>>,
+) -> Result<(), Error>
+where
+    C: MultiMillerLoop,
+    ParamsVerifier<C>: Send + Sync + 'static,
+    VerifyingKey<C::G1Affine>: Send + Sync + 'static,
+{
+    tokio::task::spawn_blocking(move || {
+        let instances: Vec<Vec<&[C::Scalar]>> = instances
+            .iter()
+            .map(|instance| instance.iter().map(|column| &column[..]).collect())
+            .collect();
+        let instances: Vec<&[&[C::Scalar]]> =
+            instances.iter().map(|instance| &instance[..]).collect();
+        verify_bytes(&params, &vk, &instances, &proof)
+    })
+    .await
+    .unwrap_or_else(|e| std::panic::resume_unwind(e.into_panic()))
+}
+
+/// Verifies a proof as [`verify_proof`] does, but uses `challenges` in order instead
+/// of squeezing them from the transcript, so that the verifier's algebraic checks
+/// can be tested independently of Fiat-Shamir.
+///
+/// The proof must have been created with the same challenges, by wrapping the
+/// prover's transcript in a [`FixedChallenges`].
+#[cfg(test)]
+pub(crate) fn verify_with_challenges<
+    'params,
+    C: MultiMillerLoop,
+    T: TranscriptRead<C::G1Affine, Challenge255<C::G1Affine>>,
+    V: VerificationStrategy<C::G1Affine>,
+>(
+    params: &'params ParamsVerifier<C>,
+    vk: &VerifyingKey<C::G1Affine>,
+    strategy: V,
+    instances: &[&[&[C::Scalar]]],
+    transcript: T,
+    challenges: &[C::Scalar],
+) -> Result<V::Output, Error> {
+    let mut transcript = FixedChallenges::new(transcript, challenges);
+    verify_proof(params, vk, strategy, instances, &mut transcript)
+}
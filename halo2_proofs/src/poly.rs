@@ -8,6 +8,7 @@ use crate::plonk::Assigned;
 use group::ff::{BatchInvert, Field};
 use pairing::arithmetic::FieldExt;
 use std::fmt::Debug;
+use std::iter;
 use std::marker::PhantomData;
 use std::ops::{Add, Deref, DerefMut, Index, IndexMut, Mul, RangeFrom, RangeFull, Sub};
 
@@ -132,6 +133,43 @@ impl<F, B> Polynomial<F, B> {
     }
 }
 
+/// Returns the evaluations $L_i(x)$ at `x` of every Lagrange basis polynomial over
+/// the domain of size $n = 2^k$, for $i$ from $0$ to $n - 1$.
+///
+/// This uses the barycentric form
+///     $$L_i(x) = \frac{\omega^i (x^n - 1)}{n (x - \omega^i)},$$
+/// so it only needs a single batch inversion. If `x` lies in the domain, the result
+/// is `1` at its position and `0` everywhere else.
+pub fn lagrange_evals<F: FieldExt>(k: u32, x: F) -> Vec<F> {
+    let n = 1usize << k;
+
+    // omega = root_of_unity^{2^(S - k)} is a primitive n'th root of unity.
+    let mut omega = F::root_of_unity();
+    for _ in k..F::S {
+        omega = omega.square();
+    }
+    let omega_powers: Vec<F> = iter::successors(Some(F::one()), |w| Some(*w * omega))
+        .take(n)
+        .collect();
+
+    let xn_minus_one = x.pow_vartime(&[n as u64]) - F::one();
+    if xn_minus_one.is_zero_vartime() {
+        return omega_powers
+            .iter()
+            .map(|w| if *w == x { F::one() } else { F::zero() })
+            .collect();
+    }
+
+    let mut denominators: Vec<F> = omega_powers.iter().map(|w| x - w).collect();
+    denominators.iter_mut().batch_invert();
+    let common = xn_minus_one * F::from(n as u64).invert().unwrap();
+    omega_powers
+        .into_iter()
+        .zip(denominators.into_iter())
+        .map(|(w, d)| w * d * common)
+        .collect()
+}
+
 pub(crate) fn batch_invert_assigned<F: FieldExt>(
     assigned: Vec<Polynomial<Assigned<F>, LagrangeCoeff>>,
 ) -> Vec<Polynomial<F, LagrangeCoeff>> {
@@ -253,8 +291,22 @@ impl<'a, F: Field, B: Basis> Mul<F> for Polynomial<F, B> {
 /// reverse (leftmost) rotations and positive numbers represent forward (rightmost)
 /// rotations. Zero represents no rotation.
 #[derive(Copy, Clone, Debug, PartialEq, PartialOrd, Eq, Ord)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Rotation(pub i32);
 
+impl std::fmt::Display for Rotation {
+    /// Renders the rotation as `@cur`, `@prev` or `@next` for the common offsets,
+    /// and as `@{n}` (for example `@5` or `@-2`) otherwise.
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.0 {
+            0 => write!(f, "@cur"),
+            -1 => write!(f, "@prev"),
+            1 => write!(f, "@next"),
+            n => write!(f, "@{}", n),
+        }
+    }
+}
+
 impl Rotation {
     /// The current location in the evaluation domain
     pub fn cur() -> Rotation {
@@ -271,3 +323,49 @@ impl Rotation {
         Rotation(1)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use group::ff::Field;
+    use pairing::bn256::Fr as Fp;
+
+    use super::{lagrange_evals, EvaluationDomain, Rotation};
+    use crate::{arithmetic::eval_polynomial, dev::test_rng};
+
+    #[test]
+    fn lagrange_evals_interpolate_instance() {
+        const K: u32 = 3;
+        let mut rng = test_rng();
+
+        let domain = EvaluationDomain::<Fp>::new(1, K);
+        let mut instance = domain.empty_lagrange();
+        for value in instance.iter_mut() {
+            *value = Fp::random(&mut rng);
+        }
+        let x = Fp::random(&mut rng);
+
+        let evals = lagrange_evals(K, x);
+        assert_eq!(evals.len(), 1 << K);
+        let barycentric = instance
+            .iter()
+            .zip(evals.iter())
+            .fold(Fp::zero(), |acc, (value, l_i)| acc + *value * l_i);
+        let coeffs = domain.lagrange_to_coeff(instance);
+        assert_eq!(barycentric, eval_polynomial(&coeffs[..], x));
+
+        // At a point of the domain, only the matching basis polynomial is non-zero.
+        let omega = domain.get_omega();
+        let mut expected = vec![Fp::zero(); 1 << K];
+        expected[2] = Fp::one();
+        assert_eq!(lagrange_evals(K, omega * omega), expected);
+    }
+
+    #[test]
+    fn rotation_display() {
+        assert_eq!(Rotation::cur().to_string(), "@cur");
+        assert_eq!(Rotation::next().to_string(), "@next");
+        assert_eq!(Rotation(-1).to_string(), "@prev");
+        assert_eq!(Rotation(5).to_string(), "@5");
+        assert_eq!(Rotation(-2).to_string(), "@-2");
+    }
+}
@@ -5,10 +5,13 @@
 
 use super::{Coeff, LagrangeCoeff, Polynomial, MSM};
 use crate::arithmetic::{
-    best_fft, best_multiexp, parallelize, CurveAffine, CurveExt, Engine, FieldExt, Group,
+    batch_to_affine, best_multiexp, g_to_lagrange, parallelize, CurveAffine, CurveExt, Engine,
+    FieldExt, Group,
 };
 use crate::helpers::CurveRead;
+use crate::plonk::Error;
 
+use blake2b_simd::Params as Blake2bParams;
 use ff::{Field, PrimeField};
 use group::{prime::PrimeCurveAffine, Curve, Group as _, GroupEncoding};
 use rand_core::OsRng;
@@ -54,6 +57,24 @@ impl<C: CurveAffine> Params<C> {
     /// MUST NOT be used in production
     pub fn unsafe_setup<E: Engine>(k: u32) -> Params<E::G1Affine> {
         // TODO: Make this function only available in test mod
+        Self::unsafe_setup_with_toxic_scalar::<E>(k, E::Scalar::random(OsRng))
+    }
+
+    /// Initializes parameters for the curve deterministically from `seed`, which is
+    /// hashed to derive the toxic scalar.
+    ///
+    /// Anyone who knows the seed can forge proofs against these parameters, so this
+    /// MUST NOT be used in production; it is intended for reproducible tests.
+    pub fn unsafe_setup_from_seed<E: Engine>(k: u32, seed: [u8; 32]) -> Params<E::G1Affine> {
+        let hash = Blake2bParams::new()
+            .hash_length(64)
+            .personal(b"Halo2-Setup-Seed")
+            .hash(&seed);
+        let s = E::Scalar::from_bytes_wide(hash.as_array());
+        Self::unsafe_setup_with_toxic_scalar::<E>(k, s)
+    }
+
+    fn unsafe_setup_with_toxic_scalar<E: Engine>(k: u32, s: E::Scalar) -> Params<E::G1Affine> {
         // Largest root of unity exponent of the Engine is `2^E::Scalar::S`, so we can
         // only support FFTs of polynomials below degree `2^E::Scalar::S`.
         assert!(k <= E::Scalar::S);
@@ -61,7 +82,6 @@ impl<C: CurveAffine> Params<C> {
 
         // Calculate g = [G1, [s] G1, [s^2] G1, ..., [s^(n-1)] G1] in parallel.
         let g1 = <E::G1Affine as PrimeCurveAffine>::generator();
-        let s = E::Scalar::random(OsRng);
 
         let mut g_projective = vec![E::G1::group_zero(); n as usize];
         parallelize(&mut g_projective, |g, start| {
@@ -81,24 +101,8 @@ impl<C: CurveAffine> Params<C> {
             g
         };
 
-        let mut g_lagrange_projective = vec![E::G1::group_zero(); n as usize];
-        let mut root = E::Scalar::ROOT_OF_UNITY_INV.invert().unwrap();
-        for _ in k..E::Scalar::S {
-            root = root.square();
-        }
-        let n_inv = Option::<E::Scalar>::from(E::Scalar::from(n).invert())
-            .expect("inversion should be ok for n = 1<<k");
-        let multiplier = (s.pow_vartime(&[n as u64]) - E::Scalar::one()) * n_inv;
-        parallelize(&mut g_lagrange_projective, |g, start| {
-            for (idx, g) in g.iter_mut().enumerate() {
-                let offset = start + idx;
-                let root_pow = root.pow_vartime(&[offset as u64]);
-                let scalar = multiplier * root_pow * (s - root_pow).invert().unwrap();
-                *g = g1 * scalar;
-            }
-        });
-
         let g_lagrange = {
+            let g_lagrange_projective = g_to_lagrange::<E::G1Affine>(g_projective, k);
             let mut g_lagrange = vec![E::G1Affine::identity(); n as usize];
             parallelize(&mut g_lagrange, |g_lagrange, starts| {
                 E::G1::batch_normalize(
@@ -122,6 +126,31 @@ impl<C: CurveAffine> Params<C> {
         }
     }
 
+    /// Truncates these parameters in place to support polynomials over the $2^k$
+    /// size evaluation domain, so that parameters generated for a large circuit can
+    /// be reused for smaller ones. The Lagrange basis is recomputed for the smaller
+    /// domain.
+    ///
+    /// Returns [`Error::NotEnoughRowsAvailable`] if `k` exceeds the size of these
+    /// parameters.
+    pub fn downsize(&mut self, k: u32) -> Result<(), Error> {
+        if k > self.k {
+            return Err(Error::not_enough_rows_available(self.k));
+        }
+        if k == self.k {
+            return Ok(());
+        }
+
+        let n = 1u64 << k;
+        self.g.truncate(n as usize);
+        let g_projective = self.g.iter().map(|g| g.to_curve()).collect();
+        self.g_lagrange = batch_to_affine(&g_to_lagrange::<C>(g_projective, k));
+        self.k = k;
+        self.n = n;
+
+        Ok(())
+    }
+
     /// This computes a commitment to a polynomial described by the provided
     /// slice of coefficients. The commitment will be blinded by the blinding
     /// factor `r`.
@@ -12,6 +12,52 @@ use group::ff::{BatchInvert, Field, PrimeField};
 
 use std::marker::PhantomData;
 
+/// Given a slice of group elements `[a_0, a_1, a_2, ...]`, this sets it to
+/// `[a_0, [c]a_1, [c^2]a_2, ...]`.
+fn distribute_powers<G: Group>(a: &mut [G], c: G::Scalar) {
+    parallelize(a, |a, index| {
+        let mut c_power = c.pow_vartime(&[index as u64, 0, 0, 0]);
+        for a in a {
+            a.group_scale(&c_power);
+            c_power *= &c;
+        }
+    });
+}
+
+/// Evaluates the polynomial with coefficients `a` over the coset
+/// $\text{shift} \cdot \langle \omega \rangle$ of the $2^{\text{log\_n}}$ size
+/// domain, in place: afterwards `a[i]` holds the evaluation at
+/// $\text{shift} \cdot \omega^i$.
+///
+/// Evaluating over a coset keeps the evaluations away from the roots of the
+/// vanishing polynomial, which is what the prover needs to divide by it.
+pub fn coset_fft<G: Group>(a: &mut [G], omega: G::Scalar, log_n: u32, shift: G::Scalar) {
+    distribute_powers(a, shift);
+    best_fft(a, omega, log_n);
+}
+
+/// Interpolates the evaluations `a` over the coset
+/// $\text{shift} \cdot \langle \omega \rangle$ back into coefficients, in place,
+/// undoing [`coset_fft`]. This takes the inverses of the `omega` and `shift` that
+/// were passed to [`coset_fft`], and `divisor` must be the inverse of
+/// $2^{\text{log\_n}}$, so that callers can precompute it.
+pub fn coset_ifft<G: Group>(
+    a: &mut [G],
+    omega_inv: G::Scalar,
+    log_n: u32,
+    shift_inv: G::Scalar,
+    divisor: G::Scalar,
+) {
+    best_fft(a, omega_inv, log_n);
+    parallelize(a, |a, index| {
+        let mut c_power = divisor * shift_inv.pow_vartime(&[index as u64, 0, 0, 0]);
+        for a in a {
+            a.group_scale(&c_power);
+            c_power *= &shift_inv;
+        }
+    });
+}
+
 /// This structure contains precomputed constants and other details needed for
 /// performing operations on an evaluation domain of size $2^k$ and an extended
 /// domain of size $2^{k} * j$ with $j \neq 0$.
@@ -555,3 +601,48 @@ fn test_l_i() {
         assert_eq!(eval_polynomial(&l[(8 - i) % 8][..], x), evaluations[7 - i]);
     }
 }
+
+#[test]
+fn test_coset_fft() {
+    use crate::arithmetic::eval_polynomial;
+    use crate::dev::test_rng;
+    use pairing::bn256::Fr as Scalar;
+
+    let k = 4;
+    let domain = EvaluationDomain::<Scalar>::new(1, k);
+    let mut rng = test_rng();
+    let shift = Scalar::ZETA;
+
+    let coeffs: Vec<Scalar> = (0..1 << k).map(|_| Scalar::random(&mut rng)).collect();
+    let mut evals = coeffs.clone();
+    coset_fft(&mut evals, domain.omega, k, shift);
+
+    // The evaluations are at the points of the coset.
+    let mut point = shift;
+    for eval in &evals {
+        assert_eq!(*eval, eval_polynomial(&coeffs, point));
+        point *= domain.omega;
+    }
+
+    coset_ifft(
+        &mut evals,
+        domain.omega_inv,
+        k,
+        shift.invert().unwrap(),
+        domain.ifft_divisor,
+    );
+    assert_eq!(evals, coeffs);
+
+    // Over the extended domain's coset, this agrees with coeff_to_extended.
+    let domain = EvaluationDomain::<Scalar>::new(3, k);
+    let mut evals = coeffs.clone();
+    evals.resize(domain.extended_len(), Scalar::zero());
+    coset_fft(
+        &mut evals,
+        domain.extended_omega,
+        domain.extended_k,
+        domain.g_coset,
+    );
+    let extended = domain.coeff_to_extended(domain.coeff_from_vec(coeffs));
+    assert_eq!(evals, extended.values);
+}
@@ -11,6 +11,7 @@ use crate::{
 };
 
 use crate::poly::Rotation;
+use crate::transcript::{EncodedChallenge, TranscriptRead};
 use ff::Field;
 use group::Group;
 use rand::RngCore;
@@ -61,6 +62,35 @@ impl<E: MultiMillerLoop> Decider<E> {
     }
 }
 
+/// Verifies a multi-point opening proof of all the `queries` at once, and performs
+/// the final pairing check.
+///
+/// The openings are folded together with random challenges drawn from the
+/// transcript, as in [`verify_proof`], so a single pairing check covers every
+/// commitment. Returns [`Error::OpeningError`] if any of the openings is invalid.
+pub fn verify_batch_openings<
+    'r,
+    'params: 'r,
+    I,
+    C: MultiMillerLoop,
+    E: EncodedChallenge<C::G1Affine>,
+    T: TranscriptRead<C::G1Affine, E>,
+>(
+    params: &'params ParamsVerifier<C>,
+    queries: I,
+    transcript: &mut T,
+) -> Result<(), Error>
+where
+    I: IntoIterator<Item = VerifierQuery<'r, C::G1Affine>> + Clone,
+{
+    let msm = verify_proof(params, transcript, queries)?;
+    if Decider::verify(params, msm) {
+        Ok(())
+    } else {
+        Err(Error::OpeningError)
+    }
+}
+
 #[doc(hidden)]
 #[derive(Debug, Clone, Copy)]
 pub struct ProverQuery<'a, C: CurveAffine> {
@@ -278,6 +308,83 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_verify_batch_openings() {
+        use group::Curve;
+
+        use super::verify_batch_openings;
+        use crate::{dev::test_rng, poly::EvaluationDomain};
+
+        const K: u32 = 4;
+
+        let params = Params::<G1Affine>::unsafe_setup::<Bn256>(K);
+        let params_verifier: ParamsVerifier<Bn256> = params.verifier(0).unwrap();
+        let domain = EvaluationDomain::<Fr>::new(1, K);
+        let mut rng = test_rng();
+
+        let polys = (0..4)
+            .map(|_| rand_poly(params.n as usize, &mut rng))
+            .collect::<Vec<_>>();
+        let commitments = polys
+            .iter()
+            .map(|poly| params.commit(poly).to_affine())
+            .collect::<Vec<_>>();
+
+        // Open every polynomial at a shared point, and the last one at the next row too.
+        let x = Fr::random(&mut rng);
+        let y = domain.rotate_omega(x, Rotation::next());
+        let openings = polys
+            .iter()
+            .map(|poly| (x, Rotation::cur(), poly))
+            .chain(Some((y, Rotation::next(), &polys[3])))
+            .collect::<Vec<_>>();
+        let commitment_indices = [0, 1, 2, 3, 3];
+
+        let mut transcript = Blake2bWrite::<_, _, Challenge255<_>>::init(vec![]);
+        create_proof(
+            &params,
+            &mut transcript,
+            openings
+                .iter()
+                .map(|(point, rotation, poly)| ProverQuery {
+                    point: *point,
+                    rotation: *rotation,
+                    poly,
+                })
+                .collect::<Vec<_>>(),
+        )
+        .unwrap();
+        let proof = transcript.finalize();
+
+        let verify = |tampered: Option<usize>| {
+            let queries = openings
+                .iter()
+                .zip(commitment_indices.iter())
+                .enumerate()
+                .map(|(i, ((point, rotation, poly), commitment))| {
+                    let mut eval = eval_polynomial(poly, *point);
+                    if tampered == Some(i) {
+                        eval += Fr::one();
+                    }
+                    VerifierQuery::new_commitment(
+                        &commitments[*commitment],
+                        *point,
+                        *rotation,
+                        eval,
+                    )
+                })
+                .collect::<Vec<_>>();
+
+            let mut transcript = Blake2bRead::<_, _, Challenge255<_>>::init(&proof[..]);
+            verify_batch_openings(&params_verifier, queries, &mut transcript)
+        };
+
+        assert!(verify(None).is_ok());
+        for i in 0..openings.len() {
+            assert!(verify(Some(i)).is_err());
+        }
+    }
+
     #[test]
     fn test_multiopen() {
         const K: u32 = 3;
@@ -2,7 +2,7 @@
 //! transcripts.
 
 use blake2b_simd::{Params as Blake2bParams, State as Blake2bState};
-use group::ff::PrimeField;
+use group::ff::{Field, PrimeField};
 use std::convert::TryInto;
 
 use crate::arithmetic::{BaseExt, Coordinates, CurveAffine, FieldExt};
@@ -19,6 +19,28 @@ const BLAKE2B_PREFIX_POINT: u8 = 1;
 /// Prefix to a prover's message containing a scalar
 const BLAKE2B_PREFIX_SCALAR: u8 = 2;
 
+/// Prefix to a prover's message containing the proof format version
+const BLAKE2B_PREFIX_VERSION: u8 = 3;
+
+/// Prefix to a common input consisting of arbitrary bytes
+const BLAKE2B_PREFIX_BYTES: u8 = 4;
+
+/// Prefix to a common input consisting of a length-prefixed vector of scalars
+const BLAKE2B_PREFIX_SCALAR_VEC: u8 = 5;
+
+/// Prefix to the protocol domain string absorbed when a transcript is initialized
+const BLAKE2B_PREFIX_DOMAIN: u8 = 6;
+
+/// Prefix to a common input consisting of a length-prefixed vector of curve points
+const BLAKE2B_PREFIX_POINT_VEC: u8 = 7;
+
+/// The version of the proof format produced by this crate's prover.
+///
+/// It is written as the first byte of every proof, and verifiers reject proofs
+/// with any other version. Version 2 proofs declare the digest of their
+/// verification key after the version.
+pub const PROOF_VERSION: u8 = 2;
+
 /// Generic transcript view (from either the prover or verifier's perspective)
 pub trait Transcript<C: CurveAffine, E: EncodedChallenge<C>> {
     /// Squeeze an encoded verifier challenge from the transcript.
@@ -32,6 +54,44 @@ pub trait Transcript<C: CurveAffine, E: EncodedChallenge<C>> {
         }
     }
 
+    /// Returns the number of bytes that a curve point occupies in the proof.
+    fn point_bytes() -> usize
+    where
+        Self: Sized,
+    {
+        C::Repr::default().as_ref().len()
+    }
+
+    /// Returns the number of bytes that a scalar occupies in the proof.
+    fn scalar_bytes() -> usize
+    where
+        Self: Sized,
+    {
+        <C::Scalar as PrimeField>::Repr::default().as_ref().len()
+    }
+
+    /// Returns the number of bytes that the proof format version occupies in the
+    /// proof.
+    ///
+    /// The default implementation matches the default
+    /// [`TranscriptWrite::write_version`], which writes the version as a scalar.
+    fn version_bytes() -> usize
+    where
+        Self: Sized,
+    {
+        Self::scalar_bytes()
+    }
+
+    /// Returns the most recently squeezed challenge without advancing the
+    /// transcript, or `None` if anything has been absorbed since (or nothing has
+    /// been squeezed yet).
+    ///
+    /// The default implementation does not track challenges and always returns
+    /// `None`.
+    fn current_challenge(&self) -> Option<E> {
+        None
+    }
+
     /// Writing the point to the transcript without writing it to the proof,
     /// treating it as a common input.
     fn common_point(&mut self, point: C) -> io::Result<()>;
@@ -39,11 +99,93 @@ pub trait Transcript<C: CurveAffine, E: EncodedChallenge<C>> {
     /// Writing the scalar to the transcript without writing it to the proof,
     /// treating it as a common input.
     fn common_scalar(&mut self, scalar: C::Scalar) -> io::Result<()>;
+
+    /// Writing arbitrary bytes to the transcript without writing them to the proof,
+    /// treating them as a common input. The bytes are prefixed with their length, so
+    /// that consecutive calls cannot be confused with a single call on the
+    /// concatenated bytes.
+    ///
+    /// The default implementation absorbs the length and then each 8-byte chunk
+    /// of `data` as scalars.
+    fn common_bytes(&mut self, data: &[u8]) -> io::Result<()> {
+        self.common_scalar(C::Scalar::from(data.len() as u64))?;
+        for chunk in data.chunks(8) {
+            let mut bytes = [0u8; 8];
+            bytes[..chunk.len()].copy_from_slice(chunk);
+            self.common_scalar(C::Scalar::from(u64::from_le_bytes(bytes)))?;
+        }
+        Ok(())
+    }
+
+    /// Writing a slice of scalars to the transcript without writing it to the proof,
+    /// treating it as a single common input. The slice is absorbed as one block,
+    /// tagged once and prefixed with its length, rather than as one
+    /// [`Transcript::common_scalar`] call per element.
+    ///
+    /// The default implementation absorbs the length and then each scalar with
+    /// [`Transcript::common_scalar`].
+    fn common_scalar_slice(&mut self, scalars: &[C::Scalar]) -> io::Result<()> {
+        self.common_scalar(C::Scalar::from(scalars.len() as u64))?;
+        for scalar in scalars {
+            self.common_scalar(*scalar)?;
+        }
+        Ok(())
+    }
+
+    /// Writing a slice of points to the transcript without writing it to the proof,
+    /// treating it as a single common input. The slice is absorbed as one block,
+    /// tagged once and prefixed with its length, rather than as one
+    /// [`Transcript::common_point`] call per element.
+    ///
+    /// If a point cannot be absorbed (for example, because it is the point at
+    /// infinity), the returned error names the index of that point within `points`,
+    /// so that the caller can locate the offending commitment.
+    ///
+    /// The default implementation absorbs the length with
+    /// [`Transcript::common_scalar`] and then each point with
+    /// [`Transcript::common_point`].
+    fn common_points(&mut self, points: &[C]) -> io::Result<()> {
+        self.common_scalar(C::Scalar::from(points.len() as u64))?;
+        for (index, point) in points.iter().enumerate() {
+            self.common_point(*point).map_err(|e| {
+                io::Error::new(e.kind(), format!("point at index {}: {}", index, e))
+            })?;
+        }
+        Ok(())
+    }
+
+    /// Writing a previously squeezed challenge back to the transcript, so that it
+    /// influences later challenges. The challenge is absorbed as the scalar it
+    /// represents.
+    fn common_challenge(&mut self, challenge: &E) -> io::Result<()> {
+        self.common_scalar(challenge.get_scalar())
+    }
 }
 
 /// Transcript view from the perspective of a verifier that has access to an
 /// input stream of data from the prover to the verifier.
 pub trait TranscriptRead<C: CurveAffine, E: EncodedChallenge<C>>: Transcript<C, E> {
+    /// Read the proof format version byte from the prover.
+    ///
+    /// The default implementation reads the version as a scalar, matching the
+    /// default [`TranscriptWrite::write_version`]. A scalar that does not encode a
+    /// single byte is rejected with an error that the verifier reports as
+    /// [`Error::UnsupportedProofVersion`].
+    ///
+    /// [`Error::UnsupportedProofVersion`]: crate::plonk::Error::UnsupportedProofVersion
+    fn read_version(&mut self) -> io::Result<u8> {
+        let repr = self.read_scalar()?.to_repr();
+        let (version, rest) = repr.as_ref().split_first().unwrap();
+        if rest.iter().all(|byte| *byte == 0) {
+            Ok(*version)
+        } else {
+            Err(io::Error::new(
+                io::ErrorKind::Other,
+                UnsupportedVersion(*version),
+            ))
+        }
+    }
+
     /// Read a curve point from the prover.
     fn read_point(&mut self) -> io::Result<C>;
 
@@ -54,6 +196,14 @@ pub trait TranscriptRead<C: CurveAffine, E: EncodedChallenge<C>>: Transcript<C,
 /// Transcript view from the perspective of a prover that has access to an
 /// output stream of messages from the prover to the verifier.
 pub trait TranscriptWrite<C: CurveAffine, E: EncodedChallenge<C>>: Transcript<C, E> {
+    /// Write the proof format version byte to the proof and the transcript.
+    ///
+    /// The default implementation writes the version as a scalar; transcripts
+    /// that can write a single byte should override it.
+    fn write_version(&mut self, version: u8) -> io::Result<()> {
+        self.write_scalar(C::Scalar::from(version as u64))
+    }
+
     /// Write a curve point to the proof and the transcript.
     fn write_point(&mut self, point: C) -> io::Result<()>;
 
@@ -66,6 +216,7 @@ pub trait TranscriptWrite<C: CurveAffine, E: EncodedChallenge<C>>: Transcript<C,
 pub struct Blake2bRead<R: Read, C: CurveAffine, E: EncodedChallenge<C>> {
     state: Blake2bState,
     reader: R,
+    last_challenge: Option<E>,
     _marker: PhantomData<(C, E)>,
 }
 
@@ -78,14 +229,38 @@ impl<R: Read, C: CurveAffine, E: EncodedChallenge<C>> Blake2bRead<R, C, E> {
                 .personal(b"Halo2-Transcript")
                 .to_state(),
             reader,
+            last_challenge: None,
             _marker: PhantomData,
         }
     }
+
+    /// Initialize a transcript given an input buffer, domain-separated by the
+    /// given protocol `domain` string. Transcripts initialized with different
+    /// domains never produce the same challenges, even given identical proof data.
+    pub fn init_with_domain(reader: R, domain: &[u8]) -> Self {
+        let mut transcript = Self::init(reader);
+        transcript.state.update(&[BLAKE2B_PREFIX_DOMAIN]);
+        transcript
+            .state
+            .update(&(domain.len() as u64).to_le_bytes());
+        transcript.state.update(domain);
+        transcript
+    }
 }
 
 impl<R: Read, C: CurveAffine> TranscriptRead<C, Challenge255<C>>
     for Blake2bRead<R, C, Challenge255<C>>
 {
+    fn read_version(&mut self) -> io::Result<u8> {
+        let mut version = [0u8; 1];
+        self.reader.read_exact(&mut version)?;
+        self.last_challenge = None;
+        self.state.update(&[BLAKE2B_PREFIX_VERSION]);
+        self.state.update(&version);
+
+        Ok(version[0])
+    }
+
     fn read_point(&mut self) -> io::Result<C> {
         let mut compressed = C::Repr::default();
         self.reader.read_exact(compressed.as_mut())?;
@@ -106,7 +281,11 @@ impl<R: Read, C: CurveAffine> TranscriptRead<C, Challenge255<C>>
                 "invalid field element encoding in proof",
             )
         })?;
-        self.common_scalar(scalar)?;
+        // `from_repr` only accepts canonical encodings, so the bytes read from the
+        // proof are exactly the bytes the prover absorbed.
+        self.last_challenge = None;
+        self.state.update(&[BLAKE2B_PREFIX_SCALAR]);
+        self.state.update(data.as_ref());
 
         Ok(scalar)
     }
@@ -115,14 +294,25 @@ impl<R: Read, C: CurveAffine> TranscriptRead<C, Challenge255<C>>
 impl<R: Read, C: CurveAffine> Transcript<C, Challenge255<C>>
     for Blake2bRead<R, C, Challenge255<C>>
 {
+    fn version_bytes() -> usize {
+        1
+    }
+
     fn squeeze_challenge(&mut self) -> Challenge255<C> {
         self.state.update(&[BLAKE2B_PREFIX_CHALLENGE]);
         let hasher = self.state.clone();
         let result: [u8; 64] = hasher.finalize().as_bytes().try_into().unwrap();
-        Challenge255::<C>::new(&result)
+        let challenge = Challenge255::<C>::new(&result);
+        self.last_challenge = Some(challenge);
+        challenge
+    }
+
+    fn current_challenge(&self) -> Option<Challenge255<C>> {
+        self.last_challenge
     }
 
     fn common_point(&mut self, point: C) -> io::Result<()> {
+        self.last_challenge = None;
         self.state.update(&[BLAKE2B_PREFIX_POINT]);
         let coords: Coordinates<C> = Option::from(point.coordinates()).ok_or_else(|| {
             io::Error::new(
@@ -137,11 +327,53 @@ impl<R: Read, C: CurveAffine> Transcript<C, Challenge255<C>>
     }
 
     fn common_scalar(&mut self, scalar: C::Scalar) -> io::Result<()> {
+        self.last_challenge = None;
         self.state.update(&[BLAKE2B_PREFIX_SCALAR]);
         self.state.update(scalar.to_repr().as_ref());
 
         Ok(())
     }
+
+    fn common_bytes(&mut self, data: &[u8]) -> io::Result<()> {
+        self.last_challenge = None;
+        self.state.update(&[BLAKE2B_PREFIX_BYTES]);
+        self.state.update(&(data.len() as u64).to_le_bytes());
+        self.state.update(data);
+
+        Ok(())
+    }
+
+    fn common_scalar_slice(&mut self, scalars: &[C::Scalar]) -> io::Result<()> {
+        self.last_challenge = None;
+        self.state.update(&[BLAKE2B_PREFIX_SCALAR_VEC]);
+        self.state.update(&(scalars.len() as u64).to_le_bytes());
+        for scalar in scalars {
+            self.state.update(scalar.to_repr().as_ref());
+        }
+
+        Ok(())
+    }
+
+    fn common_points(&mut self, points: &[C]) -> io::Result<()> {
+        self.last_challenge = None;
+        self.state.update(&[BLAKE2B_PREFIX_POINT_VEC]);
+        self.state.update(&(points.len() as u64).to_le_bytes());
+        for (index, point) in points.iter().enumerate() {
+            let coords: Coordinates<C> = Option::from(point.coordinates()).ok_or_else(|| {
+                io::Error::new(
+                    io::ErrorKind::Other,
+                    format!(
+                        "point at index {}: cannot write points at infinity to the transcript",
+                        index
+                    ),
+                )
+            })?;
+            coords.x().write(&mut self.state)?;
+            coords.y().write(&mut self.state)?;
+        }
+
+        Ok(())
+    }
 }
 
 /// We will replace BLAKE2b with an algebraic hash function in a later version.
@@ -149,6 +381,7 @@ impl<R: Read, C: CurveAffine> Transcript<C, Challenge255<C>>
 pub struct Blake2bWrite<W: Write, C: CurveAffine, E: EncodedChallenge<C>> {
     state: Blake2bState,
     writer: W,
+    last_challenge: Option<E>,
     _marker: PhantomData<(C, E)>,
 }
 
@@ -161,10 +394,24 @@ impl<W: Write, C: CurveAffine, E: EncodedChallenge<C>> Blake2bWrite<W, C, E> {
                 .personal(b"Halo2-Transcript")
                 .to_state(),
             writer,
+            last_challenge: None,
             _marker: PhantomData,
         }
     }
 
+    /// Initialize a transcript given an output buffer, domain-separated by the
+    /// given protocol `domain` string. Transcripts initialized with different
+    /// domains never produce the same challenges, even given identical proof data.
+    pub fn init_with_domain(writer: W, domain: &[u8]) -> Self {
+        let mut transcript = Self::init(writer);
+        transcript.state.update(&[BLAKE2B_PREFIX_DOMAIN]);
+        transcript
+            .state
+            .update(&(domain.len() as u64).to_le_bytes());
+        transcript.state.update(domain);
+        transcript
+    }
+
     /// Conclude the interaction and return the output buffer (writer).
     pub fn finalize(self) -> W {
         // TODO: handle outstanding scalars? see issue #138
@@ -175,14 +422,24 @@ impl<W: Write, C: CurveAffine, E: EncodedChallenge<C>> Blake2bWrite<W, C, E> {
 impl<W: Write, C: CurveAffine> TranscriptWrite<C, Challenge255<C>>
     for Blake2bWrite<W, C, Challenge255<C>>
 {
+    fn write_version(&mut self, version: u8) -> io::Result<()> {
+        self.last_challenge = None;
+        self.state.update(&[BLAKE2B_PREFIX_VERSION]);
+        self.state.update(&[version]);
+        self.writer.write_all(&[version])
+    }
+
     fn write_point(&mut self, point: C) -> io::Result<()> {
         self.common_point(point)?;
         let compressed = point.to_bytes();
         self.writer.write_all(compressed.as_ref())
     }
     fn write_scalar(&mut self, scalar: C::Scalar) -> io::Result<()> {
-        self.common_scalar(scalar)?;
+        // Absorb exactly the canonical bytes that are written to the proof.
         let data = scalar.to_repr();
+        self.last_challenge = None;
+        self.state.update(&[BLAKE2B_PREFIX_SCALAR]);
+        self.state.update(data.as_ref());
         self.writer.write_all(data.as_ref())
     }
 }
@@ -190,14 +447,25 @@ impl<W: Write, C: CurveAffine> TranscriptWrite<C, Challenge255<C>>
 impl<W: Write, C: CurveAffine> Transcript<C, Challenge255<C>>
     for Blake2bWrite<W, C, Challenge255<C>>
 {
+    fn version_bytes() -> usize {
+        1
+    }
+
     fn squeeze_challenge(&mut self) -> Challenge255<C> {
         self.state.update(&[BLAKE2B_PREFIX_CHALLENGE]);
         let hasher = self.state.clone();
         let result: [u8; 64] = hasher.finalize().as_bytes().try_into().unwrap();
-        Challenge255::<C>::new(&result)
+        let challenge = Challenge255::<C>::new(&result);
+        self.last_challenge = Some(challenge);
+        challenge
+    }
+
+    fn current_challenge(&self) -> Option<Challenge255<C>> {
+        self.last_challenge
     }
 
     fn common_point(&mut self, point: C) -> io::Result<()> {
+        self.last_challenge = None;
         self.state.update(&[BLAKE2B_PREFIX_POINT]);
         let coords: Coordinates<C> = Option::from(point.coordinates()).ok_or_else(|| {
             io::Error::new(
@@ -212,11 +480,331 @@ impl<W: Write, C: CurveAffine> Transcript<C, Challenge255<C>>
     }
 
     fn common_scalar(&mut self, scalar: C::Scalar) -> io::Result<()> {
+        self.last_challenge = None;
         self.state.update(&[BLAKE2B_PREFIX_SCALAR]);
         self.state.update(scalar.to_repr().as_ref());
 
         Ok(())
     }
+
+    fn common_bytes(&mut self, data: &[u8]) -> io::Result<()> {
+        self.last_challenge = None;
+        self.state.update(&[BLAKE2B_PREFIX_BYTES]);
+        self.state.update(&(data.len() as u64).to_le_bytes());
+        self.state.update(data);
+
+        Ok(())
+    }
+
+    fn common_scalar_slice(&mut self, scalars: &[C::Scalar]) -> io::Result<()> {
+        self.last_challenge = None;
+        self.state.update(&[BLAKE2B_PREFIX_SCALAR_VEC]);
+        self.state.update(&(scalars.len() as u64).to_le_bytes());
+        for scalar in scalars {
+            self.state.update(scalar.to_repr().as_ref());
+        }
+
+        Ok(())
+    }
+
+    fn common_points(&mut self, points: &[C]) -> io::Result<()> {
+        self.last_challenge = None;
+        self.state.update(&[BLAKE2B_PREFIX_POINT_VEC]);
+        self.state.update(&(points.len() as u64).to_le_bytes());
+        for (index, point) in points.iter().enumerate() {
+            let coords: Coordinates<C> = Option::from(point.coordinates()).ok_or_else(|| {
+                io::Error::new(
+                    io::ErrorKind::Other,
+                    format!(
+                        "point at index {}: cannot write points at infinity to the transcript",
+                        index
+                    ),
+                )
+            })?;
+            coords.x().write(&mut self.state)?;
+            coords.y().write(&mut self.state)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// The error carried by the [`io::Error`] that [`LimitedRead`] returns once its
+/// budget is exhausted. It is converted into [`Error::ProofTooLarge`].
+///
+/// [`Error::ProofTooLarge`]: crate::plonk::Error::ProofTooLarge
+#[derive(Debug)]
+pub(crate) struct ProofTooLarge;
+
+impl std::fmt::Display for ProofTooLarge {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "proof exceeds the maximum size")
+    }
+}
+
+impl std::error::Error for ProofTooLarge {}
+
+/// The error carried by the [`io::Error`] that [`TranscriptRead::read_version`]
+/// returns when the proof's version does not fit in a byte. It holds the version's
+/// least significant byte, and is converted into [`Error::UnsupportedProofVersion`].
+///
+/// [`Error::UnsupportedProofVersion`]: crate::plonk::Error::UnsupportedProofVersion
+#[derive(Debug)]
+pub(crate) struct UnsupportedVersion(pub(crate) u8);
+
+impl std::fmt::Display for UnsupportedVersion {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "unsupported proof format version")
+    }
+}
+
+impl std::error::Error for UnsupportedVersion {}
+
+/// A [`TranscriptRead`] wrapper that rejects proofs larger than a byte budget.
+///
+/// Each read is checked against the remaining budget before it is performed, so a
+/// verifier never consumes more than `limit` bytes of proof, regardless of how many
+/// elements the proof claims to contain.
+#[derive(Debug, Clone)]
+pub struct LimitedRead<T> {
+    inner: T,
+    remaining: usize,
+}
+
+impl<T> LimitedRead<T> {
+    /// Wraps `inner`, allowing at most `limit` bytes of proof to be read.
+    pub fn new(inner: T, limit: usize) -> Self {
+        LimitedRead {
+            inner,
+            remaining: limit,
+        }
+    }
+
+    /// Returns the wrapped transcript.
+    pub fn into_inner(self) -> T {
+        self.inner
+    }
+
+    fn consume(&mut self, len: usize) -> io::Result<()> {
+        self.remaining = self
+            .remaining
+            .checked_sub(len)
+            .ok_or_else(|| io::Error::new(io::ErrorKind::Other, ProofTooLarge))?;
+        Ok(())
+    }
+}
+
+impl<C: CurveAffine, E: EncodedChallenge<C>, T: Transcript<C, E>> Transcript<C, E>
+    for LimitedRead<T>
+{
+    fn version_bytes() -> usize {
+        T::version_bytes()
+    }
+
+    fn squeeze_challenge(&mut self) -> E {
+        self.inner.squeeze_challenge()
+    }
+
+    fn current_challenge(&self) -> Option<E> {
+        self.inner.current_challenge()
+    }
+
+    fn common_point(&mut self, point: C) -> io::Result<()> {
+        self.inner.common_point(point)
+    }
+
+    fn common_scalar(&mut self, scalar: C::Scalar) -> io::Result<()> {
+        self.inner.common_scalar(scalar)
+    }
+
+    fn common_bytes(&mut self, data: &[u8]) -> io::Result<()> {
+        self.inner.common_bytes(data)
+    }
+
+    fn common_scalar_slice(&mut self, scalars: &[C::Scalar]) -> io::Result<()> {
+        self.inner.common_scalar_slice(scalars)
+    }
+
+    fn common_points(&mut self, points: &[C]) -> io::Result<()> {
+        self.inner.common_points(points)
+    }
+}
+
+impl<C: CurveAffine, E: EncodedChallenge<C>, T: TranscriptRead<C, E>> TranscriptRead<C, E>
+    for LimitedRead<T>
+{
+    fn read_version(&mut self) -> io::Result<u8> {
+        self.consume(Self::version_bytes())?;
+        self.inner.read_version()
+    }
+
+    fn read_point(&mut self) -> io::Result<C> {
+        self.consume(Self::point_bytes())?;
+        self.inner.read_point()
+    }
+
+    fn read_scalar(&mut self) -> io::Result<C::Scalar> {
+        self.consume(Self::scalar_bytes())?;
+        self.inner.read_scalar()
+    }
+}
+
+/// A writer that copies every byte written to `inner` into a secondary sink, for
+/// example to log a proof as it is created.
+///
+/// Pass it to a transcript such as [`Blake2bWrite::init`] in place of the proof's
+/// writer. The transcript is unaffected, so it produces the same proof and the same
+/// challenges as it would without the tee, and the sink receives exactly the bytes
+/// the transcript writes, whatever their encoding.
+#[derive(Debug, Clone)]
+pub struct TeeWriter<W: Write, S: Write> {
+    inner: W,
+    sink: S,
+}
+
+impl<W: Write, S: Write> TeeWriter<W, S> {
+    /// Wraps `inner`, copying the bytes written to it into `sink`.
+    pub fn new(inner: W, sink: S) -> Self {
+        TeeWriter { inner, sink }
+    }
+
+    /// Returns the wrapped writer and the sink.
+    pub fn into_inner(self) -> (W, S) {
+        (self.inner, self.sink)
+    }
+}
+
+impl<W: Write, S: Write> Write for TeeWriter<W, S> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let written = self.inner.write(buf)?;
+        self.sink.write_all(&buf[..written])?;
+        Ok(written)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()?;
+        self.sink.flush()
+    }
+}
+
+/// A transcript wrapper that returns the given challenges in order, instead of
+/// squeezing them from the wrapped transcript. Everything else is forwarded to the
+/// wrapped transcript.
+///
+/// This lets the algebraic checks of the prover and verifier be tested
+/// independently of Fiat-Shamir. Squeezing more challenges than were supplied
+/// panics.
+#[cfg(test)]
+#[derive(Debug)]
+pub(crate) struct FixedChallenges<'c, T, C: CurveAffine> {
+    inner: T,
+    challenges: std::slice::Iter<'c, C::Scalar>,
+    last_challenge: Option<Challenge255<C>>,
+}
+
+#[cfg(test)]
+impl<'c, T, C: CurveAffine> FixedChallenges<'c, T, C> {
+    /// Wraps `inner`, squeezing `challenges` in order.
+    pub(crate) fn new(inner: T, challenges: &'c [C::Scalar]) -> Self {
+        FixedChallenges {
+            inner,
+            challenges: challenges.iter(),
+            last_challenge: None,
+        }
+    }
+
+    /// Returns the wrapped transcript.
+    pub(crate) fn into_inner(self) -> T {
+        self.inner
+    }
+}
+
+#[cfg(test)]
+impl<'c, C: CurveAffine, T: Transcript<C, Challenge255<C>>> Transcript<C, Challenge255<C>>
+    for FixedChallenges<'c, T, C>
+{
+    fn version_bytes() -> usize {
+        T::version_bytes()
+    }
+
+    fn squeeze_challenge(&mut self) -> Challenge255<C> {
+        let scalar = self
+            .challenges
+            .next()
+            .expect("more challenges were squeezed than supplied");
+        let challenge = Challenge255::from_scalar(*scalar);
+        self.last_challenge = Some(challenge);
+        challenge
+    }
+
+    fn current_challenge(&self) -> Option<Challenge255<C>> {
+        self.last_challenge
+    }
+
+    fn common_point(&mut self, point: C) -> io::Result<()> {
+        self.last_challenge = None;
+        self.inner.common_point(point)
+    }
+
+    fn common_scalar(&mut self, scalar: C::Scalar) -> io::Result<()> {
+        self.last_challenge = None;
+        self.inner.common_scalar(scalar)
+    }
+
+    fn common_bytes(&mut self, data: &[u8]) -> io::Result<()> {
+        self.last_challenge = None;
+        self.inner.common_bytes(data)
+    }
+
+    fn common_scalar_slice(&mut self, scalars: &[C::Scalar]) -> io::Result<()> {
+        self.last_challenge = None;
+        self.inner.common_scalar_slice(scalars)
+    }
+
+    fn common_points(&mut self, points: &[C]) -> io::Result<()> {
+        self.last_challenge = None;
+        self.inner.common_points(points)
+    }
+}
+
+#[cfg(test)]
+impl<'c, C: CurveAffine, T: TranscriptRead<C, Challenge255<C>>> TranscriptRead<C, Challenge255<C>>
+    for FixedChallenges<'c, T, C>
+{
+    fn read_version(&mut self) -> io::Result<u8> {
+        self.last_challenge = None;
+        self.inner.read_version()
+    }
+
+    fn read_point(&mut self) -> io::Result<C> {
+        self.last_challenge = None;
+        self.inner.read_point()
+    }
+
+    fn read_scalar(&mut self) -> io::Result<C::Scalar> {
+        self.last_challenge = None;
+        self.inner.read_scalar()
+    }
+}
+
+#[cfg(test)]
+impl<'c, C: CurveAffine, T: TranscriptWrite<C, Challenge255<C>>> TranscriptWrite<C, Challenge255<C>>
+    for FixedChallenges<'c, T, C>
+{
+    fn write_version(&mut self, version: u8) -> io::Result<()> {
+        self.last_challenge = None;
+        self.inner.write_version(version)
+    }
+
+    fn write_point(&mut self, point: C) -> io::Result<()> {
+        self.last_challenge = None;
+        self.inner.write_point(point)
+    }
+
+    fn write_scalar(&mut self, scalar: C::Scalar) -> io::Result<()> {
+        self.last_challenge = None;
+        self.inner.write_scalar(scalar)
+    }
 }
 
 /// The scalar representation of a verifier challenge.
@@ -259,6 +847,20 @@ pub trait EncodedChallenge<C: CurveAffine> {
             _marker: PhantomData,
         }
     }
+
+    /// Get the `k`-th power of the scalar field element this challenge represents.
+    fn get_scalar_pow(&self, k: u64) -> C::Scalar {
+        self.get_scalar().pow_vartime(&[k])
+    }
+
+    /// Get the first `n` powers `[1, c, c^2, ..., c^{n-1}]` of the scalar field element
+    /// `c` this challenge represents.
+    fn powers(&self, n: usize) -> Vec<C::Scalar> {
+        let scalar = self.get_scalar();
+        std::iter::successors(Some(C::Scalar::one()), |power| Some(*power * scalar))
+            .take(n)
+            .collect()
+    }
 }
 
 /// A 255-bit challenge.
@@ -273,6 +875,21 @@ impl<C: CurveAffine> std::ops::Deref for Challenge255<C> {
     }
 }
 
+impl<C: CurveAffine> Challenge255<C> {
+    /// Reconstructs the challenge representing `scalar`, such as one that was stored
+    /// using [`EncodedChallenge::get_scalar`].
+    pub fn from_scalar(scalar: C::Scalar) -> Self {
+        Challenge255(
+            scalar
+                .to_repr()
+                .as_ref()
+                .try_into()
+                .expect("Scalar fits into 256 bits"),
+            PhantomData,
+        )
+    }
+}
+
 impl<C: CurveAffine> EncodedChallenge<C> for Challenge255<C> {
     type Input = [u8; 64];
 
@@ -293,6 +910,40 @@ impl<C: CurveAffine> EncodedChallenge<C> for Challenge255<C> {
     }
 }
 
+/// A single operation on a transcript, as run by [`replay`].
+#[derive(Clone, Debug)]
+pub enum TranscriptOp<C: CurveAffine> {
+    /// Absorb a curve point as a common input.
+    CommonPoint(C),
+    /// Absorb a scalar as a common input.
+    CommonScalar(C::Scalar),
+    /// Absorb a length-prefixed byte string as a common input.
+    CommonBytes(Vec<u8>),
+    /// Squeeze a challenge.
+    Squeeze,
+}
+
+/// Runs the given operations through a fresh [`Blake2bWrite`] transcript, and returns
+/// the challenges squeezed along the way.
+///
+/// This can be used to check that another implementation of the transcript (for example,
+/// in an external verifier) reproduces the same challenges for the same inputs.
+pub fn replay<C: CurveAffine>(ops: &[TranscriptOp<C>]) -> io::Result<Vec<C::Scalar>> {
+    let mut transcript = Blake2bWrite::<_, C, Challenge255<C>>::init(io::sink());
+
+    let mut challenges = vec![];
+    for op in ops {
+        match op {
+            TranscriptOp::CommonPoint(point) => transcript.common_point(*point)?,
+            TranscriptOp::CommonScalar(scalar) => transcript.common_scalar(*scalar)?,
+            TranscriptOp::CommonBytes(data) => transcript.common_bytes(data)?,
+            TranscriptOp::Squeeze => challenges.push(transcript.squeeze_challenge().get_scalar()),
+        }
+    }
+
+    Ok(challenges)
+}
+
 pub(crate) fn read_n_points<C: CurveAffine, E: EncodedChallenge<C>, T: TranscriptRead<C, E>>(
     transcript: &mut T,
     n: usize,
@@ -306,3 +957,684 @@ pub(crate) fn read_n_scalars<C: CurveAffine, E: EncodedChallenge<C>, T: Transcri
 ) -> io::Result<Vec<C::Scalar>> {
     (0..n).map(|_| transcript.read_scalar()).collect()
 }
+
+#[cfg(test)]
+mod tests {
+    use group::ff::{Field, PrimeField};
+    use group::prime::PrimeCurveAffine;
+    use group::Curve;
+    use pairing::bn256::{Fr, G1Affine};
+
+    use super::{
+        replay, Blake2bRead, Blake2bWrite, Challenge255, EncodedChallenge, LimitedRead, TeeWriter,
+        Transcript, TranscriptOp, TranscriptRead, TranscriptWrite,
+    };
+    use crate::{arithmetic::CurveAffine, dev::test_rng, plonk::Error};
+    use std::io;
+
+    #[test]
+    fn scalar_bytes_are_canonical() {
+        let mut rng = test_rng();
+        let scalar = Fr::random(&mut rng);
+
+        let mut writer = Blake2bWrite::<_, G1Affine, Challenge255<_>>::init(vec![]);
+        writer.write_scalar(scalar).unwrap();
+        let challenge = writer.squeeze_challenge().get_scalar();
+        let proof = writer.finalize();
+
+        // The proof contains the canonical little-endian encoding of the scalar.
+        assert_eq!(&proof[..], scalar.to_repr().as_ref());
+
+        // Reading the proof back yields the same scalar and the same transcript state.
+        let mut reader = Blake2bRead::<_, G1Affine, Challenge255<_>>::init(&proof[..]);
+        assert_eq!(reader.read_scalar().unwrap(), scalar);
+        assert_eq!(reader.squeeze_challenge().get_scalar(), challenge);
+
+        // Absorbing the scalar as a common input is equivalent to writing it.
+        let mut common = Blake2bWrite::<_, G1Affine, Challenge255<_>>::init(vec![]);
+        common.common_scalar(scalar).unwrap();
+        assert_eq!(common.squeeze_challenge().get_scalar(), challenge);
+    }
+
+    #[test]
+    fn challenge_powers() {
+        let mut transcript = Blake2bWrite::<_, G1Affine, Challenge255<_>>::init(vec![]);
+        let challenge = transcript.squeeze_challenge();
+        let y = challenge.get_scalar();
+
+        let powers = challenge.powers(5);
+        assert_eq!(powers.len(), 5);
+        for (k, power) in powers.iter().enumerate() {
+            assert_eq!(*power, y.pow_vartime(&[k as u64]));
+            assert_eq!(*power, challenge.get_scalar_pow(k as u64));
+        }
+
+        assert!(challenge.powers(0).is_empty());
+    }
+
+    #[test]
+    fn current_challenge() {
+        let mut transcript = Blake2bWrite::<_, G1Affine, Challenge255<_>>::init(vec![]);
+        let mut reference = transcript.clone();
+        assert!(transcript.current_challenge().is_none());
+
+        let challenge = transcript.squeeze_challenge().get_scalar();
+        reference.squeeze_challenge();
+        let current = transcript.current_challenge().unwrap().get_scalar();
+        assert_eq!(current, challenge);
+        assert_eq!(
+            transcript.current_challenge().unwrap().get_scalar(),
+            current
+        );
+
+        // Reading the cached challenge does not advance the transcript.
+        assert_eq!(
+            transcript.squeeze_challenge().get_scalar(),
+            reference.squeeze_challenge().get_scalar()
+        );
+
+        // Absorbing anything clears the cached challenge.
+        transcript.common_scalar(Fr::one()).unwrap();
+        assert!(transcript.current_challenge().is_none());
+    }
+
+    #[test]
+    fn limited_read() {
+        let mut rng = test_rng();
+        let scalars = [Fr::random(&mut rng), Fr::random(&mut rng)];
+
+        let mut writer = Blake2bWrite::<_, G1Affine, Challenge255<_>>::init(vec![]);
+        for scalar in scalars.iter() {
+            writer.write_scalar(*scalar).unwrap();
+        }
+        let proof = writer.finalize();
+
+        // Reading within the budget succeeds.
+        let mut reader = LimitedRead::new(
+            Blake2bRead::<_, G1Affine, Challenge255<_>>::init(&proof[..]),
+            proof.len(),
+        );
+        for scalar in scalars.iter() {
+            assert_eq!(reader.read_scalar().unwrap(), *scalar);
+        }
+
+        // Reading past it fails before anything is consumed from the proof.
+        let mut reader = LimitedRead::new(
+            Blake2bRead::<_, G1Affine, Challenge255<_>>::init(&proof[..]),
+            proof.len() - 1,
+        );
+        assert_eq!(reader.read_scalar().unwrap(), scalars[0]);
+        let err = reader.read_scalar().unwrap_err();
+        assert!(matches!(Error::from(err), Error::ProofTooLarge));
+    }
+
+    #[test]
+    fn common_bytes() {
+        let challenge = |blobs: &[&[u8]]| {
+            let mut writer = Blake2bWrite::<_, G1Affine, Challenge255<_>>::init(vec![]);
+            let mut reader = Blake2bRead::<_, G1Affine, Challenge255<_>>::init(&b""[..]);
+            for blob in blobs {
+                writer.common_bytes(blob).unwrap();
+                reader.common_bytes(blob).unwrap();
+            }
+
+            let challenge = writer.squeeze_challenge().get_scalar();
+            assert_eq!(reader.squeeze_challenge().get_scalar(), challenge);
+            challenge
+        };
+
+        assert_eq!(challenge(&[b"message"]), challenge(&[b"message"]));
+        assert_ne!(challenge(&[b"message"]), challenge(&[b"massage"]));
+        assert_ne!(challenge(&[b"message"]), challenge(&[]));
+
+        // The length prefix separates consecutive blobs.
+        assert_ne!(challenge(&[b"mess", b"age"]), challenge(&[b"message"]));
+        assert_ne!(challenge(&[b"mess", b"age"]), challenge(&[b"me", b"ssage"]));
+    }
+
+    #[test]
+    fn replay_golden() {
+        let ops = [3u64, 2, 8, 57]
+            .iter()
+            .map(|v| TranscriptOp::<G1Affine>::CommonScalar(Fr::from(*v)))
+            .chain(vec![TranscriptOp::Squeeze, TranscriptOp::Squeeze])
+            .collect::<Vec<_>>();
+
+        let challenges = replay(&ops).unwrap();
+        assert_eq!(challenges.len(), 2);
+        assert_eq!(
+            challenges[0].to_repr().as_ref(),
+            &[
+                60, 171, 23, 37, 33, 136, 226, 234, 80, 160, 162, 24, 4, 212, 106, 235, 52, 180,
+                182, 4, 44, 171, 236, 58, 120, 235, 117, 76, 190, 122, 1, 41,
+            ][..]
+        );
+        assert_eq!(
+            challenges[1].to_repr().as_ref(),
+            &[
+                53, 171, 92, 67, 225, 183, 128, 98, 103, 36, 14, 28, 69, 144, 186, 92, 28, 174,
+                210, 156, 212, 181, 101, 255, 33, 146, 207, 250, 63, 232, 56, 10,
+            ][..]
+        );
+    }
+
+    #[test]
+    fn challenges_are_deterministic() {
+        /// Absorbs a fixed sequence covering every kind of common input, and returns
+        /// the bytes of the challenges squeezed along the way.
+        fn absorb<T: Transcript<G1Affine, Challenge255<G1Affine>>>(
+            mut transcript: T,
+        ) -> Vec<[u8; 32]> {
+            transcript.common_scalar(Fr::from(3)).unwrap();
+            transcript.common_bytes(b"halo2").unwrap();
+            transcript
+                .common_scalar_slice(&[Fr::from(1), Fr::from(2), Fr::from(3)])
+                .unwrap();
+            let first = transcript.squeeze_challenge();
+
+            let g = G1Affine::generator();
+            transcript.common_point(g).unwrap();
+            transcript
+                .common_points(&[g, (g * Fr::from(2)).to_affine()])
+                .unwrap();
+            transcript.common_challenge(&first).unwrap();
+            let second = transcript.squeeze_challenge();
+
+            vec![*first, *second]
+        }
+
+        // Every transcript implementation must derive the same challenges from the
+        // same inputs.
+        let transcripts: [fn() -> Vec<[u8; 32]>; 4] = [
+            || absorb(Blake2bWrite::<_, G1Affine, Challenge255<_>>::init(vec![])),
+            || absorb(Blake2bRead::<_, G1Affine, Challenge255<_>>::init(&[][..])),
+            || {
+                absorb(LimitedRead::new(
+                    Blake2bRead::<_, G1Affine, Challenge255<_>>::init(&[][..]),
+                    0,
+                ))
+            },
+            || {
+                absorb(Blake2bWrite::<_, G1Affine, Challenge255<_>>::init(
+                    TeeWriter::new(vec![], vec![]),
+                ))
+            },
+        ];
+
+        // The first challenge only depends on scalars and bytes, whose encodings are
+        // fixed, so it is pinned to guard against nondeterminism across processes.
+        let expected = transcripts[0]();
+        assert_eq!(
+            expected[0],
+            [
+                217, 49, 239, 147, 169, 2, 39, 231, 46, 60, 199, 200, 20, 100, 174, 94, 172, 205,
+                176, 15, 195, 237, 228, 75, 69, 154, 234, 190, 97, 100, 170, 43,
+            ]
+        );
+
+        // Repeat the absorptions with the heap shifted around between runs, so that
+        // the transcript state and its inputs land at different addresses, and on
+        // other threads, so that they also land on different stacks.
+        let mut allocations: Vec<Vec<u8>> = vec![];
+        for i in 0..64 {
+            allocations.push(vec![i as u8; 1 + (i * 97) % 4096]);
+            if i % 4 == 3 {
+                allocations.remove(i % allocations.len());
+            }
+            for transcript in transcripts.iter() {
+                assert_eq!(transcript(), expected);
+            }
+        }
+        for transcript in transcripts.iter() {
+            assert_eq!(std::thread::spawn(*transcript).join().unwrap(), expected);
+        }
+    }
+
+    #[test]
+    fn common_scalar_slice() {
+        let mut rng = test_rng();
+        let scalars = (0..4).map(|_| Fr::random(&mut rng)).collect::<Vec<_>>();
+
+        let mut writer = Blake2bWrite::<_, G1Affine, Challenge255<_>>::init(vec![]);
+        let mut reader = Blake2bRead::<_, G1Affine, Challenge255<_>>::init(&[][..]);
+        writer.common_scalar_slice(&scalars).unwrap();
+        reader.common_scalar_slice(&scalars).unwrap();
+        let challenge = writer.squeeze_challenge().get_scalar();
+        assert_eq!(reader.squeeze_challenge().get_scalar(), challenge);
+
+        // Absorbing the same scalars one at a time is framed differently.
+        let mut elementwise = Blake2bWrite::<_, G1Affine, Challenge255<_>>::init(vec![]);
+        for scalar in &scalars {
+            elementwise.common_scalar(*scalar).unwrap();
+        }
+        assert_ne!(elementwise.squeeze_challenge().get_scalar(), challenge);
+
+        // The length prefix separates consecutive slices.
+        let mut split = Blake2bWrite::<_, G1Affine, Challenge255<_>>::init(vec![]);
+        split.common_scalar_slice(&scalars[..2]).unwrap();
+        split.common_scalar_slice(&scalars[2..]).unwrap();
+        assert_ne!(split.squeeze_challenge().get_scalar(), challenge);
+    }
+
+    #[test]
+    fn common_points_block() {
+        let mut rng = test_rng();
+        let points = (0..4)
+            .map(|_| (G1Affine::generator() * Fr::random(&mut rng)).to_affine())
+            .collect::<Vec<_>>();
+
+        let mut writer = Blake2bWrite::<_, G1Affine, Challenge255<_>>::init(vec![]);
+        let mut reader = Blake2bRead::<_, G1Affine, Challenge255<_>>::init(&[][..]);
+        writer.common_points(&points).unwrap();
+        reader.common_points(&points).unwrap();
+        let challenge = writer.squeeze_challenge().get_scalar();
+        assert_eq!(reader.squeeze_challenge().get_scalar(), challenge);
+
+        // Absorbing the same points one at a time is framed differently.
+        let mut elementwise = Blake2bWrite::<_, G1Affine, Challenge255<_>>::init(vec![]);
+        for point in points.iter() {
+            elementwise.common_point(*point).unwrap();
+        }
+        assert_ne!(elementwise.squeeze_challenge().get_scalar(), challenge);
+
+        // The length prefix separates consecutive slices.
+        let mut split = Blake2bWrite::<_, G1Affine, Challenge255<_>>::init(vec![]);
+        split.common_points(&points[..2]).unwrap();
+        split.common_points(&points[2..]).unwrap();
+        assert_ne!(split.squeeze_challenge().get_scalar(), challenge);
+
+        // The point at infinity cannot be absorbed as part of a slice either.
+        let mut writer = Blake2bWrite::<_, G1Affine, Challenge255<_>>::init(vec![]);
+        assert!(writer
+            .common_points(&[points[0], G1Affine::identity()])
+            .is_err());
+    }
+
+    #[test]
+    fn common_challenge() {
+        let squeeze = |reabsorb: bool| {
+            let mut transcript = Blake2bWrite::<_, G1Affine, Challenge255<_>>::init(vec![]);
+            transcript.common_scalar(Fr::from(1)).unwrap();
+            let y = transcript.squeeze_challenge();
+            if reabsorb {
+                transcript.common_challenge(&y).unwrap();
+            }
+            transcript.squeeze_challenge().get_scalar()
+        };
+        assert_ne!(squeeze(true), squeeze(false));
+
+        // Re-absorbing a challenge is the same as absorbing its scalar.
+        let mut transcript = Blake2bWrite::<_, G1Affine, Challenge255<_>>::init(vec![]);
+        transcript.common_scalar(Fr::from(1)).unwrap();
+        let y = transcript.squeeze_challenge().get_scalar();
+        transcript.common_scalar(y).unwrap();
+        assert_eq!(transcript.squeeze_challenge().get_scalar(), squeeze(true));
+    }
+
+    #[test]
+    fn challenge_from_scalar() {
+        let mut transcript = Blake2bWrite::<_, G1Affine, Challenge255<_>>::init(vec![]);
+        transcript.common_scalar(Fr::from(1)).unwrap();
+        let challenge = transcript.squeeze_challenge();
+
+        let stored = challenge.get_scalar();
+        let restored = Challenge255::<G1Affine>::from_scalar(stored);
+        assert_eq!(restored.get_scalar(), stored);
+        assert_eq!(*restored, *challenge);
+    }
+
+    #[test]
+    fn tee_writer() {
+        let mut rng = test_rng();
+        let point = G1Affine::generator();
+        let scalar = Fr::random(&mut rng);
+
+        fn write<T: TranscriptWrite<G1Affine, Challenge255<G1Affine>>>(
+            transcript: &mut T,
+            point: G1Affine,
+            scalar: Fr,
+        ) -> Vec<Fr> {
+            transcript.write_version(1).unwrap();
+            transcript.write_point(point).unwrap();
+            let x = transcript.squeeze_challenge().get_scalar();
+            transcript.common_scalar(x).unwrap();
+            transcript.write_scalar(scalar).unwrap();
+            let y = transcript.squeeze_challenge().get_scalar();
+            vec![x, y]
+        }
+
+        let mut plain = Blake2bWrite::<_, G1Affine, Challenge255<_>>::init(vec![]);
+        let plain_challenges = write(&mut plain, point, scalar);
+        let plain_proof = plain.finalize();
+
+        let mut tee =
+            Blake2bWrite::<_, G1Affine, Challenge255<_>>::init(TeeWriter::new(vec![], vec![]));
+        let tee_challenges = write(&mut tee, point, scalar);
+        let (proof, captured) = tee.finalize().into_inner();
+
+        assert_eq!(tee_challenges, plain_challenges);
+        assert_eq!(proof, plain_proof);
+        assert_eq!(captured, proof);
+    }
+
+    #[test]
+    fn byte_layout() {
+        type Writer = Blake2bWrite<Vec<u8>, G1Affine, Challenge255<G1Affine>>;
+        type Reader<'a> = Blake2bRead<&'a [u8], G1Affine, Challenge255<G1Affine>>;
+
+        assert_eq!(Writer::point_bytes(), 32);
+        assert_eq!(Writer::scalar_bytes(), 32);
+        assert_eq!(Reader::point_bytes(), 32);
+        assert_eq!(Reader::scalar_bytes(), 32);
+
+        let (points, scalars) = (3usize, 5u64);
+        let mut writer = Writer::init(vec![]);
+        for _ in 0..points {
+            writer.write_point(G1Affine::generator()).unwrap();
+        }
+        for i in 0..scalars {
+            writer.write_scalar(Fr::from(i)).unwrap();
+        }
+        let proof = writer.finalize();
+        assert_eq!(
+            proof.len(),
+            points * Writer::point_bytes() + scalars as usize * Writer::scalar_bytes()
+        );
+    }
+
+    /// A transcript that only implements the required methods, forwarding them to
+    /// `T`, so that every provided method uses its default body.
+    struct Minimal<T>(T);
+
+    impl<C: CurveAffine, E: EncodedChallenge<C>, T: Transcript<C, E>> Transcript<C, E> for Minimal<T> {
+        fn squeeze_challenge(&mut self) -> E {
+            self.0.squeeze_challenge()
+        }
+
+        fn common_point(&mut self, point: C) -> io::Result<()> {
+            self.0.common_point(point)
+        }
+
+        fn common_scalar(&mut self, scalar: C::Scalar) -> io::Result<()> {
+            self.0.common_scalar(scalar)
+        }
+    }
+
+    impl<C: CurveAffine, E: EncodedChallenge<C>, T: TranscriptRead<C, E>> TranscriptRead<C, E>
+        for Minimal<T>
+    {
+        fn read_point(&mut self) -> io::Result<C> {
+            self.0.read_point()
+        }
+
+        fn read_scalar(&mut self) -> io::Result<C::Scalar> {
+            self.0.read_scalar()
+        }
+    }
+
+    impl<C: CurveAffine, E: EncodedChallenge<C>, T: TranscriptWrite<C, E>> TranscriptWrite<C, E>
+        for Minimal<T>
+    {
+        fn write_point(&mut self, point: C) -> io::Result<()> {
+            self.0.write_point(point)
+        }
+
+        fn write_scalar(&mut self, scalar: C::Scalar) -> io::Result<()> {
+            self.0.write_scalar(scalar)
+        }
+    }
+
+    #[test]
+    fn default_methods() {
+        let g = G1Affine::generator();
+        let scalars = [Fr::from(1), Fr::from(2)];
+
+        let mut writer = Minimal(Blake2bWrite::<_, G1Affine, Challenge255<_>>::init(vec![]));
+        writer.write_version(7).unwrap();
+        writer.common_bytes(b"halo2 transcript").unwrap();
+        writer.common_scalar_slice(&scalars).unwrap();
+        writer.common_points(&[g, g]).unwrap();
+        assert!(writer.current_challenge().is_none());
+        let challenge = writer.squeeze_challenge().get_scalar();
+        let proof = writer.0.finalize();
+
+        let mut reader = Minimal(Blake2bRead::<_, G1Affine, Challenge255<_>>::init(
+            &proof[..],
+        ));
+        assert_eq!(reader.read_version().unwrap(), 7);
+        reader.common_bytes(b"halo2 transcript").unwrap();
+        reader.common_scalar_slice(&scalars).unwrap();
+        reader.common_points(&[g, g]).unwrap();
+        assert_eq!(reader.squeeze_challenge().get_scalar(), challenge);
+
+        let err = writer
+            .common_points(&[g, G1Affine::identity()])
+            .unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "point at index 1: cannot write points at infinity to the transcript"
+        );
+    }
+
+    #[test]
+    fn limited_read_version() {
+        // A transcript using the default `write_version` spends a whole scalar on it.
+        let mut writer = Minimal(Blake2bWrite::<_, G1Affine, Challenge255<_>>::init(vec![]));
+        writer.write_version(1).unwrap();
+        let proof = writer.0.finalize();
+        assert_eq!(proof.len(), 32);
+
+        let mut reader = LimitedRead::new(
+            Minimal(Blake2bRead::<_, G1Affine, Challenge255<_>>::init(
+                &proof[..],
+            )),
+            proof.len() - 1,
+        );
+        let err = reader.read_version().unwrap_err();
+        assert!(matches!(Error::from(err), Error::ProofTooLarge));
+
+        let mut reader = LimitedRead::new(
+            Minimal(Blake2bRead::<_, G1Affine, Challenge255<_>>::init(
+                &proof[..],
+            )),
+            proof.len(),
+        );
+        assert_eq!(reader.read_version().unwrap(), 1);
+
+        // The BLAKE2b transcript writes a single byte.
+        let mut writer = Blake2bWrite::<_, G1Affine, Challenge255<_>>::init(vec![]);
+        writer.write_version(1).unwrap();
+        let proof = writer.finalize();
+        let mut reader = LimitedRead::new(
+            Blake2bRead::<_, G1Affine, Challenge255<_>>::init(&proof[..]),
+            1,
+        );
+        assert_eq!(reader.read_version().unwrap(), 1);
+    }
+
+    #[test]
+    fn default_read_version_rejects_wide_scalars() {
+        let mut writer = Minimal(Blake2bWrite::<_, G1Affine, Challenge255<_>>::init(vec![]));
+        writer.write_scalar(Fr::from(0x100 + 1)).unwrap();
+        let proof = writer.0.finalize();
+
+        let mut reader = Minimal(Blake2bRead::<_, G1Affine, Challenge255<_>>::init(
+            &proof[..],
+        ));
+        let err = reader.read_version().unwrap_err();
+        assert!(matches!(
+            Error::from(err),
+            Error::UnsupportedProofVersion(1)
+        ));
+    }
+
+    #[test]
+    fn common_points_identity() {
+        let g = G1Affine::generator();
+        let mut points = vec![g; 4];
+
+        let mut transcript = Blake2bWrite::<_, G1Affine, Challenge255<_>>::init(vec![]);
+        assert!(transcript.common_points(&points).is_ok());
+
+        points[2] = G1Affine::identity();
+        let mut transcript = Blake2bWrite::<_, G1Affine, Challenge255<_>>::init(vec![]);
+        let err = transcript.common_points(&points).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "point at index 2: cannot write points at infinity to the transcript"
+        );
+    }
+
+    #[test]
+    fn init_with_domain() {
+        let challenge = |domain: Option<&[u8]>| {
+            let mut writer = match domain {
+                Some(domain) => {
+                    Blake2bWrite::<_, G1Affine, Challenge255<_>>::init_with_domain(vec![], domain)
+                }
+                None => Blake2bWrite::<_, G1Affine, Challenge255<_>>::init(vec![]),
+            };
+            writer.write_scalar(Fr::from(42)).unwrap();
+            let challenge = writer.squeeze_challenge().get_scalar();
+            let proof = writer.finalize();
+
+            // The verifier must use the same domain to reproduce the challenge.
+            let mut reader = match domain {
+                Some(domain) => Blake2bRead::<_, G1Affine, Challenge255<_>>::init_with_domain(
+                    &proof[..],
+                    domain,
+                ),
+                None => Blake2bRead::<_, G1Affine, Challenge255<_>>::init(&proof[..]),
+            };
+            reader.read_scalar().unwrap();
+            assert_eq!(reader.squeeze_challenge().get_scalar(), challenge);
+
+            challenge
+        };
+
+        assert_eq!(
+            challenge(Some(&b"protocol a"[..])),
+            challenge(Some(&b"protocol a"[..]))
+        );
+        assert_ne!(
+            challenge(Some(&b"protocol a"[..])),
+            challenge(Some(&b"protocol b"[..]))
+        );
+        assert_ne!(challenge(Some(&b"protocol a"[..])), challenge(None));
+        assert_ne!(challenge(Some(&b""[..])), challenge(None));
+    }
+}
+
+#[cfg(test)]
+mod proptests {
+    use std::collections::HashMap;
+
+    use group::{ff::PrimeField, Curve, Group};
+    use pairing::{
+        arithmetic::{Coordinates, CurveAffine},
+        bn256::{Fr, G1Affine, G1},
+    };
+    use proptest::{collection::vec, prelude::*};
+
+    use super::{
+        Blake2bWrite, Challenge255, EncodedChallenge, Transcript, BLAKE2B_PREFIX_POINT,
+        BLAKE2B_PREFIX_SCALAR,
+    };
+
+    /// A single absorption into the transcript.
+    #[derive(Clone, Debug, PartialEq)]
+    enum Absorption {
+        Scalar(Fr),
+        Point(G1Affine),
+        Bytes(Vec<u8>),
+        ScalarSlice(Vec<Fr>),
+        PointSlice(Vec<G1Affine>),
+    }
+
+    fn point_encoding(point: G1Affine) -> Vec<u8> {
+        let coords: Coordinates<G1Affine> = Option::from(point.coordinates()).unwrap();
+        let mut bytes = coords.x().to_repr().as_ref().to_vec();
+        bytes.extend_from_slice(coords.y().to_repr().as_ref());
+        bytes
+    }
+
+    /// Absorptions derived from `seed` whose encodings are numerically related, so
+    /// that a transcript without domain separation would confuse them.
+    fn related(seed: u64) -> Vec<Absorption> {
+        let scalar = Fr::from(seed);
+        let point = (G1::generator() * Fr::from(seed + 1)).to_affine();
+        let scalar_bytes = scalar.to_repr().as_ref().to_vec();
+        let point_bytes = point_encoding(point);
+
+        vec![
+            Absorption::Scalar(scalar),
+            Absorption::Point(point),
+            Absorption::Bytes(scalar_bytes.clone()),
+            Absorption::Bytes(point_bytes.clone()),
+            Absorption::Bytes(point_bytes[..32].to_vec()),
+            Absorption::ScalarSlice(vec![scalar]),
+            Absorption::PointSlice(vec![point]),
+            // Bytes that spell out the prefixed encoding of another absorption.
+            Absorption::Bytes([&[BLAKE2B_PREFIX_SCALAR][..], &scalar_bytes].concat()),
+            Absorption::Bytes([&[BLAKE2B_PREFIX_POINT][..], &point_bytes].concat()),
+        ]
+    }
+
+    fn challenge(absorptions: &[Absorption]) -> Fr {
+        let mut transcript = Blake2bWrite::<_, G1Affine, Challenge255<_>>::init(vec![]);
+        for absorption in absorptions {
+            match absorption {
+                Absorption::Scalar(scalar) => transcript.common_scalar(*scalar),
+                Absorption::Point(point) => transcript.common_point(*point),
+                Absorption::Bytes(bytes) => transcript.common_bytes(bytes),
+                Absorption::ScalarSlice(scalars) => transcript.common_scalar_slice(scalars),
+                Absorption::PointSlice(points) => transcript.common_points(points),
+            }
+            .unwrap();
+        }
+        transcript.squeeze_challenge().get_scalar()
+    }
+
+    #[test]
+    fn related_absorptions_do_not_collide() {
+        let singles: Vec<_> = (0..3).flat_map(related).collect();
+        let sequences = std::iter::once(vec![])
+            .chain(singles.iter().map(|a| vec![a.clone()]))
+            .chain(
+                singles
+                    .iter()
+                    .flat_map(|a| singles.iter().map(move |b| vec![a.clone(), b.clone()])),
+            );
+
+        let mut seen: HashMap<Vec<u8>, Vec<Absorption>> = HashMap::new();
+        for sequence in sequences {
+            let key = challenge(&sequence).to_repr().as_ref().to_vec();
+            // Some seeds derive identical absorptions (the generator's x-coordinate
+            // is 1), which must of course agree.
+            if let Some(other) = seen.insert(key, sequence.clone()) {
+                assert_eq!(other, sequence, "distinct absorptions collide");
+            }
+        }
+    }
+
+    prop_compose! {
+        /// Use few seeds, so that related absorptions are drawn often.
+        fn arb_absorption()(
+            absorption in (0u64..4).prop_flat_map(|seed| proptest::sample::select(related(seed))),
+        ) -> Absorption {
+            absorption
+        }
+    }
+
+    proptest! {
+        #[test]
+        fn distinct_absorptions_have_distinct_challenges(
+            a in vec(arb_absorption(), 0..4),
+            b in vec(arb_absorption(), 0..4),
+        ) {
+            if a != b {
+                prop_assert_ne!(challenge(&a), challenge(&b));
+            }
+        }
+    }
+}
@@ -452,6 +452,25 @@ fn plonk_api() {
         .expect("proof generation should not fail");
         let proof: Vec<u8> = transcript.finalize();
 
+        // Check that a proof with an unknown format version is rejected.
+        {
+            let mut bad_proof = proof.clone();
+            bad_proof[0] = bad_proof[0].wrapping_add(1);
+
+            let strategy = SingleVerifier::new(&params_verifier);
+            let mut transcript = Blake2bRead::<_, _, Challenge255<_>>::init(&bad_proof[..]);
+            assert_matches!(
+                verify_proof(
+                    &params_verifier,
+                    pk.get_vk(),
+                    strategy,
+                    &[&[&pubinputs[..]], &[&pubinputs[..]]],
+                    &mut transcript,
+                ),
+                Err(Error::UnsupportedProofVersion(version)) if version == bad_proof[0]
+            );
+        }
+
         // Test single-verifier strategy.
         {
             let strategy = SingleVerifier::new(&params_verifier);